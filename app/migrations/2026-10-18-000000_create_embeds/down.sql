@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS embeds_message_id;
+
+DROP TABLE embeds;
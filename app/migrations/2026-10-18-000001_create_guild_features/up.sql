@@ -0,0 +1,10 @@
+-- Commands, listeners and integrations toggled on each guild, features without a row are enabled
+
+CREATE TABLE guild_features (
+  guild_id BIGINT NOT NULL,
+  kind VARCHAR(20) NOT NULL,
+  name VARCHAR(50) NOT NULL,
+  enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (guild_id, kind, name)
+);
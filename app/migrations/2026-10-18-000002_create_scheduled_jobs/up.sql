@@ -0,0 +1,12 @@
+-- Pending background jobs, resumed by the scheduler after a restart
+
+CREATE TABLE scheduled_jobs (
+  id BIGSERIAL PRIMARY KEY,
+  job VARCHAR(50) NOT NULL,
+  kind VARCHAR(20) NOT NULL,
+  expression VARCHAR(100) NOT NULL DEFAULT '',
+  arguments JSONB NOT NULL DEFAULT '{}',
+  next_run TIMESTAMP WITH TIME ZONE NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1 @@
+ALTER TABLE embeds DROP COLUMN locale;
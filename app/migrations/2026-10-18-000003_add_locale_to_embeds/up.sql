@@ -0,0 +1,3 @@
+-- Locale the embeds are built on, so the updates keep the language of the guild
+
+ALTER TABLE embeds ADD COLUMN locale VARCHAR(10) NOT NULL DEFAULT 'en-US';
@@ -0,0 +1,3 @@
+DELETE FROM polls WHERE embed_message_id IS NULL;
+
+ALTER TABLE polls ALTER COLUMN embed_message_id SET NOT NULL;
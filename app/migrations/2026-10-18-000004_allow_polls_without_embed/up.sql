@@ -0,0 +1,3 @@
+-- Polls are saved before their setup embed is sent, the message id is set after sending it
+
+ALTER TABLE polls ALTER COLUMN embed_message_id DROP NOT NULL;
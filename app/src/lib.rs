@@ -19,7 +19,6 @@ impl TypeMapKey for HttpKey {
     type Value = HttpClient;
 }
 
-// TODO: implementar algum jeito para que cada servidor tenha seu próprio idioma e não alterar o idioma de todos os servidores
 i18n!("public/locales", fallback = "en-US");
 
 pub mod modules;
@@ -1,39 +1,193 @@
 include!("lib.rs");
 
 use bostil_core::{
-    arguments::ArgumentsLevel, commands::CommandContext, runners::runners::CommandResponse,
+    arguments::InvocationContext, dispatchers::ListenerDispatcher, embeds::EMBEDS,
+    errors::BostilError, locales::LOCALES, runners::runners::CommandResponse, scheduler::SCHEDULER,
 };
+use rust_i18n::t;
 use serenity::{
-    all::{Command, GatewayIntents, GuildId, Interaction, Message, Ready, VoiceState},
+    all::{CommandInteraction, GatewayIntents, Interaction, Message, Reaction, Ready, VoiceState},
     async_trait,
-    builder::EditInteractionResponse,
+    builder::{
+        CreateAutocompleteResponse, CreateInteractionResponse, CreateInteractionResponseMessage,
+    },
     client::Context,
-    framework::StandardFramework,
     gateway::ActivityData,
     prelude::EventHandler,
     Client,
 };
 use songbird::SerenityInit;
-use std::env;
+use std::{env, time::Duration};
 use tracing::{debug, error, info, warn};
 
 use crate::modules::{
-    app::listeners::voice::join_channel,
     core::actions,
     core::helpers::{establish_connection, MIGRATIONS},
 };
 
 struct Handler;
 
+/// Time the runner of a slash command has to respond before its interaction is deferred (Discord waits for 3 seconds)
+const DEFER_AFTER: Duration = Duration::from_millis(2500);
+
+/// Dispatcher of the collected listeners (and integrations)
+fn listener_dispatcher() -> Option<ListenerDispatcher> {
+    match LISTENER_COLLECTOR.lock() {
+        Ok(collector) => Some(ListenerDispatcher::new(collector.clone())),
+        Err(why) => {
+            error!("Cannot get listener collector: {}", why);
+            None
+        }
+    }
+}
+
+/// Respond to a deferred slash command with an error
+///
+/// User-facing errors are shown as their message on the locale of the guild (or of the user),
+/// the others are logged and a generic message is shown
+async fn respond_error(
+    ctx: &Context,
+    command: &CommandInteraction,
+    why: BostilError,
+    deferred: bool,
+) {
+    let locale = LOCALES.resolve(command.guild_id, Some(&command.locale));
+
+    let content = match &why {
+        BostilError::UserFacing { key, arguments } => arguments.iter().fold(
+            t!(key.as_str(), locale = &locale).to_string(),
+            |content, (name, value)| content.replace(&format!("%{{{}}}", name), value),
+        ),
+        _ => {
+            error!(
+                "Command {} ({}) failed: {}",
+                command.data.name, command.id, why
+            );
+
+            t!("commands.error", locale = &locale).to_string()
+        }
+    };
+
+    respond(
+        ctx,
+        command,
+        CommandResponse::String(content).ephemeral(),
+        deferred,
+    )
+    .await
+}
+
+/// Respond to the slash command with the response of its runner, followed by its follow-up messages
+///
+/// A deferred interaction is edited, except for ephemeral responses: the deferred response can't become ephemeral,
+/// so it's replaced by an ephemeral follow-up
+async fn respond(
+    ctx: &Context,
+    command: &CommandInteraction,
+    response: CommandResponse,
+    deferred: bool,
+) {
+    let (response, followups) = response.split_followups();
+
+    let responded = match response {
+        CommandResponse::None => {
+            debug!("Deleting slash command: {}", command.data.name);
+
+            // the interaction must be acknowledged before its response is deleted
+            match deferred {
+                true => command.delete_response(&ctx.http).await,
+                false => match command.defer(&ctx.http).await {
+                    Ok(_) => command.delete_response(&ctx.http).await,
+                    Err(why) => Err(why),
+                },
+            }
+        }
+        CommandResponse::Modal(modal) if !deferred => {
+            command
+                .create_response(&ctx.http, CreateInteractionResponse::Modal(modal))
+                .await
+        }
+        CommandResponse::Modal(_) => {
+            error!(
+                "Command {} responded with a modal after being deferred",
+                command.data.name
+            );
+
+            return Box::pin(respond_error(
+                ctx,
+                command,
+                BostilError::validation("Modal response after the interaction was deferred"),
+                deferred,
+            ))
+            .await;
+        }
+        CommandResponse::Message(_) | CommandResponse::Paginated(_) if !deferred => {
+            // these responses can only edit the deferred response
+            match command.defer(&ctx.http).await {
+                Ok(_) => return Box::pin(respond(ctx, command, response, true)).await,
+                Err(why) => Err(why),
+            }
+        }
+        CommandResponse::Paginated(paginator) => {
+            paginator.edit_response(ctx, command).await.map(|_| ())
+        }
+        response if response.is_ephemeral() && deferred => {
+            if let Err(why) = command.delete_response(&ctx.http).await {
+                error!("Cannot delete slash command response: {}", why);
+            }
+
+            command
+                .create_followup(&ctx.http, response.to_followup())
+                .await
+                .map(|_| ())
+        }
+        response if deferred => command
+            .edit_response(&ctx.http, response.to_edit_response())
+            .await
+            .map(|_| ()),
+        response => {
+            command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(response.to_response_message()),
+                )
+                .await
+        }
+    };
+
+    if let Err(why) = responded {
+        error!("Cannot respond to slash command: {}", why);
+    }
+
+    for followup in followups {
+        if let Err(why) = command.create_followup(&ctx.http, followup).await {
+            error!("Cannot send follow-up of slash command: {}", why);
+        }
+    }
+}
+
 #[async_trait]
 impl EventHandler for Handler {
     // ---------
     // On receive message
     // ---------
-    async fn message(&self, _ctx: Context, msg: Message) {
+    async fn message(&self, ctx: Context, msg: Message) {
         debug!("Received message from User: {:#?}", msg.author.name);
 
-        // TODO: use integrations and listeners collectors instead of hardcoding
+        if let Some(dispatcher) = listener_dispatcher() {
+            dispatcher.message(&ctx, &msg).await;
+        }
+    }
+
+    // ---------
+    // On add reaction to a message
+    // ---------
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        debug!("Received reaction: {:#?}", reaction.emoji);
+
+        if let Some(dispatcher) = listener_dispatcher() {
+            dispatcher.reaction_add(&ctx, &reaction).await;
+        }
     }
 
     // ---------
@@ -50,43 +204,43 @@ impl EventHandler for Handler {
             }
         };
 
-        let global_commands = collector
-            .clone()
-            .get_fingerprints(Some(CommandContext::Global));
-        let guild_commands = collector
-            .clone()
-            .get_fingerprints(Some(CommandContext::Guild));
-
-        if global_commands.len() == 0 && guild_commands.len() == 0 {
+        if collector.commands.is_empty() {
             warn!("No commands to register");
-            return;
         }
 
-        if let Err(why) = Command::set_global_commands(&ctx.http, global_commands.clone()).await {
-            error!("Cannot register global slash commands: {}", why);
-            return;
-        }
-
-        info!("Registered global slash commands");
-        debug!(
-            "Registered {:#?} global commands",
-            global_commands.clone().len()
-        );
-        debug!(
-            "Registered {:#?} guild commands",
-            guild_commands.clone().len()
-        );
-
-        for guild in ready.guilds.iter() {
-            let commands = GuildId::set_commands(guild.id, &ctx.http, guild_commands.clone()).await;
-
-            if let Err(why) = commands {
-                error!("Cannot register slash commands: {}", why);
+        // on dry-run the changes are only logged
+        let dry_run = env::var("COMMANDS_DRY_RUN").is_ok_and(|value| value == "true");
+
+        let scopes = std::iter::once(None).chain(ready.guilds.iter().map(|guild| Some(guild.id)));
+
+        for guild_id in scopes {
+            match collector.sync_commands(&ctx.http, guild_id, dry_run).await {
+                Ok(diff) if dry_run => info!("[dry-run] {}", diff),
+                Ok(diff) if diff.is_empty() => debug!("{}", diff),
+                Ok(diff) => info!("Registered slash commands. {}", diff),
+                Err(why) => match guild_id {
+                    Some(guild_id) => {
+                        error!(
+                            "Cannot register slash commands for guild {}: {}",
+                            guild_id, why
+                        )
+                    }
+                    None => error!("Cannot register global slash commands: {}", why),
+                },
             }
+        }
 
-            info!("Registered slash commands for guild {}", guild.id);
+        match EMBEDS.restore(&ctx).await {
+            Ok(restored) => info!("Restored embeds: {}", restored),
+            Err(why) => error!("Cannot restore embeds: {}", why),
         }
 
+        // live embeds (eg.: poll progress) are refreshed by their lifetime
+        EMBEDS.start_scheduler(&ctx, Duration::from_secs(30));
+
+        // background jobs (eg.: poll timers), the stored ones are resumed
+        SCHEDULER.start(&ctx, Duration::from_secs(1));
+
         ctx.set_activity(Some(ActivityData::playing(
             "O Auxílio Emergencial no PIX do Mito",
         )))
@@ -96,25 +250,10 @@ impl EventHandler for Handler {
     // On User connect to voice channel
     // ---------
     async fn voice_state_update(&self, ctx: Context, old: Option<VoiceState>, new: VoiceState) {
-        let is_bot: bool = new.user_id.to_user(&ctx.http).await.unwrap().bot;
-        let has_connected: bool = new.channel_id.is_some() && old.is_none();
-
-        if has_connected && !is_bot {
-            debug!("User connected to voice channel: {:#?}", new.channel_id);
+        debug!("Received voice state update from User: {}", new.user_id);
 
-            join_channel(&new.channel_id.unwrap(), &ctx, &new.user_id).await;
-        }
-
-        match old {
-            Some(old) => {
-                if old.channel_id.is_some() && new.channel_id.is_none() && !is_bot {
-                    debug!(
-                        "User disconnected from voice channel: {:#?}",
-                        old.channel_id
-                    );
-                }
-            }
-            None => {}
+        if let Some(dispatcher) = listener_dispatcher() {
+            dispatcher.voice_state_update(&ctx, &old, &new).await;
         }
     }
 
@@ -124,80 +263,137 @@ impl EventHandler for Handler {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         match interaction {
             Interaction::Modal(submit) => {
-                submit.defer(&ctx.http.clone()).await.unwrap();
-
                 debug!(
                     "Received modal submit interaction from User: {:#?}",
                     submit.user.name
                 );
 
-                // let registered_interactions = get_modal_interactions();
-
-                // // custom_id is in the format: '<interaction_name>/<id>'
-                // match registered_interactions.iter().enumerate().find(|(_, i)| {
-                //     i.name
-                //         == submit
-                //             .clone()
-                //             .data
-                //             .custom_id
-                //             .split("/")
-                //             .collect::<Vec<&str>>()
-                //             .first()
-                //             .unwrap()
-                //             .to_string()
-                // }) {
-                //     Some((_, interaction)) => {
-                //         let Some(guild) = ({
-                //             let cloned_ctx = ctx.clone();
-                //             let guild_reference = cloned_ctx.cache.guild(submit.guild_id.unwrap());
-
-                //             match guild_reference {
-                //                 Some(guild) => Some(guild.clone()),
-                //                 None => None,
-                //             }
-                //         }) else {
-                //             error!("Cannot get guild from cache");
-                //             return;
-                //         };
-
-                //         interaction
-                //             .runner
-                //             .run(&ArgumentsLevel::provide(
-                //                 &interaction.arguments,
-                //                 &ctx,
-                //                 &guild,
-                //                 &submit.user,
-                //                 &submit.channel_id,
-                //                 None,
-                //                 Some(submit.id),
-                //                 Some(&submit.data),
-                //                 None,
-                //             ))
-                //             .await;
-                //     }
-
-                //     None => {
-                //         error!(
-                //             "Modal submit interaction {} not found",
-                //             submit.data.custom_id.split("/").collect::<Vec<&str>>()[0]
-                //         );
-                //     }
-                // };
+                let Some(dispatcher) = listener_dispatcher() else {
+                    return;
+                };
+
+                if let Err(why) = dispatcher.modal(&ctx, &submit).await {
+                    warn!("Cannot route modal submit: {}", why);
+
+                    if let Err(why) = submit
+                        .create_response(
+                            &ctx.http,
+                            CreateInteractionResponse::Message(
+                                CreateInteractionResponseMessage::new()
+                                    .content(t!(
+                                        "interactions.modal.unknown",
+                                        locale =
+                                            &LOCALES.resolve(submit.guild_id, Some(&submit.locale))
+                                    ))
+                                    .ephemeral(true),
+                            ),
+                        )
+                        .await
+                    {
+                        error!("Cannot respond to modal submit: {}", why);
+                    }
+                }
             }
 
-            Interaction::Command(command) => {
-                info!(
-                    "Received command \"{}\" interaction from User: {:#?}",
-                    command.data.name, command.user.name
+            Interaction::Component(component) => {
+                debug!(
+                    "Received component interaction \"{}\" from User: {:#?}",
+                    component.data.custom_id, component.user.name
                 );
 
-                // Defer the interaction and edit it later
-                match command.defer(&ctx.http.clone()).await {
-                    Ok(_) => {}
+                let Some(dispatcher) = listener_dispatcher() else {
+                    return;
+                };
+
+                if let Err(why) = dispatcher.component(&ctx, &component).await {
+                    warn!("Cannot route component interaction: {}", why);
+
+                    if let Err(why) = component
+                        .create_response(
+                            &ctx.http,
+                            CreateInteractionResponse::Message(
+                                CreateInteractionResponseMessage::new()
+                                    .content(t!(
+                                        "interactions.component.unknown",
+                                        locale = &LOCALES
+                                            .resolve(component.guild_id, Some(&component.locale))
+                                    ))
+                                    .ephemeral(true),
+                            ),
+                        )
+                        .await
+                    {
+                        error!("Cannot respond to component interaction: {}", why);
+                    }
+                }
+            }
+
+            Interaction::Autocomplete(autocomplete) => {
+                let collector = match COMMAND_COLLECTOR.lock() {
+                    Ok(collector) => collector.clone(),
+                    Err(why) => {
+                        error!("Cannot get command collector: {}", why);
+                        return;
+                    }
+                };
+
+                let Some(command_interface) = collector
+                    .commands
+                    .iter()
+                    .find(|c| c.name == autocomplete.data.name)
+                else {
+                    error!("Command {} not found", autocomplete.data.name);
+                    return;
+                };
+
+                let resolved_command = match command_interface.resolve(&autocomplete.data.options) {
+                    Ok(resolved_command) => resolved_command,
                     Err(why) => {
-                        error!("Cannot defer slash command: {}", why);
+                        warn!("Cannot resolve autocomplete: {}", why);
+                        return;
                     }
+                };
+
+                let guild = autocomplete
+                    .guild_id
+                    .and_then(|guild_id| ctx.cache.guild(guild_id).map(|guild| guild.clone()));
+                let invocation = InvocationContext::new(
+                    &ctx,
+                    guild,
+                    &autocomplete.user,
+                    &autocomplete.channel_id,
+                )
+                .options(resolved_command.options.clone())
+                .interaction_id(autocomplete.id)
+                .guild_id(autocomplete.guild_id)
+                .locale(&LOCALES.resolve(autocomplete.guild_id, Some(&autocomplete.locale)));
+
+                let Some(choices) = resolved_command.suggest(&invocation).await else {
+                    debug!(
+                        "No autocomplete provider for command {}",
+                        resolved_command.name
+                    );
+                    return;
+                };
+
+                if let Err(why) = autocomplete
+                    .create_response(
+                        &ctx.http,
+                        CreateInteractionResponse::Autocomplete(
+                            CreateAutocompleteResponse::new().set_choices(choices),
+                        ),
+                    )
+                    .await
+                {
+                    error!("Cannot respond to autocomplete: {}", why);
                 }
+            }
+
+            Interaction::Command(command) => {
+                info!(
+                    "Received command \"{}\" interaction from User: {:#?}",
+                    command.data.name, command.user.name
+                );
 
                 let collector = match COMMAND_COLLECTOR.lock() {
                     Ok(collector) => collector.clone(),
@@ -216,79 +412,63 @@ impl EventHandler for Handler {
                     .find(|(_, c)| c.name == command.data.name)
                 {
                     Some((_, command_interface)) => {
-                        let Some(guild) = ({
-                            let cloned_ctx = ctx.clone();
-                            let guild_reference = cloned_ctx.cache.guild(command.guild_id.unwrap());
+                        // commands used outside of a guild (eg.: global commands on DM) have no guild
+                        let guild = command.guild_id.and_then(|guild_id| {
+                            ctx.cache.guild(guild_id).map(|guild| guild.clone())
+                        });
+
+                        let resolved_command =
+                            match command_interface.resolve(&command.data.options) {
+                                Ok(resolved_command) => resolved_command,
+                                Err(why) => {
+                                    warn!("Cannot resolve command {}: {}", command.data.name, why);
+
+                                    return respond_error(&ctx, &command, why.into(), false).await;
+                                }
+                            };
+
+                        debug!("Resolved command: {}", resolved_command.name);
+
+                        let invocation =
+                            InvocationContext::new(&ctx, guild, &command.user, &command.channel_id)
+                                .options(resolved_command.options.clone())
+                                .interaction_id(command.id)
+                                .guild_id(command.guild_id)
+                                .locale(&LOCALES.resolve(command.guild_id, Some(&command.locale)))
+                                .member_permissions(
+                                    command
+                                        .member
+                                        .as_ref()
+                                        .and_then(|member| member.permissions),
+                                );
+
+                        // the interaction is deferred (and edited later) only when the runner is slow to respond
+                        let run = collector.run(&resolved_command, &invocation);
+                        tokio::pin!(run);
+
+                        let mut deferred = false;
+                        let result = tokio::select! {
+                            result = &mut run => result,
+                            _ = tokio::time::sleep(DEFER_AFTER) => {
+                                if let Err(why) = command.defer(&ctx.http).await {
+                                    error!("Cannot defer slash command: {}", why);
+                                }
 
-                            match guild_reference {
-                                Some(guild) => Some(guild.clone()),
-                                None => None,
+                                deferred = true;
+                                run.await
                             }
-                        }) else {
-                            error!("Cannot get guild from cache");
-                            return;
                         };
 
-                        match command_interface
-                            .runner
-                            .run(&ArgumentsLevel::provide(
-                                &command_interface.arguments,
-                                &ctx,
-                                &guild,
-                                &command.user,
-                                &command.channel_id,
-                                Some(command.data.options.clone()),
-                                Some(command.id),
-                                None,
-                                None,
-                            ))
-                            .await
-                        {
+                        match result {
                             Ok(command_response) => {
-                                debug!("Responding to slash command: {}", command.data.name);
-
-                                if CommandResponse::None != command_response {
-                                    if let Err(why) = match command_response {
-                                        CommandResponse::String(string) => {
-                                            command
-                                                .edit_response(
-                                                    &ctx.http,
-                                                    EditInteractionResponse::default()
-                                                        .content(string),
-                                                )
-                                                .await
-                                        }
-                                        CommandResponse::Embed(embed) => {
-                                            command
-                                                .edit_response(
-                                                    &ctx.http,
-                                                    EditInteractionResponse::default()
-                                                        .embed(embed.into()),
-                                                )
-                                                .await
-                                        }
-                                        CommandResponse::Message(message) => {
-                                            command.edit_response(&ctx.http, message).await
-                                        }
-                                        // if none is returned ignore
-                                        CommandResponse::None => todo!(),
-                                    } {
-                                        error!("Cannot respond to slash command: {}", why);
-                                    }
-                                } else {
-                                    debug!("Deleting slash command: {}", command.data.name);
-
-                                    if let Err(why) = command
-                                        .edit_response(&ctx.http, EditInteractionResponse::new())
-                                        .await
-                                    {
-                                        error!("Cannot respond to slash command: {}", why);
-                                    }
-                                }
-                            }
-                            Err(why) => {
-                                error!("Cannot run slash command: {}", why);
+                                debug!(
+                                    "Responding to slash command {}: {}",
+                                    command.data.name, command_response
+                                );
+
+                                respond(&ctx, &command, command_response, deferred).await
                             }
+                            Err(why) => respond_error(&ctx, &command, why, deferred).await,
                         }
                     }
                     None => {
@@ -345,28 +525,33 @@ async fn main() {
 
     info!("Starting bot");
 
+    actions::collectors::register_locales();
     actions::collectors::register_commands(&mut command_collector);
+    actions::collectors::register_middlewares(&mut command_collector);
     actions::collectors::register_listeners(&mut listener_collector);
+    actions::collectors::register_integrations(&mut listener_collector);
+    actions::collectors::register_embeds();
+    actions::collectors::register_features();
+    actions::collectors::register_jobs();
 
     info!("Collected commands: {:#?}", command_collector.length);
     info!("Collected listeners: {:#?}", listener_collector.length);
 
     *COMMAND_COLLECTOR.lock().unwrap() = command_collector;
+    *LISTENER_COLLECTOR.lock().unwrap() = listener_collector;
 
     let intents = GatewayIntents::MESSAGE_CONTENT
         | GatewayIntents::DIRECT_MESSAGES
         | GatewayIntents::GUILD_WEBHOOKS
         | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
         | GatewayIntents::GUILDS
         | GatewayIntents::GUILD_MEMBERS
         | GatewayIntents::GUILD_VOICE_STATES
         | GatewayIntents::GUILD_INTEGRATIONS;
 
-    let framework = StandardFramework::new();
-
     let mut client = Client::builder(token, intents)
         .event_handler(Handler)
-        .framework(framework)
         .register_songbird()
         .type_map_insert::<HttpKey>(HttpClient::new())
         .await
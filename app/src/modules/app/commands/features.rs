@@ -0,0 +1,359 @@
+use crate::{
+    modules::core::actions::collectors::sync_guild_commands, COMMAND_COLLECTOR, LISTENER_COLLECTOR,
+};
+use bostil_core::{
+    arguments::{InvocationContext, Locale},
+    command,
+    commands::Autocomplete,
+    errors::BostilError,
+    features::{FeatureKind, FeatureToggle, FEATURES},
+    preconditions::Precondition,
+    runners::runners::{CommandResponse, CommandResult},
+};
+use rust_i18n::t;
+use serenity::{
+    all::{CommandDataOption, CommandOptionType, GuildId, Permissions},
+    builder::{AutocompleteChoice, CreateCommand, CreateCommandOption, CreateEmbed},
+    client::Context,
+    model::Colour,
+};
+
+fn preconditions() -> Vec<Precondition> {
+    vec![
+        Precondition::GuildOnly,
+        Precondition::RequiresPermissions(Permissions::MANAGE_GUILD),
+    ]
+}
+
+/// Names of the collected features of the kind
+fn feature_names(kind: FeatureKind) -> Vec<String> {
+    let mut names = match kind {
+        FeatureKind::Command => match COMMAND_COLLECTOR.lock() {
+            Ok(collector) => collector
+                .commands
+                .iter()
+                .map(|command| command.name.clone())
+                .collect(),
+            Err(_) => vec![],
+        },
+        FeatureKind::Listener | FeatureKind::Integration => match LISTENER_COLLECTOR.lock() {
+            Ok(collector) => collector
+                .listeners
+                .iter()
+                .filter(|listener| listener.feature == kind)
+                .map(|listener| listener.name.clone())
+                .collect(),
+            Err(_) => vec![],
+        },
+    };
+
+    names.sort();
+    names.dedup();
+
+    names
+}
+
+fn option<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| option.value.as_str())
+}
+
+fn kind_name(kind: FeatureKind, locale: &str) -> String {
+    let key = format!("commands.features.kinds.{}", kind.name());
+
+    t!(key.as_str(), locale = locale).to_string()
+}
+
+/// Features disabled on the guild, grouped by kind
+fn overview(guild_id: GuildId, locale: &str) -> CreateEmbed {
+    let embed = CreateEmbed::default()
+        .title(t!("commands.features.title", locale = locale))
+        .colour(Colour::BLURPLE);
+
+    FeatureKind::ALL.iter().fold(embed, |embed, kind| {
+        let disabled = FEATURES.disabled(guild_id, *kind);
+
+        let value = match disabled.is_empty() {
+            true => t!("commands.features.none", locale = locale).to_string(),
+            false => disabled
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<String>>()
+                .join(", "),
+        };
+
+        embed.field(kind_name(*kind, locale), value, false)
+    })
+}
+
+/// Command to list, enable or disable the commands, listeners and integrations on the guild
+#[command(
+    name = "features",
+    description = "Enable or disable the commands, listeners and integrations on the server",
+    context = Guild,
+    category = Admin,
+    fingerprint = fingerprint,
+    preconditions = preconditions,
+    autocomplete = autocompletes,
+)]
+async fn features(
+    options: Vec<CommandDataOption>,
+    guild_id: GuildId,
+    ctx: Context,
+    locale: Locale,
+) -> CommandResult<'static> {
+    let action = option(&options, "action").unwrap_or("list");
+
+    if action == "list" {
+        return Ok(CommandResponse::Embeds(vec![overview(guild_id, &locale)]).ephemeral());
+    }
+
+    let (Some(kind), Some(name)) = (
+        option(&options, "kind").and_then(FeatureKind::from_name),
+        option(&options, "name"),
+    ) else {
+        return Err(BostilError::user_facing("commands.features.missing"));
+    };
+
+    if !feature_names(kind).iter().any(|feature| feature == name) {
+        return Err(BostilError::user_facing("commands.features.not_found")
+            .with("kind", kind_name(kind, &locale))
+            .with("name", name));
+    }
+
+    // disabling this command would lock the admins out of the toggles
+    if kind == FeatureKind::Command && name == "features" {
+        return Err(BostilError::user_facing("commands.features.protected"));
+    }
+
+    let enabled = action == "enable";
+
+    FEATURES.set(FeatureToggle {
+        guild_id,
+        kind,
+        name: name.to_string(),
+        enabled,
+    })?;
+
+    // the disabled commands are removed from the guild
+    if kind == FeatureKind::Command {
+        sync_guild_commands(&ctx, guild_id).await?;
+    }
+
+    let key = match enabled {
+        true => "commands.features.toggled.enabled",
+        false => "commands.features.toggled.disabled",
+    };
+
+    Ok(CommandResponse::String(
+        t!(
+            key,
+            locale = &locale,
+            kind = kind_name(kind, &locale),
+            name = name
+        )
+        .to_string(),
+    )
+    .ephemeral())
+}
+
+fn autocompletes() -> Vec<Autocomplete> {
+    vec![Autocomplete::from_fn("name", feature_suggestions)]
+}
+
+/// Features of the chosen kind (commands when not chosen yet) whose name contains the typed value
+fn feature_suggestions(invocation: &InvocationContext, value: &str) -> Vec<AutocompleteChoice> {
+    let kind = option(&invocation.options, "kind")
+        .and_then(FeatureKind::from_name)
+        .unwrap_or(FeatureKind::Command);
+    let value = value.to_lowercase();
+
+    feature_names(kind)
+        .into_iter()
+        .filter(|name| name.to_lowercase().contains(&value))
+        .take(25)
+        .map(|name| AutocompleteChoice::new(name.clone(), name))
+        .collect()
+}
+
+fn fingerprint() -> CreateCommand {
+    CreateCommand::new("features")
+        .description("Enable or disable the commands, listeners and integrations on the server")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "action", "What to do")
+                .add_string_choice("List", "list")
+                .add_string_choice("Enable", "enable")
+                .add_string_choice("Disable", "disable")
+                .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "kind", "Kind of the feature")
+                .add_string_choice("Command", "command")
+                .add_string_choice("Listener", "listener")
+                .add_string_choice("Integration", "integration"),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "name", "Name of the feature")
+                .set_autocomplete(true),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use bostil_core::{
+        errors::BostilError,
+        features::{FeatureKind, FeatureToggle, FEATURES},
+        locales::LOCALES,
+        runners::runners::CommandResponse,
+        testing::{fakes, TestHarness},
+    };
+    use rust_i18n::t;
+    use serenity::{
+        all::{GuildId, Permissions},
+        json::{json, to_value},
+    };
+
+    use super::FEATURES_COMMAND;
+    use crate::COMMAND_COLLECTOR;
+
+    fn collect_features() {
+        let mut collector = COMMAND_COLLECTOR.lock().unwrap();
+
+        if !collector
+            .commands
+            .iter()
+            .any(|command| command.name == "features")
+        {
+            collector.store_command(FEATURES_COMMAND.clone());
+        }
+    }
+
+    async fn admin_harness(guild_id: u64) -> TestHarness {
+        TestHarness::new()
+            .await
+            .guild(fakes::guild(guild_id, "guild"))
+            .member_permissions(Permissions::MANAGE_GUILD)
+    }
+
+    #[tokio::test]
+    async fn disabled_command_is_denied() {
+        let harness = admin_harness(180).await;
+
+        FEATURES
+            .set(FeatureToggle {
+                guild_id: GuildId::new(180),
+                kind: FeatureKind::Command,
+                name: "features".to_string(),
+                enabled: false,
+            })
+            .unwrap();
+
+        let result = harness
+            .run_command(
+                &FEATURES_COMMAND,
+                vec![fakes::string_option("action", "list")],
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(BostilError::UserFacing { key, .. }) if key == "commands.features.disabled"
+        ));
+    }
+
+    #[tokio::test]
+    async fn lists_disabled_features_to_the_admin() {
+        let harness = admin_harness(183).await;
+
+        let result = harness
+            .run_command(
+                &FEATURES_COMMAND,
+                vec![fakes::string_option("action", "list")],
+            )
+            .await;
+
+        let Ok(response) = result else {
+            panic!("Expected the disabled features");
+        };
+
+        assert!(response.is_ephemeral());
+        assert!(matches!(
+            response,
+            CommandResponse::Ephemeral(response) if matches!(*response, CommandResponse::Embeds(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn lists_on_the_locale_of_each_guild() {
+        LOCALES.set_available(vec!["en-US".to_string(), "pt-BR".to_string()]);
+        LOCALES.set(GuildId::new(185), "pt-BR").unwrap();
+
+        for (guild_id, locale) in [(184, "en-US"), (185, "pt-BR")] {
+            let result = admin_harness(guild_id)
+                .await
+                .run_command(
+                    &FEATURES_COMMAND,
+                    vec![fakes::string_option("action", "list")],
+                )
+                .await;
+
+            let Ok(CommandResponse::Ephemeral(response)) = result else {
+                panic!("Expected the disabled features");
+            };
+            let CommandResponse::Embeds(embeds) = *response else {
+                panic!("Expected the features embed");
+            };
+
+            assert_eq!(
+                to_value(&embeds[0]).unwrap()["title"],
+                json!(t!("commands.features.title", locale = locale))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn features_command_is_protected() {
+        collect_features();
+        let harness = admin_harness(181).await;
+
+        let result = harness
+            .run_command(
+                &FEATURES_COMMAND,
+                vec![
+                    fakes::string_option("action", "disable"),
+                    fakes::string_option("kind", "command"),
+                    fakes::string_option("name", "features"),
+                ],
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(BostilError::UserFacing { key, .. }) if key == "commands.features.protected"
+        ));
+        assert!(FEATURES.is_enabled(Some(GuildId::new(181)), FeatureKind::Command, "features"));
+    }
+
+    #[tokio::test]
+    async fn unknown_feature() {
+        let harness = admin_harness(182).await;
+
+        let result = harness
+            .run_command(
+                &FEATURES_COMMAND,
+                vec![
+                    fakes::string_option("action", "disable"),
+                    fakes::string_option("kind", "listener"),
+                    fakes::string_option("name", "unknown"),
+                ],
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(BostilError::UserFacing { key, .. }) if key == "commands.features.not_found"
+        ));
+    }
+}
@@ -0,0 +1,236 @@
+use bostil_core::{
+    arguments::{InvocationContext, Locale},
+    command,
+    commands::{Autocomplete, CommandCategory},
+    errors::BostilError,
+    help::CommandHelp,
+    pagination::{FieldPages, PageSource, Paginator},
+    runners::runners::{CommandResponse, CommandResult},
+};
+use rust_i18n::t;
+use serenity::{
+    all::{CommandDataOption, CommandOptionType, User},
+    builder::{
+        AutocompleteChoice, CreateCommand, CreateCommandOption, CreateEmbed,
+        EditInteractionResponse,
+    },
+    model::Colour,
+};
+
+use crate::COMMAND_COLLECTOR;
+
+/// Help of the collected commands on the locale
+fn collected_help(locale: &str) -> Vec<CommandHelp> {
+    match COMMAND_COLLECTOR.lock() {
+        Ok(collector) => collector.help(locale),
+        Err(_) => vec![],
+    }
+}
+
+fn category_name(category: CommandCategory, locale: &str) -> String {
+    let key = format!("commands.help.categories.{}", category.name());
+
+    t!(key.as_str(), locale = locale).to_string()
+}
+
+/// One page per category, listing its commands
+fn overview(help: &[CommandHelp], locale: &str) -> Vec<CreateEmbed> {
+    CommandCategory::ALL
+        .iter()
+        .filter_map(|category| {
+            let commands = help
+                .iter()
+                .filter(|command| command.category == *category)
+                .collect::<Vec<&CommandHelp>>();
+
+            if commands.is_empty() {
+                return None;
+            }
+
+            let pages = commands.iter().fold(
+                FieldPages::new(t!("commands.help.title", locale = locale))
+                    .description(format!(
+                        "**{}**\n{}",
+                        category_name(*category, locale),
+                        t!("commands.help.description", locale = locale)
+                    ))
+                    .colour(Colour::BLURPLE),
+                |pages, command| {
+                    pages.field(
+                        format!("/{}", command.name),
+                        command.description.clone(),
+                        false,
+                    )
+                },
+            );
+
+            Some(pages)
+        })
+        .flat_map(|pages| {
+            (0..pages.pages())
+                .map(|page| pages.render(page))
+                .collect::<Vec<CreateEmbed>>()
+        })
+        .collect()
+}
+
+/// Usages of the command with its options
+fn details(help: &CommandHelp, locale: &str) -> CreateEmbed {
+    let embed = CreateEmbed::default()
+        .title(format!("/{}", help.name))
+        .description(format!(
+            "{}\n\n**{}:** {}",
+            help.description,
+            t!("commands.help.category", locale = locale),
+            category_name(help.category, locale)
+        ))
+        .colour(Colour::BLURPLE);
+
+    help.usages.iter().fold(embed, |embed, usage| {
+        let options = usage
+            .options
+            .iter()
+            .map(|option| format!("`{}` — {}", option.name, option.description))
+            .collect::<Vec<String>>();
+
+        let value = match options.is_empty() {
+            true => usage.description.clone(),
+            false => format!("{}\n{}", usage.description, options.join("\n")),
+        };
+
+        embed.field(format!("`{}`", usage.syntax()), value, false)
+    })
+}
+
+/// Command to list the commands by category, or show the usage of a command
+#[command(
+    name = "help",
+    description = "List the commands of the bot, or show how to use a command",
+    context = Global,
+    category = General,
+    fingerprint = fingerprint,
+    autocomplete = autocompletes,
+)]
+async fn help(
+    options: Vec<CommandDataOption>,
+    user: User,
+    locale: Locale,
+) -> CommandResult<'static> {
+    let help = collected_help(&locale);
+
+    let Some(name) = options
+        .iter()
+        .find(|option| option.name == "command")
+        .and_then(|option| option.value.as_str())
+    else {
+        return Ok(CommandResponse::Paginated(
+            Paginator::new(overview(&help, &locale)).owner(user.id),
+        ));
+    };
+
+    match help.iter().find(|command| command.name == name) {
+        Some(command) => Ok(CommandResponse::Message(
+            EditInteractionResponse::new().embed(details(command, &locale)),
+        )),
+        None => Err(BostilError::user_facing("commands.help.not_found").with("command", name)),
+    }
+}
+
+fn autocompletes() -> Vec<Autocomplete> {
+    vec![Autocomplete::from_fn("command", command_suggestions)]
+}
+
+/// Commands whose name contains the typed value
+fn command_suggestions(invocation: &InvocationContext, value: &str) -> Vec<AutocompleteChoice> {
+    let value = value.to_lowercase();
+
+    collected_help(&invocation.locale)
+        .into_iter()
+        .filter(|command| command.name.to_lowercase().contains(&value))
+        .take(25)
+        .map(|command| AutocompleteChoice::new(command.name.clone(), command.name))
+        .collect()
+}
+
+fn fingerprint() -> CreateCommand {
+    CreateCommand::new("help")
+        .description("List the commands of the bot, or show how to use a command")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "command",
+                "The command to show how to use",
+            )
+            .set_autocomplete(true),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use bostil_core::{
+        errors::BostilError,
+        runners::runners::CommandResponse,
+        testing::{fakes, TestHarness},
+    };
+    use serenity::json::{json, to_value};
+
+    use super::HELP_COMMAND;
+    use crate::COMMAND_COLLECTOR;
+
+    fn collect_help() {
+        let mut collector = COMMAND_COLLECTOR.lock().unwrap();
+
+        if !collector
+            .commands
+            .iter()
+            .any(|command| command.name == "help")
+        {
+            collector.store_command(HELP_COMMAND.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn shows_usage_of_a_command() {
+        collect_help();
+        let harness = TestHarness::new().await;
+
+        let result = harness
+            .run_command(&HELP_COMMAND, vec![fakes::string_option("command", "help")])
+            .await;
+
+        let Ok(CommandResponse::Message(message)) = result else {
+            panic!("Expected the usage of the command");
+        };
+        let embed = to_value(message).unwrap()["embeds"][0].clone();
+
+        assert_eq!(embed["title"], json!("/help"));
+        assert_eq!(embed["fields"][0]["name"], json!("`/help [command]`"));
+    }
+
+    #[tokio::test]
+    async fn lists_commands_by_page() {
+        collect_help();
+        let harness = TestHarness::new().await;
+
+        let result = harness.run_command(&HELP_COMMAND, vec![]).await;
+
+        assert!(matches!(result, Ok(CommandResponse::Paginated(_))));
+    }
+
+    #[tokio::test]
+    async fn unknown_command() {
+        let harness = TestHarness::new().await;
+
+        let result = harness
+            .run_command(
+                &HELP_COMMAND,
+                vec![fakes::string_option("command", "unknown")],
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(BostilError::UserFacing { key, .. }) if key == "commands.help.not_found"
+        ));
+    }
+}
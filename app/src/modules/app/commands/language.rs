@@ -1,47 +1,265 @@
 use bostil_core::{
-    commands::{Command, CommandCategory, CommandContext},
-    runners::runners::{CommandResponse, CommandResult, CommandRunnerFn},
+    arguments::Locale,
+    command,
+    errors::BostilError,
+    locales::{DEFAULT_LOCALE, LOCALES},
+    preconditions::Precondition,
+    runners::runners::{CommandResponse, CommandResult},
 };
-use lazy_static::lazy_static;
+use rust_i18n::t;
 use serenity::{
-    all::CommandOptionType,
-    async_trait,
+    all::{CommandDataOption, CommandOptionType, GuildId, Permissions},
     builder::{CreateCommand, CreateCommandOption},
+    client::Context,
 };
-use std::any::Any;
 
-#[derive(Clone)]
-struct Language;
+use crate::modules::core::actions::collectors::sync_guild_commands;
 
-#[async_trait]
-impl CommandRunnerFn for Language {
-    async fn run<'a>(&self, _args: &Vec<Box<dyn Any + Send + Sync>>) -> CommandResult<'a> {
-        Ok(CommandResponse::String("".to_string()))
-    }
+fn preconditions() -> Vec<Precondition> {
+    vec![
+        Precondition::GuildOnly,
+        Precondition::RequiresPermissions(Permissions::MANAGE_GUILD),
+    ]
 }
 
-lazy_static! {
-    /// Command to set the language of bot responses within a guild
-    pub static ref LANGUAGE_COMMAND: Command = Command::new(
-        "language",
-        "Sets the language of the bot",
-        CommandContext::Guild,
-        CommandCategory::General,
-        vec![],
-        Box::new(Language),
-        Some(
-            CreateCommand::new("language")
-                .description("Language Preferences Menu")
-                .add_option(
-                    CreateCommandOption::new(
-                        CommandOptionType::String,
-                        "choose_language",
-                        "Choose the language of preference"
-                    )
-                    .add_string_choice("Portuguese", "pt-BR")
-                    .add_string_choice("English", "en-US")
-                    .required(true)
-                ),
+/// The choices are the languages with a locale file, so the locales must be registered before the commands
+fn fingerprint() -> CreateCommand {
+    let choose_language = LOCALES.available().into_iter().fold(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "choose_language",
+            "Choose the language of preference",
         ),
+        |option, language| {
+            option.add_string_choice(language_name(&language, DEFAULT_LOCALE), language)
+        },
     );
+
+    CreateCommand::new("language")
+        .description("Language Preferences Menu")
+        .add_option(choose_language)
+}
+
+/// Name of the language on the locale (eg.: `pt-BR` is "Portuguese" on `en-US`)
+fn language_name(language: &str, locale: &str) -> String {
+    let key = format!("commands.language.{}", language);
+
+    t!(key.as_str(), locale = locale).to_string()
+}
+
+/// Command to set the language of bot responses within a guild, or show the current one
+#[command(
+    name = "language",
+    description = "Sets the language of the bot",
+    context = Guild,
+    category = General,
+    fingerprint = fingerprint,
+    preconditions = preconditions,
+)]
+async fn language(
+    ctx: Context,
+    options: Vec<CommandDataOption>,
+    guild_id: GuildId,
+    locale: Locale,
+) -> CommandResult<'static> {
+    let Some(choice) = options
+        .iter()
+        .find(|option| option.name == "choose_language")
+        .and_then(|option| option.value.as_str())
+    else {
+        return Ok(CommandResponse::String(
+            t!(
+                "commands.language.current_language",
+                locale = &locale,
+                language_name = language_name(&locale, &locale),
+                language_code = &*locale
+            )
+            .to_string(),
+        )
+        .ephemeral());
+    };
+
+    // only the languages with a locale file can be chosen
+    if !LOCALES
+        .available()
+        .iter()
+        .any(|available| available == choice)
+    {
+        return Err(BostilError::user_facing(
+            "commands.language.invalid_language",
+        ));
+    }
+
+    LOCALES.set(guild_id, choice)?;
+
+    sync_guild_commands(&ctx, guild_id).await?;
+
+    Ok(CommandResponse::String(
+        t!(
+            "commands.language.reply",
+            locale = choice,
+            language_name = language_name(choice, choice)
+        )
+        .to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use bostil_core::{
+        errors::BostilError,
+        locales::LOCALES,
+        runners::runners::CommandResponse,
+        testing::{fakes, TestHarness},
+    };
+    use serenity::{
+        all::{GuildId, Permissions},
+        json::json,
+    };
+
+    use super::LANGUAGE_COMMAND;
+    use crate::modules::core::locales::translate;
+
+    async fn admin_harness(guild_id: u64) -> TestHarness {
+        LOCALES.set_available(vec!["en-US".to_string(), "pt-BR".to_string()]);
+
+        TestHarness::new()
+            .await
+            .guild(fakes::guild(guild_id, "guild"))
+            .member_permissions(Permissions::MANAGE_GUILD)
+    }
+
+    #[tokio::test]
+    async fn shows_the_current_language() {
+        let harness = admin_harness(220).await;
+
+        let result = harness.run_command(&LANGUAGE_COMMAND, vec![]).await;
+
+        let Ok(CommandResponse::Ephemeral(response)) = result else {
+            panic!("Expected the current language");
+        };
+
+        assert_eq!(
+            *response,
+            CommandResponse::String("Current language is English [en-US]".to_string())
+        );
+    }
+
+    /// Guild without registered commands, the synced ones are created
+    fn stub_guild_commands(harness: &TestHarness, guild_id: u64) {
+        let suffix = format!("/guilds/{}/commands", guild_id);
+
+        harness.http.stub("GET", &suffix, 200, json!([]));
+        harness.http.stub(
+            "POST",
+            &suffix,
+            201,
+            json!({
+                "id": "10",
+                "application_id": "1",
+                "version": "1",
+                "type": 1,
+                "guild_id": guild_id.to_string(),
+                "name": "features",
+                "description": "Manage the features of the guild",
+            }),
+        );
+    }
+
+    #[tokio::test]
+    async fn changes_the_language_of_the_guild() {
+        let harness = admin_harness(221).await;
+        stub_guild_commands(&harness, 221);
+
+        let result = harness
+            .run_command(
+                &LANGUAGE_COMMAND,
+                vec![fakes::string_option("choose_language", "pt-BR")],
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Ok(CommandResponse::String(reply)) if reply == "O idioma mudou para Português"
+        ));
+        assert_eq!(
+            LOCALES.guild_locale(GuildId::new(221)),
+            Some("pt-BR".to_string())
+        );
+        // the commands of the guild are synced
+        assert!(harness
+            .requests()
+            .iter()
+            .any(|request| request.method == "GET"
+                && request.path == "/applications/1/guilds/221/commands"));
+    }
+
+    #[tokio::test]
+    async fn changes_the_language_of_an_uncached_guild() {
+        LOCALES.set_available(vec!["en-US".to_string(), "pt-BR".to_string()]);
+        let harness = TestHarness::new()
+            .await
+            .uncached_guild(223)
+            .member_permissions(Permissions::MANAGE_GUILD);
+        stub_guild_commands(&harness, 223);
+
+        let result = harness
+            .run_command(
+                &LANGUAGE_COMMAND,
+                vec![fakes::string_option("choose_language", "pt-BR")],
+            )
+            .await;
+
+        assert!(matches!(result, Ok(CommandResponse::String(_))));
+        assert_eq!(
+            LOCALES.guild_locale(GuildId::new(223)),
+            Some("pt-BR".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn unavailable_language() {
+        let harness = admin_harness(222).await;
+
+        let result = harness
+            .run_command(
+                &LANGUAGE_COMMAND,
+                vec![fakes::string_option("choose_language", "fr-FR")],
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(BostilError::UserFacing { key, .. }) if key == "commands.language.invalid_language"
+        ));
+        assert_eq!(LOCALES.guild_locale(GuildId::new(222)), None);
+    }
+
+    #[test]
+    fn fingerprint_is_localized() {
+        LOCALES.set_available(vec!["en-US".to_string(), "pt-BR".to_string()]);
+        LOCALES.set_translator(translate);
+
+        let fingerprint = LANGUAGE_COMMAND.localized_fingerprint();
+
+        assert_eq!(fingerprint["name"], json!("language"));
+        assert_eq!(fingerprint["name_localizations"]["pt-BR"], json!("idioma"));
+        assert_eq!(
+            fingerprint["options"][0]["name_localizations"]["pt-BR"],
+            json!("escolher_idioma")
+        );
+
+        let choices = fingerprint["options"][0]["choices"].as_array().unwrap();
+        let portuguese = choices
+            .iter()
+            .find(|choice| choice["value"] == "pt-BR")
+            .expect("Available language without a choice");
+
+        assert_eq!(choices.len(), 2);
+        assert_eq!(portuguese["name"], json!("Portuguese"));
+        assert_eq!(
+            portuguese["name_localizations"],
+            json!({ "en-US": "Portuguese", "pt-BR": "Português" })
+        );
+    }
 }
@@ -1,79 +1,96 @@
 use bostil_core::{
-    arguments::ArgumentsLevel,
-    commands::{Command, CommandCategory, CommandContext},
-    runners::runners::{CommandResponse, CommandResult, CommandRunnerFn},
+    command,
+    runners::runners::{CommandResponse, CommandResult},
 };
-use lazy_static::lazy_static;
-use serenity::{async_trait, builder::CreateCommand, client::Context};
-use std::{any::Any, time::Duration};
+use serenity::client::Context;
+use std::time::Duration;
 use tracing::{debug, error, info};
 
 use crate::ShardManagerContainer;
 
-#[derive(Clone)]
-struct Ping;
+/// # Ping Command
+///
+/// > Command to check if the bot is alive, and test the latency to the server
+#[command(
+    name = "ping",
+    description = "Check if the bot is alive, and test the latency to the server",
+    context = Global,
+    category = General,
+)]
+async fn ping(context: Context) -> CommandResult<'static> {
+    let data = context.data.read().await;
 
-#[async_trait]
-impl CommandRunnerFn for Ping {
-    async fn run<'a>(&self, arguments: &Vec<Box<dyn Any + Send + Sync>>) -> CommandResult<'a> {
-        let context = arguments
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Context>())
-            .collect::<Vec<&Context>>()[0];
+    let shard_manager = match data.get::<ShardManagerContainer>() {
+        Some(v) => v,
+        None => {
+            error!("No shard manager found");
 
-        let data = context.data.read().await;
+            return Ok(CommandResponse::String(
+                "There was a problem getting the shard manager".to_string(),
+            ));
+        }
+    };
+
+    debug!("ShardManager: #{:?}", shard_manager);
 
-        let shard_manager = match data.get::<ShardManagerContainer>() {
-            Some(v) => v,
-            None => {
-                error!("No shard manager found");
+    let runners = shard_manager.runners.lock().await;
 
-                return Ok(CommandResponse::String(
-                    "There was a problem getting the shard manager".to_string(),
-                ));
-            }
-        };
+    let runner = match runners.get(&context.shard_id) {
+        Some(runner) => runner,
+        None => {
+            error!("No shard runner found for shard {}", context.shard_id);
 
-        debug!("ShardManager: #{:?}", shard_manager);
+            return Ok(CommandResponse::String(
+                "There was a problem getting the shard runner".to_string(),
+            ));
+        }
+    };
 
-        let runners = shard_manager.runners.lock().await;
+    if runner.latency.is_none() {
+        info!("The shard runner latency is not available");
+    }
 
-        let runner = match runners.get(&context.shard_id) {
-            Some(runner) => runner,
-            None => {
-                error!("No shard runner found for shard {}", context.shard_id);
+    Ok(CommandResponse::String(format!(
+        "Pong! The shard runner latency is: {} ms",
+        runner.latency.unwrap_or(Duration::from_secs(0)).as_millis()
+    )))
+}
 
-                return Ok(CommandResponse::String(
-                    "There was a problem getting the shard runner".to_string(),
-                ));
-            }
-        };
+#[cfg(test)]
+mod tests {
+    use bostil_core::{runners::runners::CommandResponse, testing::TestHarness};
 
-        if runner.latency.is_none() {
-            info!("The shard runner latency is not available");
-        }
+    use super::PING_COMMAND;
+    use crate::ShardManagerContainer;
 
-        Ok(CommandResponse::String(format!(
-            "Pong! The shard runner latency is: {} ms",
-            runner.latency.unwrap_or(Duration::from_secs(0)).as_millis()
-        )))
+    #[tokio::test]
+    async fn without_shard_manager() {
+        let harness = TestHarness::new().await;
+
+        let response = harness.run_command(&PING_COMMAND, vec![]).await.unwrap();
+
+        assert_eq!(
+            response,
+            CommandResponse::String("There was a problem getting the shard manager".to_string())
+        );
     }
-}
 
-lazy_static! {
-    /// # Ping Command
-    /// 
-    /// > Command to check if the bot is alive, and test the latency to the server
-    pub static ref PING_COMMAND: Command = Command::new(
-        "ping",
-        "Check if the bot is alive, and test the latency to the server",
-        CommandContext::Global,
-        CommandCategory::General,
-        vec![ArgumentsLevel::Context],
-        Box::new(Ping),
-        Some(
-            CreateCommand::new("ping")
-                .description("Check if the bot is alive, and test the latency to the server"),
-        ),
-    );
+    #[tokio::test]
+    async fn without_shard_runner() {
+        let harness = TestHarness::new().await;
+        harness
+            .context
+            .data
+            .write()
+            .await
+            .insert::<ShardManagerContainer>(harness.shard_manager.clone());
+
+        let response = harness.run_command(&PING_COMMAND, vec![]).await.unwrap();
+
+        assert_eq!(
+            response,
+            CommandResponse::String("There was a problem getting the shard runner".to_string())
+        );
+        assert!(harness.requests().is_empty());
+    }
 }
@@ -1,7 +1,7 @@
 mod setup;
 mod vote;
 
-#[allow(unused_imports)]
+#[allow(unused_imports, clippy::module_inception)]
 pub mod embeds {
     pub use super::setup::SETUP_EMBED;
     pub use super::vote::VOTE_EMBED;
@@ -1,8 +1,12 @@
-use bostil_core::embeds::{ApplicationEmbed, EmbedLifetime};
+use bostil_core::{
+    embeds::{ApplicationEmbed, EmbedLifetime},
+    register_embed,
+};
 use diesel::{BoolExpressionMethods, ExpressionMethods};
 use once_cell::sync::Lazy;
 use rust_i18n::t;
-use serenity::{all::MessageId, builder::CreateEmbed};
+use serenity::{all::MessageId, builder::CreateEmbed, json::Value};
+use tracing::error;
 use uuid::Uuid;
 
 use crate::{
@@ -20,51 +24,99 @@ use crate::{
 struct PollSetupEmbed;
 
 impl EmbedLifetime for PollSetupEmbed {
-    fn build(&self, arguments: &Vec<Box<dyn std::any::Any + Send + Sync>>) -> CreateEmbed {
+    fn build(
+        &self,
+        locale: &str,
+        arguments: &[Box<dyn std::any::Any + Send + Sync>],
+    ) -> CreateEmbed {
         use crate::diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
 
         let poll_id = arguments[0].downcast_ref::<Uuid>().unwrap();
         let stage = arguments[1].downcast_ref::<PollStage>().unwrap();
 
         let connection = &mut establish_connection();
-        let poll = polls::table
+        // without the poll the embed is built without its fields
+        let poll = match polls::table
             .find(poll_id)
             .select(Poll::as_select())
             .first::<Poll>(connection)
-            .expect("Error loading poll");
+        {
+            Ok(poll) => Some(poll),
+            Err(why) => {
+                error!("Error loading poll {}: {}", poll_id, why);
+                None
+            }
+        };
 
         let embed = CreateEmbed::default().color(stage.embed_color());
 
         match stage {
             PollStage::Closed => embed
-                .title(t!("commands.poll.setup.embed.stages.closed.title"))
-                .description(t!("commands.poll.setup.stages.closed.description")),
+                .title(t!(
+                    "commands.poll.setup.embed.stages.closed.title",
+                    locale = locale
+                ))
+                .description(t!(
+                    "commands.poll.setup.embed.stages.closed.description",
+                    locale = locale
+                )),
             PollStage::Voting => embed
-                .title(t!("commands.poll.setup.embed.stages.voting.title"))
-                .description(t!("commands.poll.setup.stages.voting.description")),
-            PollStage::Setup => embed
-                .title(t!("commands.poll.setup.embed.stages.setup.title"))
-                .description(t!("commands.poll.setup.embed.stages.setup.description"))
-                .field("ID", poll.id.to_string(), true)
-                .field("User", format!("<@{}>", poll.created_by), true)
-                .field("\u{200B}", "\u{200B}", false), // Separator
+                .title(t!(
+                    "commands.poll.setup.embed.stages.voting.title",
+                    locale = locale
+                ))
+                .description(t!(
+                    "commands.poll.setup.embed.stages.voting.description",
+                    locale = locale
+                )),
+            PollStage::Setup => {
+                let embed = embed
+                    .title(t!(
+                        "commands.poll.setup.embed.stages.setup.title",
+                        locale = locale
+                    ))
+                    .description(t!(
+                        "commands.poll.setup.embed.stages.setup.description",
+                        locale = locale
+                    ));
+
+                match poll {
+                    Some(poll) => embed
+                        .field("ID", poll.id.to_string(), true)
+                        .field("User", format!("<@{}>", poll.created_by), true)
+                        .field("\u{200B}", "\u{200B}", false), // Separator
+                    None => embed,
+                }
+            }
         }
     }
 
-    fn after_sent(&self, arguments: &Vec<Box<dyn std::any::Any + Send + Sync>>) {
+    fn after_sent(
+        &self,
+        embed_message_id: MessageId,
+        arguments: &[Box<dyn std::any::Any + Send + Sync>],
+    ) {
         use crate::diesel::{QueryDsl, RunQueryDsl};
 
         let poll_id = arguments[0].downcast_ref::<Uuid>().unwrap();
-        let embed_message_id = arguments[1].downcast_ref::<MessageId>().unwrap();
 
         let connection = &mut establish_connection();
 
-        diesel::update(
+        if let Err(why) = diesel::update(
             polls::table.filter(polls::id.eq(poll_id).and(polls::embed_message_id.is_null())),
         )
-        .set(polls::embed_message_id.eq(MessageIdWrapper(*embed_message_id)))
+        .set(polls::embed_message_id.eq(MessageIdWrapper(embed_message_id)))
         .execute(connection)
-        .expect("Error updating poll");
+        {
+            error!("Error updating poll {}: {}", poll_id, why);
+        }
+    }
+
+    fn recover(&self, stored: &Value) -> Option<Vec<Box<dyn std::any::Any + Send + Sync>>> {
+        let poll_id = Uuid::parse_str(stored["poll_id"].as_str()?).ok()?;
+        let stage = PollStage::from_name(stored["stage"].as_str()?)?;
+
+        Some(vec![Box::new(poll_id), Box::new(stage)])
     }
 }
 
@@ -78,8 +130,10 @@ pub static SETUP_EMBED: Lazy<ApplicationEmbed> = Lazy::new(|| {
             Box::new(None::<Option<PollStage>>),
         ],
         Box::new(PollSetupEmbed),
-        None,
+        Some(true),
         None,
         None,
     )
 });
+
+register_embed!(SETUP_EMBED);
@@ -1,3 +1,6 @@
+// The voting stage of the polls isn't wired yet
+#![allow(dead_code)]
+
 use bostil_core::embeds::{ApplicationEmbed, EmbedLifetime};
 use once_cell::sync::Lazy;
 use serenity::builder::CreateEmbed;
@@ -14,14 +17,18 @@ use crate::{
 struct PollVoteEmbed;
 
 impl EmbedLifetime for PollVoteEmbed {
-    fn build(&self, arguments: &Vec<Box<dyn std::any::Any + Send + Sync>>) -> CreateEmbed {
+    fn build(
+        &self,
+        _locale: &str,
+        arguments: &[Box<dyn std::any::Any + Send + Sync>],
+    ) -> CreateEmbed {
         use crate::diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
 
         let poll_id = arguments[0].downcast_ref::<Uuid>().unwrap();
-        let stage = arguments[1].downcast_ref::<PollStage>().unwrap();
+        let _stage = arguments[1].downcast_ref::<PollStage>().unwrap();
 
         let connection = &mut establish_connection();
-        let poll = polls::table
+        let _poll = polls::table
             .find(poll_id)
             .select(Poll::as_select())
             .first::<Poll>(connection)
@@ -1,18 +1,15 @@
 use bostil_core::{
-    arguments::ArgumentsLevel,
     commands::{Command, CommandCategory, CommandContext},
-    runners::runners::{CommandResult, CommandRunnerFn},
+    preconditions::Precondition,
+    register_command,
 };
 use lazy_static::lazy_static;
-use serenity::{all::CommandDataOption, async_trait, builder::CreateCommand, model::Colour};
+use serenity::{builder::CreateCommand, model::Colour};
 
 mod embeds;
 mod progress_bar;
 mod setup;
 
-#[derive(Clone)]
-struct PollCommand;
-
 #[derive(Debug, Clone, Copy)]
 pub enum PollStage {
     Setup,
@@ -28,62 +25,313 @@ impl PollStage {
             PollStage::Closed => Colour::DARK_GREEN,
         }
     }
-}
-
-#[async_trait]
-impl CommandRunnerFn for PollCommand {
-    async fn run<'a>(
-        &self,
-        args: &Vec<Box<dyn std::any::Any + Send + Sync>>,
-    ) -> CommandResult<'a> {
-        let options = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Option<Vec<CommandDataOption>>>())
-            .collect::<Vec<&Option<Vec<CommandDataOption>>>>()[0]
-            .as_ref()
-            .unwrap();
-        let first_option = options.get(0).unwrap();
-        let command_name = first_option.name.clone();
 
-        let command_runner = command_suite(command_name);
-
-        let response = command_runner.run(args);
-
-        response.await
+    /// Name of the stage, stored on the arguments of the recoverable embeds
+    pub fn name(&self) -> &'static str {
+        match self {
+            PollStage::Setup => "setup",
+            PollStage::Voting => "voting",
+            PollStage::Closed => "closed",
+        }
     }
-}
 
-fn command_suite(command_name: String) -> &'static Box<dyn CommandRunnerFn + Send + Sync> {
-    let command_runner = match command_name.as_str() {
-        "setup" => &setup::SETUP_COMMAND.runner,
-        _ => {
-            panic!("Command not found");
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "setup" => Some(PollStage::Setup),
+            "voting" => Some(PollStage::Voting),
+            "closed" => Some(PollStage::Closed),
+            _ => None,
         }
-    };
-
-    command_runner
+    }
 }
 
 lazy_static! {
-    pub static ref POLL_COMMANDS: Command = Command::new(
+    pub static ref POLL_COMMANDS: Command = Command::with_subcommands(
         "poll",
         "Poll commands",
         CommandContext::Guild,
         CommandCategory::Misc,
-        vec![
-            ArgumentsLevel::Options,
-            ArgumentsLevel::Context,
-            ArgumentsLevel::Guild,
-            ArgumentsLevel::User,
-            ArgumentsLevel::ChannelId,
-        ],
-        Box::new(PollCommand),
-        Some(
-            CreateCommand::new("poll")
-                .name_localized("pt-BR", "urna")
-                .description("Create and manage polls")
-                .description_localized("pt-BR", "Crie e administre enquetes")
-                .add_option(setup::SETUP_OPTION.clone()),
-        ),
-    );
+        Some(CreateCommand::new("poll").description("Create and manage polls")),
+    )
+    .add_precondition(Precondition::GuildOnly)
+    .add_subcommand(setup::SETUP_COMMAND.clone());
+}
+
+register_command!(POLL_COMMANDS);
+
+#[cfg(test)]
+mod tests {
+    use bostil_core::{
+        collectors::ListenerCollector,
+        embeds::EMBEDS,
+        errors::BostilError,
+        locales::{DEFAULT_LOCALE, LOCALES},
+        runners::runners::CommandResponse,
+        testing::{fakes, TestHarness},
+    };
+    use diesel::{QueryDsl, RunQueryDsl, SelectableHelper};
+    use rust_i18n::t;
+    use serenity::all::Message;
+    use uuid::Uuid;
+
+    use super::POLL_COMMANDS;
+    use crate::{
+        modules::core::{
+            entities::{poll::Poll, PollState},
+            helpers::{establish_connection, use_test_database},
+            locales::translate,
+        },
+        schema::polls,
+    };
+
+    fn find_poll(poll_id: Uuid) -> Poll {
+        polls::table
+            .find(poll_id)
+            .select(Poll::as_select())
+            .first::<Poll>(&mut establish_connection())
+            .unwrap()
+    }
+
+    /// Run `/poll setup` on the guild, returning the setup message (as edited with its components)
+    async fn setup_poll(harness: &TestHarness) -> Message {
+        use_test_database();
+        EMBEDS.store_registered();
+
+        let result = harness
+            .run_command(
+                &POLL_COMMANDS,
+                vec![fakes::subcommand(
+                    "setup",
+                    vec![
+                        fakes::string_option("name", "Pizza"),
+                        fakes::string_option("description", "Best flavor"),
+                    ],
+                )],
+            )
+            .await;
+
+        assert!(matches!(result, Ok(CommandResponse::String(_))));
+
+        // `/channels/<thread_id>/messages/<message_id>`
+        let edit = harness
+            .requests()
+            .into_iter()
+            .find(|request| request.method == "PATCH" && request.body["components"].is_array())
+            .expect("Setup components not added");
+        let segments = edit.path.split('/').collect::<Vec<&str>>();
+
+        let mut message = fakes::message(
+            segments[4].parse().unwrap(),
+            segments[2].parse().unwrap(),
+            &harness.user,
+            "",
+        );
+        message.components = serde_json::from_value(edit.body["components"].clone()).unwrap();
+
+        message
+    }
+
+    /// Custom_id of the component of the setup message with the given name (`<name>/<poll_id>`)
+    fn custom_id(message: &Message, name: &str) -> String {
+        serde_json::to_value(&message.components).unwrap()[0]["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|component| component["custom_id"].as_str())
+            .find(|custom_id| custom_id.starts_with(&format!("{}/", name)))
+            .expect("Component not found")
+            .to_string()
+    }
+
+    fn poll_id(custom_id: &str) -> Uuid {
+        Uuid::parse_str(custom_id.split_once('/').unwrap().1).unwrap()
+    }
+
+    fn registered_listeners() -> ListenerCollector {
+        let mut collector = ListenerCollector::new();
+        collector.store_registered();
+
+        collector
+    }
+
+    #[tokio::test]
+    async fn setup_is_guild_only() {
+        let harness = TestHarness::new().await;
+
+        let result = harness
+            .run_command(
+                &POLL_COMMANDS,
+                vec![fakes::subcommand(
+                    "setup",
+                    vec![fakes::string_option("name", "Pizza")],
+                )],
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(BostilError::UserFacing { key, .. }) if key == "commands.preconditions.guild_only"
+        ));
+    }
+
+    #[tokio::test]
+    async fn setup_requires_name() {
+        let harness = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+
+        let result = harness
+            .run_command(&POLL_COMMANDS, vec![fakes::subcommand("setup", vec![])])
+            .await;
+
+        assert!(matches!(result, Err(BostilError::Validation(_))));
+        assert!(harness.requests().is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Postgres database on TEST_DATABASE_URL"]
+    async fn setup_saves_the_poll_and_sends_its_embed() {
+        let harness = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+
+        let message = setup_poll(&harness).await;
+        let poll_id = poll_id(&custom_id(&message, "add_option"));
+
+        // the embed is built from the saved poll, so it shows its id
+        let embed = harness
+            .sent_messages()
+            .into_iter()
+            .find_map(|message| message["embeds"].get(0).cloned())
+            .expect("Setup embed not sent");
+
+        assert_eq!(embed["fields"][0]["value"], poll_id.to_string());
+
+        let poll = find_poll(poll_id);
+
+        assert_eq!(poll.name, "Pizza");
+        assert_eq!(poll.description.as_deref(), Some("Best flavor"));
+        assert_eq!(poll.created_by, harness.user.id);
+        assert_eq!(poll.embed_message_id.map(|id| id.0), Some(message.id));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Postgres database on TEST_DATABASE_URL"]
+    async fn setup_then_add_option_opens_its_modal() {
+        let harness = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+        let message = setup_poll(&harness).await;
+        let custom_id = custom_id(&message, "add_option");
+        harness.http.clear();
+
+        harness
+            .interact_collected(
+                &registered_listeners(),
+                &fakes::button_interaction(&custom_id, &harness.user, &message),
+            )
+            .await
+            .unwrap();
+
+        let requests = harness.requests();
+
+        // the modal saves the option on the same poll
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/interactions/1/token/callback");
+        assert_eq!(requests[0].body["type"], 9);
+        assert_eq!(
+            requests[0].body["data"]["custom_id"],
+            format!("option_data_poll/{}", poll_id(&custom_id))
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Postgres database on TEST_DATABASE_URL"]
+    async fn setup_then_start_poll_starts_it() {
+        let harness = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+        let message = setup_poll(&harness).await;
+        let custom_id = custom_id(&message, "start_poll");
+
+        harness
+            .interact_collected(
+                &registered_listeners(),
+                &fakes::button_interaction(&custom_id, &harness.user, &message),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(find_poll(poll_id(&custom_id)).state, PollState::Started);
+    }
+
+    #[tokio::test]
+    async fn unknown_poll_kind_is_shown_to_the_user() {
+        LOCALES.set_translator(translate);
+        let harness = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+        let message = fakes::message(1, 1, &harness.user, "");
+
+        harness
+            .interact_collected(
+                &registered_listeners(),
+                &fakes::select_interaction(
+                    &format!("poll_kind/{}", Uuid::new_v4()),
+                    &["unknown"],
+                    &harness.user,
+                    &message,
+                ),
+            )
+            .await
+            .unwrap();
+
+        let requests = harness.requests();
+
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].body["type"], 4);
+        assert_eq!(requests[0].body["data"]["flags"], 64);
+        assert_eq!(
+            requests[0].body["data"]["content"],
+            t!("interactions.error", locale = DEFAULT_LOCALE).to_string()
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Postgres database on TEST_DATABASE_URL"]
+    async fn start_unknown_poll_responds_not_found() {
+        use_test_database();
+        LOCALES.set_translator(translate);
+        let harness = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+        let message = fakes::message(1, 1, &harness.user, "");
+
+        harness
+            .interact_collected(
+                &registered_listeners(),
+                &fakes::button_interaction(
+                    &format!("start_poll/{}", Uuid::new_v4()),
+                    &harness.user,
+                    &message,
+                ),
+            )
+            .await
+            .unwrap();
+
+        let requests = harness.requests();
+
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].body["data"]["flags"], 64);
+        assert_eq!(
+            requests[0].body["data"]["content"],
+            t!(
+                "commands.poll.setup.response.not_found",
+                locale = DEFAULT_LOCALE
+            )
+            .to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_subcommand() {
+        let harness = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+
+        let result = harness
+            .run_command(&POLL_COMMANDS, vec![fakes::subcommand("vote", vec![])])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(BostilError::UserFacing { key, .. }) if key == "commands.unknown_subcommand"
+        ));
+    }
 }
@@ -1,3 +1,6 @@
+// The partial results are shown by the voting stage of the polls, which isn't wired yet
+#![allow(dead_code)]
+
 use crate::modules::core::entities::poll::PollWithChoicesAndVotes;
 
 type PartialResults = Vec<(String, u64)>;
@@ -1,7 +1,9 @@
 use bostil_core::{
-    arguments::ArgumentsLevel,
-    commands::{Command, CommandCategory, CommandContext},
-    runners::runners::{CommandResponse, CommandResult, CommandRunnerFn},
+    arguments::{ArgumentsLevel, Locale},
+    commands::SubCommand,
+    embeds::EMBEDS,
+    errors::BostilError,
+    runners::runners::{CommandResponse, CommandResult, TypedCommandRunnerFn},
 };
 use lazy_static::lazy_static;
 use once_cell::sync::Lazy;
@@ -9,61 +11,57 @@ use rust_i18n::t;
 use serenity::{
     all::{
         AutoArchiveDuration, ButtonStyle, ChannelId, ChannelType, CommandDataOption,
-        CommandOptionType, ComponentInteraction, InputTextStyle, User,
+        CommandOptionType, User,
     },
     async_trait,
     builder::{
-        CreateActionRow, CreateButton, CreateCommandOption, CreateInputText,
-        CreateInteractionResponse, CreateModal, CreateSelectMenu, CreateSelectMenuKind,
+        CreateActionRow, CreateButton, CreateCommandOption, CreateSelectMenu, CreateSelectMenuKind,
         CreateSelectMenuOption, CreateThread, EditMessage,
     },
-    collector::ComponentInteractionCollector,
-    futures::StreamExt,
+    json::json,
     prelude::Context,
 };
-use std::{time::Duration, vec};
+use std::vec;
 use tracing::error;
+use uuid::Uuid;
+
+use super::PollStage;
+use crate::{
+    modules::core::{
+        entities::{ChannelIdWrapper, PollKind, UserIdWrapper},
+        helpers::establish_connection,
+    },
+    schema::polls,
+};
 
 #[derive(Clone)]
 struct CreatePollRunner;
 
 #[async_trait]
-impl CommandRunnerFn for CreatePollRunner {
-    async fn run<'a>(&self, args: &Vec<Box<dyn std::any::Any + Send + Sync>>) -> CommandResult<'a> {
+impl TypedCommandRunnerFn for CreatePollRunner {
+    type Arguments = (Vec<CommandDataOption>, Context, User, ChannelId, Locale);
+
+    async fn run_typed<'a>(
+        &self,
+        (options, ctx, user, channel_id, locale): Self::Arguments,
+    ) -> CommandResult<'a> {
         use super::embeds::embeds::SETUP_EMBED;
+        use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
 
-        let options = args
+        let poll_name = match options
             .iter()
-            .filter_map(|arg| arg.downcast_ref::<Option<Vec<CommandDataOption>>>())
-            .collect::<Vec<&Option<Vec<CommandDataOption>>>>()[0]
-            .as_ref()
-            .unwrap();
-
-        let poll_name = match options.iter().find(|option| option.name == "name") {
-            Some(option) => option.value.as_str().unwrap(),
-            None => {
-                panic!("Poll name is required")
-            }
-        };
-        let poll_description = match options.iter().find(|option| option.name == "description") {
-            Some(option) => Some(option.value.as_str().unwrap().to_string()),
-            None => None,
+            .find(|option| option.name == "name")
+            .and_then(|option| option.value.as_str())
+        {
+            Some(poll_name) => poll_name,
+            None => return Err(BostilError::validation("Poll name is required")),
         };
-        let ctx = args
+        let poll_description = options
             .iter()
-            .find_map(|arg| arg.downcast_ref::<Context>())
-            .unwrap();
-        let channel_id = args
-            .iter()
-            .find_map(|arg| arg.downcast_ref::<ChannelId>())
-            .unwrap();
-        let user_id = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<User>())
-            .collect::<Vec<&User>>()
-            .get(0)
-            .unwrap()
-            .id;
+            .find(|option| option.name == "description")
+            .and_then(|option| option.value.as_str())
+            .map(String::from);
+        let user_id = user.id;
 
         // Step 1: Create thread
         let thread_channel = channel_id
@@ -81,38 +79,74 @@ impl CommandRunnerFn for CreatePollRunner {
             .add_thread_member(&ctx.http, user_id)
             .await?;
 
-        // Step 2: Create a partial poll and send it to the thread
-        let mut embed_message = match SETUP_EMBED.send_message(&ctx, &thread_channel).await {
+        // Step 2: Create a partial poll (configured by the setup embed) and send it to the thread
+        let poll_id = Uuid::new_v4();
+        let connection = &mut establish_connection();
+
+        diesel::insert_into(polls::table)
+            .values((
+                polls::id.eq(poll_id),
+                polls::name.eq(poll_name),
+                polls::description.eq(poll_description),
+                polls::kind.eq(PollKind::SingleChoice),
+                polls::timer.eq(0),
+                polls::thread_id.eq(ChannelIdWrapper(thread_channel.id)),
+                polls::created_by.eq(UserIdWrapper(user_id)),
+            ))
+            .execute(connection)
+            .map_err(BostilError::database)?;
+
+        let arguments = json!({ "poll_id": poll_id.to_string(), "stage": PollStage::Setup.name() });
+
+        let mut embed_message = match EMBEDS
+            .send(
+                &ctx,
+                &SETUP_EMBED.name,
+                thread_channel.id,
+                &locale,
+                arguments,
+            )
+            .await
+        {
             Ok(message) => message,
-            Err(_) => {
-                error!("Failed to send message to thread {}", thread_channel.id);
-
-                return Ok(CommandResponse::String(
-                    t!("commands.poll.setup.response.error", "thread_id" => thread_channel.id)
-                        .to_string(),
-                ));
+            Err(why) => {
+                error!(
+                    "Failed to send message to thread {}: {}",
+                    thread_channel.id, why
+                );
+
+                // the poll can't be configured without its embed
+                if let Err(why) = diesel::delete(polls::table.find(poll_id)).execute(connection) {
+                    error!("Failed to delete poll {}: {}", poll_id, why);
+                }
+
+                return Err(
+                    BostilError::user_facing("commands.poll.setup.response.error")
+                        .with("thread_id", thread_channel.id),
+                );
             }
         };
 
         // Step 3: Add buttons to the message to choose between add options, starting poll and cancel
+        // (their custom_id carries the poll id, see the `poll_setup` component listeners)
         embed_message
             .edit(
                 &ctx.http,
                 EditMessage::default().components(vec![
                     CreateActionRow::Buttons(vec![
-                        CreateButton::new("add_option")
+                        CreateButton::new(format!("add_option/{}", poll_id))
                             .style(ButtonStyle::Secondary)
                             .label("Adicionar opção"),
-                        CreateButton::new("start_poll")
+                        CreateButton::new(format!("start_poll/{}", poll_id))
                             .style(ButtonStyle::Primary)
                             .label("Iniciar votação"),
-                        CreateButton::new("cancel_poll")
+                        CreateButton::new(format!("cancel_poll/{}", poll_id))
                             .style(ButtonStyle::Danger)
                             .label("Cancelar votação"),
                     ]),
                     CreateActionRow::SelectMenu(
                         CreateSelectMenu::new(
-                            "poll_kind",
+                            format!("poll_kind/{}", poll_id),
                             CreateSelectMenuKind::String {
                                 options: vec![
                                     CreateSelectMenuOption::new("Escolha única", "single_choice")
@@ -133,16 +167,10 @@ impl CommandRunnerFn for CreatePollRunner {
             )
             .await?;
 
-        // Step 5: Add interaction listener
-        let interaction_stream = embed_message
-            .await_component_interactions(&ctx)
-            .timeout(Duration::from_secs(60 * 60 * 24)); // 1 Day to configure the poll
-
-        interaction_handler(interaction_stream, ctx).await;
-
         Ok(CommandResponse::String(
             t!(
                 "commands.poll.setup.response.initial",
+                locale = &locale,
                 "thread_id" => thread_channel.id,
             )
             .to_string(),
@@ -150,74 +178,14 @@ impl CommandRunnerFn for CreatePollRunner {
     }
 }
 
-async fn interaction_handler(interaction_stream: ComponentInteractionCollector, ctx: &Context) {
-    match interaction_stream.stream().next().await {
-        Some(interaction) => {
-            let interaction_id = interaction.data.custom_id.as_str();
-
-            match interaction_id {
-                "add_option" => add_option(interaction, ctx).await,
-                _ => {}
-            }
-        }
-
-        None => {
-            error!("No interaction received in 1 day");
-        }
-    }
-}
-
-async fn add_option(interaction: ComponentInteraction, ctx: &Context) {
-    match interaction
-        .create_response(
-            &ctx.http,
-            CreateInteractionResponse::Modal(
-                CreateModal::new(
-                    format!("option_data_poll/{}", interaction.id),
-                    "Adicionar opção",
-                )
-                .components(vec![
-                    CreateActionRow::InputText(
-                        CreateInputText::new(InputTextStyle::Short, "Nome da Opção", "name_option")
-                            .placeholder("Digite o nome da opção")
-                            .max_length(25)
-                            .min_length(1)
-                            .required(true),
-                    ),
-                    CreateActionRow::InputText(
-                        CreateInputText::new(
-                            InputTextStyle::Paragraph,
-                            "Descrição da Opção",
-                            "description_option",
-                        )
-                        .placeholder("Digite a descrição da opção")
-                        .max_length(500)
-                        .min_length(1),
-                    ),
-                ]),
-            ),
-        )
-        .await
-    {
-        Ok(_) => {}
-        Err(why) => {
-            error!("Failed to create interaction response: {}", why);
-        }
-    }
-}
-
 pub static SETUP_OPTION: Lazy<CreateCommandOption> = Lazy::new(|| {
     CreateCommandOption::new(CommandOptionType::SubCommand, "setup", "Setup a poll")
-        .name_localized("pt-BR", "configurar")
-        .description_localized("pt-BR", "Configura uma votação")
         .add_sub_option(
             CreateCommandOption::new(
                 CommandOptionType::String,
                 "name",
                 "The name of the option (max 25 characters)",
             )
-            .name_localized("pt-BR", "nome")
-            .description_localized("pt-BR", "O nome da opção (máx 25 caracteres)")
             .max_length(25)
             .required(true),
         )
@@ -227,8 +195,6 @@ pub static SETUP_OPTION: Lazy<CreateCommandOption> = Lazy::new(|| {
                 "channel",
                 "The channel where the poll will be created",
             )
-            .name_localized("pt-BR", "canal")
-            .description_localized("pt-BR", "O canal onde a votação será realizada")
             .required(true),
         )
         .add_sub_option(
@@ -237,29 +203,23 @@ pub static SETUP_OPTION: Lazy<CreateCommandOption> = Lazy::new(|| {
                 "description",
                 "The description of the option (max 365 characters)",
             )
-            .name_localized("pt-BR", "descrição")
-            .description_localized(
-                "pt-BR",
-                "A descrição dessa opção (máximo de 365 caracteres)",
-            )
             .max_length(365),
         )
 });
 
 lazy_static! {
-    pub static ref SETUP_COMMAND: Command = Command::new(
+    pub static ref SETUP_COMMAND: SubCommand = SubCommand::new(
         "setup",
         "Setup a poll",
-        CommandContext::Guild,
-        CommandCategory::Misc,
         vec![
             ArgumentsLevel::Options,
             ArgumentsLevel::Context,
             ArgumentsLevel::Guild,
             ArgumentsLevel::User,
             ArgumentsLevel::ChannelId,
+            ArgumentsLevel::Locale,
         ],
         Box::new(CreatePollRunner),
-        None,
+        Some(SETUP_OPTION.clone()),
     );
 }
@@ -1,5 +1,7 @@
 // Equalizer is a struct that represents FFMPEG's equalizer filter.
 // ex: equalizer=f=1000:t=q:w=1:g=2,equalizer=f=100:t=q:w=2:g=-5
+// The equalizers aren't applied to the radio sources yet
+#![allow(dead_code, clippy::enum_variant_names)]
 
 use std::vec;
 
@@ -2,26 +2,23 @@ pub mod consumer;
 pub mod equalizers;
 
 use bostil_core::{
-    arguments::ArgumentsLevel,
-    commands::{Command, CommandCategory, CommandContext},
-    runners::runners::{CommandResponse, CommandResult, CommandRunnerFn},
+    arguments::{InvocationContext, Locale},
+    command,
+    commands::Autocomplete,
+    errors::{BostilError, BostilResult},
+    preconditions::Precondition,
+    runners::runners::{CommandResponse, CommandResult},
 };
-use lazy_static::lazy_static;
 use rust_i18n::t;
 use serenity::{
     all::{CommandDataOption, CommandDataOptionValue, CommandOptionType, Guild, User},
-    async_trait,
-    builder::{CreateCommand, CreateCommandOption},
-    framework::standard::CommandResult as SerenityCommandResult,
+    builder::{AutocompleteChoice, CreateCommand, CreateCommandOption},
     prelude::Context,
 };
-use tracing::{debug, error};
+use tracing::debug;
 
 use crate::modules::core::actions::voice::join;
 
-#[derive(Clone)]
-struct RadioCommand;
-
 #[derive(Debug, Clone, Copy)]
 pub enum Radio {
     CanoaGrandeFM,
@@ -33,6 +30,15 @@ pub enum Radio {
 }
 
 impl Radio {
+    pub const ALL: [Radio; 6] = [
+        Radio::CanoaGrandeFM,
+        Radio::PingoNosIFs,
+        Radio::TupiFM,
+        Radio::EightyEightFM,
+        Radio::EightyNineFM,
+        Radio::NinetyFourFm,
+    ];
+
     pub fn get_url(&self) -> Option<String> {
         match self {
             Radio::CanoaGrandeFM => {
@@ -47,16 +53,6 @@ impl Radio {
             Radio::PingoNosIFs => None,
         }
     }
-    pub fn to_string(&self) -> String {
-        match self {
-            Radio::CanoaGrandeFM => "Canoa Grande FM".to_string(),
-            Radio::PingoNosIFs => "Pingo nos IFs".to_string(),
-            Radio::TupiFM => "Tupi FM".to_string(),
-            Radio::EightyNineFM => "89 FM".to_string(),
-            Radio::EightyEightFM => "88.3 FM".to_string(),
-            Radio::NinetyFourFm => "94 FM".to_string(),
-        }
-    }
 }
 
 impl std::fmt::Display for Radio {
@@ -72,142 +68,167 @@ impl std::fmt::Display for Radio {
     }
 }
 
-#[async_trait]
-impl CommandRunnerFn for RadioCommand {
-    async fn run<'a>(&self, args: &Vec<Box<dyn std::any::Any + Send + Sync>>) -> CommandResult<'a> {
-        let ctx = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Context>())
-            .collect::<Vec<&Context>>()[0];
-        let guild = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Guild>())
-            .collect::<Vec<&Guild>>()[0];
-        let user = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<User>())
-            .collect::<Vec<&User>>()[0];
-        let options = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Option<Vec<CommandDataOption>>>())
-            .collect::<Vec<&Option<Vec<CommandDataOption>>>>()[0]
-            .as_ref()
-            .unwrap();
-
-        if let Err(_) = join(ctx, guild, &user.id).await {
-            error!("Failed to join voice channel");
-
-            return Ok(CommandResponse::String("Dá não pai".to_string()));
-        }
-
-        match run(options, ctx, guild).await {
-            Ok(response) => Ok(CommandResponse::String(response)),
-            Err(_) => Ok(CommandResponse::String("Deu não pai".to_string())),
-        }
-    }
+#[command(
+    name = "radio",
+    description = "Tune in to the best radios in \"Bostil\"",
+    context = Guild,
+    category = Voice,
+    fingerprint = fingerprint,
+    preconditions = preconditions,
+    autocomplete = autocompletes,
+)]
+async fn radio_command(
+    options: Vec<CommandDataOption>,
+    ctx: Context,
+    guild: Guild,
+    user: User,
+    locale: Locale,
+) -> CommandResult<'static> {
+    join(&ctx, &guild, &user.id, &locale).await?;
+
+    let reply = run(&options, &ctx, &guild, &locale).await?;
+
+    Ok(CommandResponse::String(reply))
 }
 
 pub async fn run(
-    options: &Vec<CommandDataOption>,
+    options: &[CommandDataOption],
     ctx: &Context,
     guild: &Guild,
-) -> SerenityCommandResult<String> {
-    let radio = match options[0].value.clone() {
-        CommandDataOptionValue::String(radio) => match radio.as_str() {
-            "Canoa Grande FM" => Radio::CanoaGrandeFM,
-            "Pingo nos IFs" => Radio::PingoNosIFs,
-            "Tupi FM" => Radio::TupiFM,
-            "89 FM" => Radio::EightyNineFM,
-            "88.3 FM" => Radio::EightyEightFM,
-            "94 FM" => Radio::NinetyFourFm,
-            _ => return Ok(t!("commands.radio.radio_not_found").to_string()),
-        },
-        _ => return Ok(t!("commands.radio.radio_not_found").to_string()),
-    };
+    locale: &str,
+) -> BostilResult<String> {
+    let radio = options
+        .iter()
+        .find(|option| option.name == "radio")
+        .and_then(|option| match &option.value {
+            CommandDataOptionValue::String(radio) => {
+                Radio::ALL.iter().find(|item| item.to_string() == *radio)
+            }
+            _ => None,
+        })
+        .copied()
+        .ok_or_else(|| BostilError::user_facing("commands.radio.radio_not_found"))?;
 
     let manager = songbird::get(ctx)
         .await
-        .expect("Songbird Voice client placed in at initialisation.")
-        .clone();
+        .ok_or_else(|| BostilError::voice("Songbird voice client not initialised"))?;
+
+    let Some(handler_lock) = manager.get(guild.id) else {
+        return Err(BostilError::user_facing("commands.radio.bot_not_connected"));
+    };
+
+    let source = consumer::get_source(radio, ctx)
+        .await
+        .map_err(|_| BostilError::user_facing("commands.radio.failed_to_get_radio_url"))?;
 
-    if let Some(handler_lock) = manager.get(guild.id) {
-        let mut voice_handler = handler_lock.lock().await;
+    handler_lock.lock().await.enqueue_input(source).await;
+    debug!("Playing radio: {}", radio.to_string());
 
-        match consumer::get_source(radio, ctx).await {
-            Ok(source) => {
-                let _ = voice_handler.enqueue_input(source.into()).await;
-                debug!("Playing radio: {}", radio.to_string());
-            }
-            Err(_) => {
-                return Ok(t!("commands.radio.failed_to_get_radio_url").to_string());
+    Ok(t!("commands.radio.reply", locale = locale, "radio_name" => radio.to_string()).to_string())
+}
+
+fn autocompletes() -> Vec<Autocomplete> {
+    vec![Autocomplete::from_fn("radio", radio_suggestions)]
+}
+
+/// Radios whose name contains the typed value
+fn radio_suggestions(_invocation: &InvocationContext, value: &str) -> Vec<AutocompleteChoice> {
+    let value = value.to_lowercase();
+
+    Radio::ALL
+        .iter()
+        .filter(|radio| radio.to_string().to_lowercase().contains(&value))
+        .map(|radio| {
+            let choice = AutocompleteChoice::new(radio.to_string(), radio.to_string());
+
+            match radio {
+                Radio::CanoaGrandeFM => choice.add_localized_name("en-US", "Big Boat FM"),
+                Radio::PingoNosIFs => choice.add_localized_name("en-US", "Ping in the IFs"),
+                _ => choice,
             }
-        }
-    } else {
-        debug!("Bot not connected to a voice channel");
+        })
+        .collect()
+}
 
-        return Ok(t!("commands.radio.bot_not_connected").to_string());
-    }
+fn preconditions() -> Vec<Precondition> {
+    vec![Precondition::UserInVoice]
+}
 
-    Ok(t!("commands.radio.reply", "radio_name" => radio.to_string()).to_string())
+fn fingerprint() -> CreateCommand {
+    CreateCommand::new("radio")
+        .description("Tune in to the best radios in Bostil")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "radio", "The radio to tune in")
+                .kind(CommandOptionType::String)
+                .required(true)
+                .set_autocomplete(true),
+        )
 }
 
-lazy_static! {
-    pub static ref RADIO_COMMAND: Command = Command::new(
-        "radio",
-        "Tune in to the best radios in \"Bostil\"",
-        CommandContext::Guild,
-        CommandCategory::Voice,
-        vec![
-            ArgumentsLevel::Options,
-            ArgumentsLevel::Context,
-            ArgumentsLevel::Guild,
-            ArgumentsLevel::User,
-        ],
-        Box::new(RadioCommand),
-        Some(
-            CreateCommand::new("radio")
-                .description("Tune in to the best radios in Bostil")
-                .description_localized("pt-BR", "Sintonize a as melhores rádios do Bostil")
-                .add_option(
-                    CreateCommandOption::new(
-                        CommandOptionType::String,
-                        "radio",
-                        "The radio to tune in",
-                    )
-                    .description_localized("pt-BR", "A rádio para sintonizar")
-                    .kind(CommandOptionType::String)
-                    .required(true)
-                    .add_string_choice_localized(
-                        "Canoa Grande FM",
-                        Radio::CanoaGrandeFM.to_string(),
-                        [("pt-BR", "Canoa Grande FM"), ("en-US", "Big Boat FM")],
-                    )
-                    .add_string_choice_localized(
-                        "Pingo nos IFs",
-                        Radio::PingoNosIFs.to_string(),
-                        [("pt-BR", "Pingo nos IFs"), ("en-US", "Ping in the IFs")],
-                    )
-                    .add_string_choice_localized(
-                        "Tupi FM",
-                        Radio::TupiFM.to_string(),
-                        [("pt-BR", "Tupi FM"), ("en-US", "Tupi FM")],
-                    )
-                    .add_string_choice_localized(
-                        "88.3 FM",
-                        Radio::EightyEightFM.to_string(),
-                        [("pt-BR", "88.3 FM"), ("en-US", "88.3 FM")],
-                    )
-                    .add_string_choice_localized(
-                        "89 FM",
-                        Radio::EightyNineFM.to_string(),
-                        [("pt-BR", "89 FM"), ("en-US", "89 FM")],
-                    )
-                    .add_string_choice_localized(
-                        "94 FM",
-                        Radio::NinetyFourFm.to_string(),
-                        [("pt-BR", "94 FM"), ("en-US", "94 FM")],
-                    ),
-                ),
-        ),
-    );
+#[cfg(test)]
+mod tests {
+    use bostil_core::{
+        errors::BostilError,
+        testing::{fakes, TestHarness},
+    };
+    use serenity::{
+        json::{json, to_value, Value},
+        model::id::UserId,
+    };
+
+    use super::RADIO_COMMAND;
+
+    #[tokio::test]
+    async fn requires_user_in_voice() {
+        let harness = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+
+        let result = harness
+            .run_command(
+                &RADIO_COMMAND,
+                vec![fakes::string_option("radio", "Tupi FM")],
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(BostilError::UserFacing { key, .. }) if key == "commands.preconditions.user_in_voice"
+        ));
+        assert!(harness.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn voice_error_without_voice_client() {
+        let mut guild = fakes::guild(1, "Bostil");
+        guild
+            .voice_states
+            .insert(UserId::new(1), fakes::voice_state(1, 1, 10));
+        let harness = TestHarness::new().await.guild(guild);
+
+        let result = harness
+            .run_command(
+                &RADIO_COMMAND,
+                vec![fakes::string_option("radio", "Tupi FM")],
+            )
+            .await;
+
+        // the harness has no songbird client, so joining the channel fails as a voice error
+        assert!(matches!(result, Err(BostilError::Voice(_))));
+    }
+
+    #[tokio::test]
+    async fn suggests_radios_by_name() {
+        let harness = TestHarness::new().await;
+
+        let choices = harness
+            .suggest(&RADIO_COMMAND, vec![fakes::focused_option("radio", "tupi")])
+            .await
+            .unwrap();
+
+        let names = choices
+            .iter()
+            .map(|choice| to_value(choice).unwrap()["name"].clone())
+            .collect::<Vec<Value>>();
+
+        assert_eq!(names, vec![json!("Tupi FM")]);
+    }
 }
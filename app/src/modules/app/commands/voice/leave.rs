@@ -1,64 +1,40 @@
 use bostil_core::{
-    arguments::ArgumentsLevel,
-    commands::{Command, CommandCategory, CommandContext},
-    runners::runners::{CommandResponse, CommandResult, CommandRunnerFn},
+    arguments::Locale,
+    command,
+    preconditions::Precondition,
+    runners::runners::{CommandResponse, CommandResult},
 };
-use lazy_static::lazy_static;
 use serenity::{
-    async_trait,
     builder::CreateCommand,
-    model::{prelude::Guild, user::User},
+    model::{id::UserId, prelude::Guild},
     prelude::Context,
 };
 
 use crate::modules::core::actions::voice::leave;
 
-#[derive(Clone)]
-struct LeaveCommand;
-
-#[async_trait]
-impl CommandRunnerFn for LeaveCommand {
-    async fn run<'a>(&self, args: &Vec<Box<dyn std::any::Any + Send + Sync>>) -> CommandResult<'a> {
-        let ctx = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Context>())
-            .collect::<Vec<&Context>>();
-        let guild = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Guild>())
-            .collect::<Vec<&Guild>>();
-        let user_id = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<User>())
-            .collect::<Vec<&User>>()
-            .get(0)
-            .unwrap()
-            .id;
+fn preconditions() -> Vec<Precondition> {
+    vec![Precondition::BotInVoice]
+}
 
-        match leave(ctx.get(0).unwrap(), guild.get(0).unwrap(), &user_id).await {
-            Ok(_) => Ok(CommandResponse::None),
-            Err(_) => Ok(CommandResponse::None),
-        }
-    }
+fn fingerprint() -> CreateCommand {
+    CreateCommand::new("leave").description("Leave the voice channel you are in")
 }
 
-lazy_static! {
-    pub static ref LEAVE_COMMAND: Command = Command::new(
-        "leave",
-        "Leave the voice channel you are in",
-        CommandContext::Guild,
-        CommandCategory::Voice,
-        vec![
-            ArgumentsLevel::Context,
-            ArgumentsLevel::Guild,
-            ArgumentsLevel::User,
-        ],
-        Box::new(LeaveCommand {}),
-        Some(
-            CreateCommand::new("leave")
-                .name_localized("pt-BR", "sair")
-                .description("Leave the voice channel you are in")
-                .description_localized("pt-BR", "Sai do canal de voz que você está"),
-        ),
-    );
+#[command(
+    name = "leave",
+    description = "Leave the voice channel you are in",
+    context = Guild,
+    category = Voice,
+    fingerprint = fingerprint,
+    preconditions = preconditions,
+)]
+async fn leave_command(
+    ctx: Context,
+    guild: Guild,
+    user_id: UserId,
+    locale: Locale,
+) -> CommandResult<'static> {
+    leave(&ctx, &guild, &user_id, &locale).await?;
+
+    Ok(CommandResponse::None)
 }
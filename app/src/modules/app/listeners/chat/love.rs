@@ -1,109 +1,60 @@
 use bostil_core::{
-    arguments::ArgumentsLevel,
-    listeners::{Listener, ListenerKind},
-    runners::runners::ListenerRunnerFn,
+    arguments::Locale,
+    cooldowns::Cooldown,
+    errors::{BostilError, BostilResult},
+    listener,
 };
-use diesel::{query_dsl::methods::FilterDsl, ExpressionMethods, RunQueryDsl};
-use lazy_static::lazy_static;
+use diesel::{query_dsl::methods::FilterDsl, ExpressionMethods, OptionalExtension, RunQueryDsl};
 use rust_i18n::t;
 use serenity::{
     all::{ChannelId, User},
-    async_trait,
     client::Context,
 };
-use std::{any::Any, cell::RefCell};
-use tracing::error;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 use crate::modules::core::{entities::user::User as UserDB, helpers::establish_connection};
 
-thread_local! {
-    static COUNTER: RefCell<u32> = RefCell::new(0);
-    static LAST_MESSAGE_TIME: RefCell<u32> = RefCell::new(0);
-}
-
-#[derive(Clone)]
-struct Love;
-
-#[async_trait]
-impl ListenerRunnerFn for Love {
-    async fn run<'a>(&self, args: &Vec<Box<dyn Any + Send + Sync>>) -> () {
-        use crate::schema::users::dsl::{username, users};
-
-        let binding = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Context>())
-            .collect::<Vec<&Context>>();
-        let ctx = *binding.first().unwrap();
-
-        let binding = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<ChannelId>())
-            .collect::<Vec<&ChannelId>>();
-        let channel = *binding.first().unwrap();
-
-        let binding = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<User>())
-            .collect::<Vec<&User>>();
-        let user_id = *binding.first().unwrap();
-
-        let connection = &mut establish_connection();
-        let user = users
-            .filter(username.eq("Isadora"))
-            .first::<UserDB>(connection)
-            .unwrap() as UserDB;
-
-        match user.id == user_id.id {
-            true => {
-                let message = COUNTER.with(|counter| {
-                    LAST_MESSAGE_TIME.with(|last_message_time| {
-                        let mut counter = counter.borrow_mut();
-                        let mut last_message_time = last_message_time.borrow_mut();
-                        let now = std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs() as u32;
+static COUNTER: AtomicU32 = AtomicU32::new(0);
 
-                        if now - *last_message_time < 5 {
-                            *last_message_time = now;
-
-                            return None.into();
-                        } else {
-                            *last_message_time = now;
-                            *counter += 1;
-
-                            if *counter == 1 {
-                                return t!("interactions.chat.love.reply", "user_id" => *user_id).into();
-                            }
+fn cooldowns() -> Vec<Cooldown> {
+    vec![Cooldown::per_user(5)]
+}
 
-                            return t!("interactions.chat.love.reply_counter", "counter" => *counter, "user_id" => *user_id)
-                                .into();
-                        }
-                    })
-                });
+/// Listener for love messages
+#[listener(
+    name = "love",
+    description = "Interact with user 'Isadora' to send love messages",
+    kind = Message,
+    cooldowns = cooldowns,
+)]
+async fn love(ctx: Context, author: User, channel: ChannelId, locale: Locale) -> BostilResult<()> {
+    use crate::schema::users::dsl::{username, users};
+
+    let connection = &mut establish_connection();
+    let user = users
+        .filter(username.eq("Isadora"))
+        .first::<UserDB>(connection)
+        .optional()
+        .map_err(BostilError::database)?;
+
+    // nothing to do while the user isn't registered
+    let Some(user) = user else {
+        return Ok(());
+    };
+
+    if user.id != author.id {
+        return Ok(());
+    }
 
-                if let Some(message) = message {
-                    if let Err(why) = channel.say(&ctx.http, message).await {
-                        error!("Error sending message: {:?}", why);
-                    }
-                }
-            }
-            false => {}
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed) + 1;
+    let message = match counter {
+        1 => t!("interactions.chat.love.reply", locale = &locale, "user_id" => author.id),
+        _ => {
+            t!("interactions.chat.love.reply_counter", locale = &locale, "counter" => counter, "user_id" => author.id)
         }
-    }
-}
+    };
+
+    channel.say(&ctx.http, message).await?;
 
-lazy_static! {
-    /// Listener for love messages
-    pub static ref LOVE_LISTENER: Listener = Listener::new(
-        "love",
-        "Interact with user 'Isadora' to send love messages",
-        ListenerKind::Message,
-        vec![
-            ArgumentsLevel::Context,
-            ArgumentsLevel::User,
-            ArgumentsLevel::ChannelId,
-        ],
-        Box::new(Love)
-    );
+    Ok(())
 }
@@ -1,3 +1 @@
 mod love;
-
-pub use love::LOVE_LISTENER;
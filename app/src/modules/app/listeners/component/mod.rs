@@ -0,0 +1,2 @@
+mod paginator;
+mod poll_setup;
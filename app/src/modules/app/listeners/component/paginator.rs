@@ -0,0 +1,48 @@
+use bostil_core::{
+    arguments::{Locale, Payload},
+    errors::BostilError,
+    listener, pagination,
+};
+use rust_i18n::t;
+use serenity::{
+    all::ComponentInteraction,
+    builder::{CreateInteractionResponse, CreateInteractionResponseMessage},
+    client::Context,
+};
+use tracing::error;
+
+/// Change the page of a paginated embed (`paginator/<action>`)
+#[listener(name = "paginator", description = "Change the page of a paginated embed", kind = Component)]
+async fn paginator(
+    ctx: Context,
+    component: ComponentInteraction,
+    Payload(action): Payload,
+    locale: Locale,
+) {
+    let why = match pagination::handle_component(&ctx, &component, &action).await {
+        Ok(_) => return,
+        Err(why) => why,
+    };
+
+    let content = match &why {
+        BostilError::UserFacing { key, .. } => t!(key.as_str(), locale = &locale).to_string(),
+        _ => {
+            error!("Cannot change the page of the paginator: {}", why);
+            t!("interactions.component.unknown", locale = &locale).to_string()
+        }
+    };
+
+    if let Err(why) = component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            ),
+        )
+        .await
+    {
+        error!("Cannot respond to paginator interaction: {}", why);
+    }
+}
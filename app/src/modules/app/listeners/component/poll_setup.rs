@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use bostil_core::{
+    arguments::Payload,
+    errors::{BostilError, BostilResult},
+    listener, register_job,
+    scheduler::{Job, Schedule, SCHEDULER},
+};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
+use once_cell::sync::Lazy;
+use serenity::{
+    all::{ComponentInteraction, ComponentInteractionDataKind, InputTextStyle},
+    builder::{CreateActionRow, CreateInputText, CreateInteractionResponse, CreateModal},
+    client::Context,
+    json::{json, Value},
+};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    modules::core::{
+        entities::{poll::Poll, PollKind, PollState},
+        helpers::establish_connection,
+    },
+    schema::polls,
+};
+
+/// Job ending a started poll when its timer runs out
+pub static CLOSE_POLL_JOB: Lazy<Job> = Lazy::new(|| {
+    Job::new("close_poll", |_, arguments: Value| async move {
+        let poll_id = arguments["poll_id"]
+            .as_str()
+            .and_then(|poll_id| uuid::Uuid::parse_str(poll_id).ok())
+            .ok_or_else(|| BostilError::validation("Poll id is required"))?;
+
+        let connection = &mut establish_connection();
+
+        // polls ended (or cancelled) before the timer are kept as they are
+        let closed = diesel::update(
+            polls::table
+                .find(poll_id)
+                .filter(polls::state.eq(PollState::Started)),
+        )
+        .set((
+            polls::state.eq(PollState::Ended),
+            polls::ended_at.eq(time::OffsetDateTime::now_utc()),
+        ))
+        .execute(connection)
+        .map_err(BostilError::database)?;
+
+        info!("Poll {} timer ran out, {} closed", poll_id, closed);
+
+        Ok(())
+    })
+});
+
+register_job!(CLOSE_POLL_JOB);
+
+/// Arguments of the job closing the poll
+fn close_poll_arguments(poll: &Poll) -> Value {
+    json!({ "poll_id": poll.id.to_string() })
+}
+
+/// Poll configured by the setup embed, named by the payload of the component custom_id (`<name>/<poll_id>`)
+fn find_poll(poll_id: Uuid) -> BostilResult<Poll> {
+    let connection = &mut establish_connection();
+
+    polls::table
+        .find(poll_id)
+        .select(Poll::as_select())
+        .first::<Poll>(connection)
+        .map_err(|why| match why {
+            diesel::result::Error::NotFound => {
+                BostilError::user_facing("commands.poll.setup.response.not_found")
+            }
+            why => BostilError::database(why),
+        })
+}
+
+/// Open the modal to add an option to the poll
+#[listener(name = "add_option", description = "Open the modal to add a poll option", kind = Component)]
+async fn add_option(
+    ctx: Context,
+    component: ComponentInteraction,
+    Payload(poll_id): Payload<Uuid>,
+) -> BostilResult<()> {
+    let poll = find_poll(poll_id)?;
+
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Modal(
+                CreateModal::new(format!("option_data_poll/{}", poll.id), "Adicionar opção")
+                    .components(vec![
+                        CreateActionRow::InputText(
+                            CreateInputText::new(
+                                InputTextStyle::Short,
+                                "Nome da Opção",
+                                "option_name",
+                            )
+                            .placeholder("Digite o nome da opção")
+                            .max_length(25)
+                            .min_length(1)
+                            .required(true),
+                        ),
+                        CreateActionRow::InputText(
+                            CreateInputText::new(
+                                InputTextStyle::Paragraph,
+                                "Descrição da Opção",
+                                "option_description",
+                            )
+                            .placeholder("Digite a descrição da opção")
+                            .max_length(500)
+                            .min_length(1),
+                        ),
+                    ]),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Change the kind of the poll (single or multiple choice)
+#[listener(name = "poll_kind", description = "Change the kind of the poll", kind = Component)]
+async fn poll_kind(
+    ctx: Context,
+    component: ComponentInteraction,
+    Payload(poll_id): Payload<Uuid>,
+) -> BostilResult<()> {
+    let kind = match &component.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => values
+            .first()
+            .and_then(|value| PollKind::from_name(value))
+            .ok_or_else(|| BostilError::validation(format!("Unknown poll kind: {:?}", values)))?,
+        _ => {
+            return Err(BostilError::validation(
+                "Poll kind must be a string select menu",
+            ))
+        }
+    };
+
+    let poll = find_poll(poll_id)?;
+    let connection = &mut establish_connection();
+
+    diesel::update(polls::table.find(poll.id))
+        .set(polls::kind.eq(kind))
+        .execute(connection)
+        .map_err(BostilError::database)?;
+
+    component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    Ok(())
+}
+
+/// Start the voting stage of the poll
+#[listener(name = "start_poll", description = "Start the poll", kind = Component)]
+async fn start_poll(
+    ctx: Context,
+    component: ComponentInteraction,
+    Payload(poll_id): Payload<Uuid>,
+) -> BostilResult<()> {
+    let poll = find_poll(poll_id)?;
+    let connection = &mut establish_connection();
+
+    diesel::update(polls::table.find(poll.id))
+        .set((
+            polls::state.eq(PollState::Started),
+            polls::started_at.eq(time::OffsetDateTime::now_utc()),
+        ))
+        .execute(connection)
+        .map_err(BostilError::database)?;
+
+    let timer = polls::table
+        .find(poll.id)
+        .select(polls::timer)
+        .first::<i64>(connection)
+        .map_err(BostilError::database)?;
+
+    if timer > 0 {
+        SCHEDULER.schedule(
+            &CLOSE_POLL_JOB.name,
+            Schedule::after(Duration::from_secs(timer as u64)),
+            close_poll_arguments(&poll),
+        )?;
+    }
+
+    component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    Ok(())
+}
+
+/// Cancel the poll, ending it without a result
+#[listener(name = "cancel_poll", description = "Cancel the poll", kind = Component)]
+async fn cancel_poll(
+    ctx: Context,
+    component: ComponentInteraction,
+    Payload(poll_id): Payload<Uuid>,
+) -> BostilResult<()> {
+    let poll = find_poll(poll_id)?;
+    let connection = &mut establish_connection();
+
+    diesel::update(polls::table.find(poll.id))
+        .set((
+            polls::state.eq(PollState::Ended),
+            polls::ended_at.eq(time::OffsetDateTime::now_utc()),
+        ))
+        .execute(connection)
+        .map_err(BostilError::database)?;
+
+    SCHEDULER.cancel_matching(&CLOSE_POLL_JOB.name, &close_poll_arguments(&poll))?;
+
+    component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 pub mod chat;
 pub mod command;
+pub mod component;
 pub mod modal;
 pub mod voice;
@@ -1,147 +1,211 @@
 use bostil_core::{
-    arguments::ArgumentsLevel,
-    listeners::{Listener, ListenerKind},
-    runners::runners::ListenerRunnerFn,
+    arguments::Payload,
+    errors::{BostilError, BostilResult},
+    listener,
 };
-use lazy_static::lazy_static;
 use serenity::{
-    all::{ActionRowComponent, Guild, ModalInteractionData, UserId},
-    async_trait,
+    all::{ActionRowComponent, ModalInteractionData},
     client::Context,
 };
-use std::any::Any;
 use tracing::{debug, error};
 use uuid::Uuid;
 
-use crate::modules::core::{
-    entities::{
-        exports::{Poll, PollChoice, PollVote},
-        poll::PollWithChoicesAndVotes,
-    },
-    helpers::establish_connection,
-};
+use crate::modules::core::{entities::exports::Poll, helpers::establish_connection};
 
-#[derive(Clone)]
-struct PollOptionModalReceiver;
-
-#[async_trait]
-impl ListenerRunnerFn for PollOptionModalReceiver {
-    async fn run<'a>(&self, args: &Vec<Box<dyn Any + Send + Sync>>) -> () {
-        use crate::schema::{poll_choices, poll_votes, polls};
-        use diesel::{ExpressionMethods, JoinOnDsl, QueryDsl, RunQueryDsl, SelectableHelper};
-
-        let ctx = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Context>())
-            .collect::<Vec<&Context>>()[0];
-        let user_id = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<UserId>())
-            .collect::<Vec<&UserId>>()[0];
-        let guild_id = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Guild>())
-            .collect::<Vec<&Guild>>()[0]
-            .id;
-        let submit_data = args
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<ModalInteractionData>())
-            .collect::<Vec<&ModalInteractionData>>()[0];
-        let poll_id = submit_data.custom_id.split("/").collect::<Vec<&str>>()[1]
-            .parse::<Uuid>()
-            .unwrap();
+/// Value of the input text with the given custom_id on the submitted modal
+fn input_value(submit_data: &ModalInteractionData, custom_id: &str) -> Option<String> {
+    submit_data
+        .components
+        .iter()
+        .flat_map(|row| row.components.iter())
+        .find_map(|component| match component {
+            ActionRowComponent::InputText(input) if input.custom_id == custom_id => {
+                input.value.clone()
+            }
+            _ => None,
+        })
+        .filter(|value| !value.is_empty())
+}
 
-        // Step 1: Recover poll data from database (join with poll_choices, and poll_votes)
-        let connection = &mut establish_connection();
-        let polls: Vec<(Poll, PollChoice, PollVote)> = polls::dsl::polls
-            .filter(polls::dsl::id.eq(poll_id))
-            .inner_join(
-                poll_choices::dsl::poll_choices.on(polls::dsl::id.eq(poll_choices::dsl::poll_id)),
-            )
-            .inner_join(poll_votes::dsl::poll_votes.on(polls::dsl::id.eq(poll_votes::dsl::poll_id)))
-            .select((
-                Poll::as_select(),
-                PollChoice::as_select(),
-                PollVote::as_select(),
-            ))
-            .load::<(Poll, PollChoice, PollVote)>(connection)
-            .expect("Error getting poll data");
-
-        let poll = PollWithChoicesAndVotes::from(polls);
-
-        println!("Poll test: {poll:?}");
-
-        // Step 2: Get new option to add to poll
-        let name = submit_data.components[0]
-            .components
-            .iter()
-            .find_map(|component| match component {
-                ActionRowComponent::InputText(input) => match input.custom_id == "option_name" {
-                    true => input.value.clone(),
-                    false => None,
-                },
-                _ => None,
-            })
-            .expect("Error getting option name");
-
-        let description = submit_data.components[1]
-            .components
-            .iter()
-            .find_map(|component| match component {
-                ActionRowComponent::InputText(input) => {
-                    match input.custom_id == "option_description" {
-                        true => input.value.clone(),
-                        false => None,
-                    }
-                }
-                _ => None,
-            });
-
-        // value is a name instead of spaces replaced by underscores
-        let value = name.clone().replace(" ", "_");
-
-        debug!("Name: {:?}, Description: {:?}", name, description);
-
-        // Step 3: Add new option to poll
-        diesel::insert_into(poll_choices::table)
-            .values((
-                poll_choices::dsl::poll_id.eq(poll.id),
-                poll_choices::dsl::value.eq(value),
-                poll_choices::dsl::label.eq(name),
-                poll_choices::dsl::description.eq(description),
-            ))
-            .execute(connection)
-            .expect("Error inserting new option");
+/// Save a poll option submitted through the poll setup modal
+#[listener(name = "option_data_poll", description = "Save a poll option", kind = Modal)]
+async fn poll_option(
+    ctx: Context,
+    submit_data: ModalInteractionData,
+    Payload(poll_id): Payload<Uuid>,
+) -> BostilResult<()> {
+    use crate::schema::{poll_choices, polls};
+    use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
 
-        // Step 4: Update poll message
-        match ctx
-            .http
-            .get_message(poll.thread_id.0, poll.embed_message_id.0)
-            .await
-        {
-            Ok(mut message) => {
-                // TODO: Get EmbedModel and use update
+    // Step 1: Recover the poll from database
+    let connection = &mut establish_connection();
+    let poll = polls::table
+        .find(poll_id)
+        .select(Poll::as_select())
+        .first::<Poll>(connection)
+        .map_err(|why| match why {
+            diesel::result::Error::NotFound => {
+                BostilError::user_facing("commands.poll.setup.response.not_found")
             }
+            why => BostilError::database(why),
+        })?;
 
-            Err(why) => {
-                error!("Error getting poll message: {:?}", why);
-            }
+    debug!("Poll: {:?}", poll);
+
+    // Step 2: Get new option to add to poll
+    let name = input_value(&submit_data, "option_name")
+        .ok_or_else(|| BostilError::validation("Option name is required"))?;
+    let description = input_value(&submit_data, "option_description");
+
+    // value is a name instead of spaces replaced by underscores
+    let value = name.replace(' ', "_");
+
+    debug!("Name: {:?}, Description: {:?}", name, description);
+
+    // Step 3: Add new option to poll
+    diesel::insert_into(poll_choices::table)
+        .values((
+            poll_choices::poll_id.eq(poll.id),
+            poll_choices::value.eq(value),
+            poll_choices::label.eq(name),
+            poll_choices::description.eq(description),
+        ))
+        .execute(connection)
+        .map_err(BostilError::database)?;
+
+    // Step 4: Update poll message
+    let Some(embed_message_id) = poll.embed_message_id else {
+        error!("Poll {} has no setup embed", poll.id);
+        return Ok(());
+    };
+
+    match ctx
+        .http
+        .get_message(poll.thread_id.0, embed_message_id.0)
+        .await
+    {
+        Ok(_message) => {
+            // TODO: Get EmbedModel and use update
+        }
+
+        Err(why) => {
+            error!("Error getting poll message: {:?}", why);
         }
     }
+
+    Ok(())
 }
 
-lazy_static! {
-    pub static ref POLL_OPTION_MODAL_INTERACTION: Listener = Listener::new(
-        "option_data_poll",
-        "Save a poll option",
-        ListenerKind::Modal,
-        vec![
-            ArgumentsLevel::ChannelId,
-            ArgumentsLevel::Context,
-            ArgumentsLevel::Guild,
-            ArgumentsLevel::User,
-            ArgumentsLevel::ModalSubmitData,
-        ],
-        Box::new(PollOptionModalReceiver),
-    );
+#[cfg(test)]
+mod tests {
+    use bostil_core::{
+        dispatchers::ListenerRouteError,
+        testing::{fakes, TestHarness},
+    };
+    use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
+    use serenity::all::{ChannelId, UserId};
+    use uuid::Uuid;
+
+    use super::OPTION_DATA_POLL_LISTENER;
+    use crate::{
+        modules::core::{
+            entities::{exports::PollChoice, ChannelIdWrapper, PollKind, UserIdWrapper},
+            helpers::{establish_connection, use_test_database},
+        },
+        schema::{poll_choices, polls},
+    };
+
+    /// Poll just created by the setup, without options
+    fn new_poll() -> Uuid {
+        let poll_id = Uuid::new_v4();
+
+        diesel::insert_into(polls::table)
+            .values((
+                polls::id.eq(poll_id),
+                polls::name.eq("Pizza"),
+                polls::kind.eq(PollKind::SingleChoice),
+                polls::timer.eq(0),
+                polls::thread_id.eq(ChannelIdWrapper(ChannelId::new(1))),
+                polls::created_by.eq(UserIdWrapper(UserId::new(1))),
+            ))
+            .execute(&mut establish_connection())
+            .unwrap();
+
+        poll_id
+    }
+
+    #[tokio::test]
+    async fn acknowledges_submission_and_shows_its_error() {
+        let harness = TestHarness::new().await;
+        let submit = fakes::modal_interaction(
+            "option_data_poll/not-a-poll",
+            &[("option_name", "Pizza")],
+            &harness.user,
+            1,
+        );
+
+        harness
+            .submit_modal(&OPTION_DATA_POLL_LISTENER, &submit)
+            .await
+            .unwrap();
+
+        let requests = harness.requests();
+
+        // the submission is acknowledged, then the invalid poll id fails before saving the option
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].path, "/interactions/1/token/callback");
+        assert_eq!(requests[1].path, "/webhooks/1/token");
+        assert_eq!(requests[1].body["flags"], 64);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Postgres database on TEST_DATABASE_URL"]
+    async fn adds_the_option_to_a_new_poll() {
+        use_test_database();
+        let poll_id = new_poll();
+        let harness = TestHarness::new().await;
+        let submit = fakes::modal_interaction(
+            &format!("option_data_poll/{}", poll_id),
+            &[
+                ("option_name", "Four cheeses"),
+                ("option_description", "The best one"),
+            ],
+            &harness.user,
+            1,
+        );
+
+        harness
+            .submit_modal(&OPTION_DATA_POLL_LISTENER, &submit)
+            .await
+            .unwrap();
+
+        let choices = poll_choices::table
+            .filter(poll_choices::poll_id.eq(poll_id))
+            .select(PollChoice::as_select())
+            .load::<PollChoice>(&mut establish_connection())
+            .unwrap();
+
+        assert_eq!(choices.len(), 1);
+        assert_eq!(choices[0].value, "Four_cheeses");
+        assert_eq!(choices[0].label, "Four cheeses");
+        assert_eq!(choices[0].description.as_deref(), Some("The best one"));
+        // only the submission is acknowledged, no error is shown
+        assert_eq!(harness.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unknown_custom_id() {
+        let harness = TestHarness::new().await;
+        let submit = fakes::modal_interaction("unknown_modal", &[], &harness.user, 1);
+
+        let result = harness
+            .submit_modal(&OPTION_DATA_POLL_LISTENER, &submit)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ListenerRouteError::UnknownCustomId(_))
+        ));
+        assert!(harness.requests().is_empty());
+    }
 }
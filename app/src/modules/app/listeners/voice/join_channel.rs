@@ -1,123 +1,223 @@
-use diesel::result::Error;
+use bostil_core::{
+    arguments::{Locale, VoiceStateUpdate},
+    cooldowns::Cooldown,
+    errors::{BostilError, BostilResult},
+    listener, register_job,
+    scheduler::Job,
+};
+use diesel::OptionalExtension;
+use once_cell::sync::Lazy;
 use rust_i18n::t;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::Mutex;
 
-use serenity::client::Context;
-use serenity::model::id::UserId;
-use serenity::model::prelude::ChannelId;
-use tokio::time;
+use serenity::{
+    all::{ChannelId, User as DiscordUser, UserId},
+    client::Context,
+};
 
 use crate::modules::core::entities::user::User;
 use crate::modules::core::entities::UserIdWrapper;
 use crate::modules::core::helpers::establish_connection;
 
-type Cache = HashMap<UserId, (u32, u32, UserId)>;
+/// How many times each user joined a voice channel (since the last clear)
+static COUNTERS: Lazy<Mutex<HashMap<UserId, u32>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 
-thread_local! {
-    static CACHE: Arc<RefCell<Cache>> = Arc::new(RefCell::new(HashMap::new()));
-}
+/// Job clearing the join counters, scheduled on startup (see `register_jobs`)
+pub static CLEAR_CACHE_JOB: Lazy<Job> = Lazy::new(|| {
+    Job::new("clear_join_counters", |_, _| async {
+        info!("Clearing cache");
 
-pub async fn clear_cache() {
-    info!("Starting clear cache task");
+        match COUNTERS.lock() {
+            Ok(mut counters) => counters.clear(),
+            Err(why) => error!("Cannot clear join counters: {}", why),
+        }
 
-    loop {
-        time::sleep(time::Duration::from_secs(86400)).await;
-        info!("Clearing cache");
+        Ok(())
+    })
+});
 
-        CACHE.with(|cache| {
-            let mut cache = cache.borrow_mut();
+register_job!(CLEAR_CACHE_JOB);
 
-            cache.clear();
-        });
-    }
+fn cooldowns() -> Vec<Cooldown> {
+    vec![Cooldown::per_user(5)]
 }
 
-pub async fn join_channel(channel: &ChannelId, ctx: &Context, user_id: &UserId) -> () {
+/// Greet the users that connect to a voice channel, counting how many times they joined
+#[listener(
+    name = "join_channel",
+    description = "Greet the users that connect to a voice channel",
+    kind = VoiceState,
+    cooldowns = cooldowns,
+)]
+pub async fn join_channel(
+    ctx: Context,
+    author: DiscordUser,
+    channel: ChannelId,
+    voice_state: VoiceStateUpdate,
+    locale: Locale,
+) -> BostilResult<()> {
     use crate::schema::users;
     use diesel::{QueryDsl, RunQueryDsl};
 
-    let members = channel
-        .to_channel(&ctx)
-        .await
-        .unwrap()
-        .guild()
-        .unwrap()
-        .members(&ctx)
-        .unwrap();
+    if !voice_state.has_connected() || author.bot {
+        return Ok(());
+    }
+
+    debug!("User connected to voice channel: {}", channel);
+
+    let Some(guild_channel) = channel.to_channel(&ctx).await?.guild() else {
+        return Ok(());
+    };
+    let members = guild_channel.members(&ctx)?;
+    let user_id = author.id;
 
     let connection = &mut establish_connection();
     let user = users::table
-        .find(UserIdWrapper(*user_id))
-        .first::<User>(connection) as Result<User, Error>;
-
-    match user {
-        Ok(user) => {
-            info!("{} joined channel", user.username);
-
-            let message = CACHE.with(|cache| {
-                let mut cache = cache.borrow_mut();
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs() as u32;
-
-                if let Some((counter, last_update, _)) = cache.get_mut(user_id) {
-                    if now - *last_update < 5 {
-                        *last_update = now;
-                        *counter += 1;
-
-                        return None;
-                    }
-                }
-
-                if let Some((counter, last_update, _)) = cache.get_mut(user_id) {
-                    if now - *last_update < 5 {
-                        *last_update = now;
-
-                        None
-                    } else {
-                        *last_update = now;
-                        *counter += 1;
-
-                        if user.username == "scaliza" {
-                            if members.len() == 1 {
-                                return t!("interactions.join_channel.scaliza.empty_channel", user_id => user_id).to_string().into();
-                            } else if members.len() >= 3 {
-                                return t!("interactions.join_channel.scaliza.many_users", user_id => user_id).to_string().into();
-                            }
-
-                            return format!("O CAPETA CHEGOU {} vezes 😡", counter).to_string().into()
-                        }
-
-                        let key = format!("interactions.join_channel.{}", (*counter as u8).min(2));
-
-                        t!(key.as_str(), user_id => user_id).to_string().into()
-                    }
-                } else {
-                    cache.insert(*user_id, (1, now, *user_id));
-                    info!("Added {} to cache", user.username);
-
-                    if user.username == "scaliza" {
-                        return t!("interactions.join_channel.scaliza.0", user_id => user_id).to_string().into();
-                    }
-
-                    return t!("interactions.join_channel.0", user_id => user_id).to_string().into();
-                }
-            });
-
-            if let Some(message) = message {
-                if let Err(why) = channel.say(&ctx.http, message).await {
-                    error!("Error sending message: {:?}", why);
-                }
-            }
+        .find(UserIdWrapper(user_id))
+        .first::<User>(connection)
+        .optional()
+        .map_err(BostilError::database)?;
+
+    let Some(user) = user else {
+        debug!("User {} not found", user_id);
+        return Ok(());
+    };
+
+    info!("{} joined channel", user.username);
+
+    let counter = match COUNTERS.lock() {
+        Ok(mut counters) => {
+            let counter = counters.entry(user_id).or_insert(0);
+            *counter += 1;
+            *counter
         }
+        Err(why) => {
+            error!("Cannot get join counters: {}", why);
+            return Ok(());
+        }
+    };
 
-        Err(_) => {
-            error!("User not found")
+    let message = match user.username == "scaliza" {
+        true if counter == 1 => {
+            t!("interactions.join_channel.scaliza.0", locale = &locale, user_id => user_id).to_string()
+        }
+        true if members.len() == 1 => {
+            t!("interactions.join_channel.scaliza.empty_channel", locale = &locale, user_id => user_id)
+                .to_string()
+        }
+        true if members.len() >= 3 => {
+            t!("interactions.join_channel.scaliza.many_users", locale = &locale, user_id => user_id)
+                .to_string()
+        }
+        true => format!("O CAPETA CHEGOU {} vezes 😡", counter),
+        false => {
+            let key = match counter {
+                1 => "interactions.join_channel.0".to_string(),
+                _ => format!("interactions.join_channel.{}", counter.min(2)),
+            };
+
+            t!(key.as_str(), locale = &locale, user_id => user_id).to_string()
         }
+    };
+
+    channel.say(&ctx.http, message).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use bostil_core::{
+        features::{FeatureKind, FeatureToggle, FEATURES},
+        scheduler::{Schedule, SCHEDULER},
+        testing::{fakes, TestHarness},
+    };
+    use serenity::{
+        json::Value,
+        model::id::{GuildId, UserId},
+    };
+
+    use super::{CLEAR_CACHE_JOB, COUNTERS, JOIN_CHANNEL_LISTENER};
+
+    async fn guild_harness(guild_id: u64) -> TestHarness {
+        TestHarness::new()
+            .await
+            .guild(fakes::guild(guild_id, "guild"))
+            .channel(30)
+    }
+
+    #[tokio::test]
+    async fn scheduled_job_clears_the_counters() {
+        let harness = TestHarness::new().await;
+        SCHEDULER.register(&CLEAR_CACHE_JOB);
+        COUNTERS.lock().unwrap().insert(UserId::new(1), 3);
+
+        let id = SCHEDULER
+            .schedule_transient(
+                &CLEAR_CACHE_JOB.name,
+                Schedule::Once(SystemTime::now()),
+                Value::Null,
+            )
+            .unwrap();
+
+        assert!(SCHEDULER.tick(&harness.context).await >= 1);
+        assert!(COUNTERS.lock().unwrap().is_empty());
+        assert_eq!(SCHEDULER.next_run(id), None);
+    }
+
+    #[tokio::test]
+    async fn greets_on_connect() {
+        let harness = guild_harness(300).await;
+        let invocation = harness
+            .invocation()
+            .voice_state(None, fakes::voice_state(300, 1, 30));
+
+        harness.dispatch(&JOIN_CHANNEL_LISTENER, &invocation).await;
+
+        // the channel is fetched before greeting, the stand-in has no channel so it stops there
+        assert!(harness
+            .requests()
+            .iter()
+            .any(|request| request.method == "GET" && request.path == "/channels/30"));
+    }
+
+    #[tokio::test]
+    async fn ignores_moves_between_channels() {
+        let harness = guild_harness(301).await;
+        let invocation = harness.invocation().voice_state(
+            Some(fakes::voice_state(301, 1, 31)),
+            fakes::voice_state(301, 1, 30),
+        );
+
+        harness.dispatch(&JOIN_CHANNEL_LISTENER, &invocation).await;
+
+        assert!(harness.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn disabled_on_the_guild() {
+        let harness = guild_harness(302).await;
+
+        FEATURES
+            .set(FeatureToggle {
+                guild_id: GuildId::new(302),
+                kind: FeatureKind::Listener,
+                name: "join_channel".to_string(),
+                enabled: false,
+            })
+            .unwrap();
+
+        let invocation = harness
+            .invocation()
+            .voice_state(None, fakes::voice_state(302, 1, 30));
+
+        harness.dispatch(&JOIN_CHANNEL_LISTENER, &invocation).await;
+
+        assert!(harness.requests().is_empty());
     }
 }
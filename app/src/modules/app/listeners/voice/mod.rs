@@ -1,3 +1,3 @@
 mod join_channel;
 
-pub use join_channel::{clear_cache, join_channel};
+pub use join_channel::{CLEAR_CACHE_JOB, JOIN_CHANNEL_LISTENER};
@@ -2,7 +2,7 @@ use bostil_core::{
     arguments::ArgumentsLevel,
     integrations::{CallbackParams, Integration},
     listeners::ListenerKind,
-    runners::runners::ListenerRunnerFn,
+    runners::runners::{ListenerResult, TypedListenerRunnerFn},
 };
 use diesel::{query_dsl::methods::FilterDsl, ExpressionMethods, RunQueryDsl};
 use lazy_static::lazy_static;
@@ -11,7 +11,6 @@ use serenity::{
     async_trait,
     gateway::ActivityData,
 };
-use std::any::Any;
 
 use crate::modules::core::{entities::user::User, helpers::establish_connection};
 
@@ -19,22 +18,13 @@ use crate::modules::core::{entities::user::User, helpers::establish_connection};
 struct Jukera;
 
 #[async_trait]
-impl ListenerRunnerFn for Jukera {
-    async fn run<'a>(&self, arguments: &Vec<Box<dyn Any + Send + Sync>>) {
-        let ctx = arguments
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Context>())
-            .collect::<Vec<&Context>>()[0];
-        let message = arguments
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<Message>())
-            .collect::<Vec<&Message>>()[0];
-        let user_id = arguments
-            .iter()
-            .filter_map(|arg| arg.downcast_ref::<UserId>())
-            .collect::<Vec<&UserId>>()[0];
+impl TypedListenerRunnerFn for Jukera {
+    type Arguments = (Context, UserId, Message);
 
+    async fn run_typed<'a>(&self, (ctx, user_id, message): Self::Arguments) -> ListenerResult {
         run(&message, &ctx, &user_id).await;
+
+        Ok(())
     }
 }
 
@@ -47,26 +37,25 @@ async fn run(message: &Message, ctx: &Context, user_id: &UserId) {
         .first::<User>(connection)
         .unwrap() as User;
 
-    match user.id == *user_id {
+    if user.id != *user_id {
+        return;
+    }
+
+    // check if message is a embed message (music session)
+    match message.embeds.is_empty() {
         true => {
-            // check if message is a embed message (music session)
-            match message.embeds.is_empty() {
-                true => {
-                    ctx.set_activity(Some(ActivityData::competing(
-                        "Campeonato de Leitada, Modalidade: Volume",
-                    )));
-                }
-                false => {
-                    let current_music = match message.embeds.first() {
-                        Some(embed) => embed.description.as_ref().unwrap(),
-                        None => return,
-                    };
+            ctx.set_activity(Some(ActivityData::competing(
+                "Campeonato de Leitada, Modalidade: Volume",
+            )));
+        }
+        false => {
+            let current_music = match message.embeds.first() {
+                Some(embed) => embed.description.as_ref().unwrap(),
+                None => return,
+            };
 
-                    ctx.set_activity(Some(ActivityData::listening(current_music)))
-                }
-            }
+            ctx.set_activity(Some(ActivityData::listening(current_music)))
         }
-        false => {}
     }
 }
 
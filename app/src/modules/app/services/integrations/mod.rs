@@ -1,5 +1,6 @@
 mod jukera;
 
+#[allow(clippy::module_inception)]
 pub mod integrations {
     pub use super::jukera::JUKERA_INTEGRATION;
 }
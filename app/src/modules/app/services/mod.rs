@@ -1 +1 @@
-pub mod integrations;
\ No newline at end of file
+pub mod integrations;
@@ -1,24 +1,90 @@
-use bostil_core::collectors::{CommandCollector, ListenerCollector};
+use bostil_core::{
+    collectors::{CommandCollector, ListenerCollector},
+    embeds::EMBEDS,
+    errors::BostilResult,
+    features::FEATURES,
+    locales::LOCALES,
+    scheduler::{Schedule, SCHEDULER},
+};
+use serenity::{all::GuildId, client::Context, json::Value};
+use std::time::Duration;
+use tracing::{error, info};
 
-use crate::modules::app::{
-    commands::commands, listeners::chat, services::integrations::integrations,
+use crate::{
+    modules::{
+        app::{listeners::voice::CLEAR_CACHE_JOB, services::integrations::integrations},
+        core::{
+            embeds::PostgresEmbedStore,
+            features::PostgresFeatureStore,
+            jobs::PostgresJobStore,
+            locales::{translate, PostgresLocaleStore},
+            middlewares::AuditLogMiddleware,
+        },
+    },
+    COMMAND_COLLECTOR,
 };
 
 /// Command registration
+///
+/// Commands are declared with `#[command]` (or `register_command!`) and collected automatically
 pub fn register_commands(collector: &mut CommandCollector) {
-    let commands = [
-        commands::language.to_command(),
-        commands::ping.to_command(),
-        commands::jingle.to_command(),
-        commands::poll.to_command(),
-        commands::radio.to_command(),
-        commands::join.to_command(),
-        commands::leave.to_command(),
-        commands::mute.to_command(),
-    ];
-
-    for command in commands.iter().cloned() {
-        collector.store_command(command);
+    collector.store_registered();
+}
+
+/// Register the middlewares executed around every command (after the built-in preconditions and cooldowns)
+pub fn register_middlewares(collector: &mut CommandCollector) {
+    collector.add_middleware(AuditLogMiddleware);
+}
+
+/// Register the embeds and the store of the recoverable ones
+///
+/// Embeds are declared with `register_embed!` and collected automatically
+pub fn register_embeds() {
+    EMBEDS.set_store(PostgresEmbedStore);
+    EMBEDS.store_registered();
+}
+
+/// Register the store of the feature toggles and load the features disabled on each guild
+pub fn register_features() {
+    FEATURES.set_store(PostgresFeatureStore);
+
+    if let Err(why) = FEATURES.load() {
+        error!("Cannot load the feature toggles: {}", why);
+    }
+}
+
+/// Register the locales that have a locale file and load the ones chosen by the guilds
+///
+/// The locale files also localize the fingerprints of the commands and are the choices of `/language`,
+/// so it must run before collecting them
+pub fn register_locales() {
+    LOCALES.set_available(
+        rust_i18n::available_locales!()
+            .into_iter()
+            .map(String::from)
+            .collect(),
+    );
+    LOCALES.set_translator(translate);
+    LOCALES.set_store(PostgresLocaleStore);
+
+    if let Err(why) = LOCALES.load() {
+        error!("Cannot load the locales of the guilds: {}", why);
+    }
+}
+
+/// Register the jobs and the store of the pending ones, and schedule the recurring jobs
+///
+/// Jobs are declared with `register_job!` and collected automatically, the stored ones are resumed on ready
+pub fn register_jobs() {
+    SCHEDULER.set_store(PostgresJobStore);
+    SCHEDULER.store_registered();
+
+    if let Err(why) = SCHEDULER.schedule_transient(
+        &CLEAR_CACHE_JOB.name,
+        Schedule::Interval(Duration::from_secs(86400)),
+        Value::Null,
+    ) {
+        error!("Cannot schedule the clear cache job: {}", why);
     }
 }
 
@@ -32,10 +98,25 @@ pub fn register_integrations(collector: &mut ListenerCollector) {
 }
 
 /// Store all the listeners
+///
+/// Listeners are declared with `#[listener]` (or `register_listener!`) and collected automatically
 pub fn register_listeners(collector: &mut ListenerCollector) {
-    let listeners = [chat::LOVE_LISTENER.to_listener()];
+    collector.store_registered();
+}
 
-    for listener in listeners.iter().cloned() {
-        collector.store_listener(listener);
-    }
+/// Register the guild commands again, after a change of what the guild sees (eg.: a disabled command
+/// or the language of the localized ones)
+pub async fn sync_guild_commands(ctx: &Context, guild_id: GuildId) -> BostilResult<()> {
+    let collector = match COMMAND_COLLECTOR.lock() {
+        Ok(collector) => collector.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+
+    let diff = collector
+        .sync_commands(&ctx.http, Some(guild_id), false)
+        .await?;
+
+    info!("Commands of guild {} synced: {}", guild_id, diff);
+
+    Ok(())
 }
@@ -1,112 +1,109 @@
+use std::sync::Arc;
+
+use bostil_core::errors::{BostilError, BostilResult};
 use rust_i18n::t;
 
-use serenity::framework::standard::CommandResult;
 use serenity::model::prelude::{Guild, UserId};
 use serenity::prelude::Context;
+use songbird::Songbird;
 use tracing::{debug, error, info};
 
-pub async fn join(ctx: &Context, guild: &Guild, user_id: &UserId) -> CommandResult<String> {
-    let channel_id = guild.voice_states.get(user_id).unwrap().channel_id;
+/// Voice client of songbird, registered on the client at initialisation
+async fn manager(ctx: &Context) -> BostilResult<Arc<Songbird>> {
+    songbird::get(ctx)
+        .await
+        .ok_or_else(|| BostilError::voice("Songbird voice client not initialised"))
+}
 
-    debug!("User is in voice channel: {:?}", channel_id);
+pub async fn join(
+    ctx: &Context,
+    guild: &Guild,
+    user_id: &UserId,
+    locale: &str,
+) -> BostilResult<String> {
+    let channel_id = guild
+        .voice_states
+        .get(user_id)
+        .and_then(|voice_state| voice_state.channel_id);
 
-    let connect_to = match channel_id {
-        Some(channel) => channel,
-        None => {
-            error!("User is not in a voice channel");
+    debug!("User is in voice channel: {:?}", channel_id);
 
-            return Ok(t!("commands.voice.user_not_connected").to_string());
-        }
+    let Some(connect_to) = channel_id else {
+        return Err(BostilError::user_facing(
+            "commands.voice.user_not_connected",
+        ));
     };
 
     debug!("Connecting to voice channel: {}", connect_to);
 
-    let manager = songbird::get(ctx)
-        .await
-        .expect("Songbird Voice client placed in at initialisation.")
-        .clone();
-
-    debug!("Manager: {:?}", manager);
+    let manager = manager(ctx).await?;
 
     if let Err(why) = manager.join(guild.id, connect_to).await {
         error!("Failed to join voice channel: {:?}", why);
 
-        return Ok(t!("commands.voice.join_failed").to_string());
+        return Err(BostilError::user_facing("commands.voice.join_failed"));
     }
 
     info!("Joined voice channel");
 
-    Ok(t!("commands.voice.join").to_string())
+    Ok(t!("commands.voice.join", locale = locale).to_string())
 }
 
-pub async fn mute(ctx: &Context, guild: &Guild, _user_id: &UserId) -> CommandResult<String> {
-    let manager = songbird::get(ctx)
-        .await
-        .expect("Songbird Voice client placed in at initialisation.")
-        .clone();
-
-    let handler = match manager.get(guild.id) {
-        Some(handler) => handler,
-        None => {
-            error!("Bot not connected to a voice channel");
+pub async fn mute(
+    ctx: &Context,
+    guild: &Guild,
+    _user_id: &UserId,
+    locale: &str,
+) -> BostilResult<String> {
+    let manager = manager(ctx).await?;
 
-            return Ok(t!("commands.voice.bot_not_connected").to_string());
-        }
+    let Some(handler) = manager.get(guild.id) else {
+        return Err(BostilError::user_facing("commands.voice.bot_not_connected"));
     };
     let mut handler = handler.lock().await;
 
     if handler.is_mute() {
         debug!("User already muted");
     } else {
-        if let Err(why) = handler.mute(true).await {
-            error!("Failed to mute user: {:?}", why);
-        }
+        handler.mute(true).await.map_err(BostilError::voice)?;
     }
 
-    Ok(t!("commands.voice.mute").to_string())
+    Ok(t!("commands.voice.mute", locale = locale).to_string())
 }
 
-pub async fn unmute(ctx: &Context, guild: &Guild, _user_id: &UserId) -> CommandResult<String> {
-    let manager = songbird::get(ctx)
-        .await
-        .expect("Songbird Voice client placed in at initialisation.")
-        .clone();
-
-    let handler = match manager.get(guild.id) {
-        Some(handler) => handler,
-        None => {
-            error!("Bot not connected to a voice channel");
+pub async fn unmute(
+    ctx: &Context,
+    guild: &Guild,
+    _user_id: &UserId,
+    locale: &str,
+) -> BostilResult<String> {
+    let manager = manager(ctx).await?;
 
-            return Ok(t!("commands.voice.bot_not_connected").to_string());
-        }
+    let Some(handler) = manager.get(guild.id) else {
+        return Err(BostilError::user_facing("commands.voice.bot_not_connected"));
     };
     let mut handler = handler.lock().await;
 
     if handler.is_mute() {
-        if let Err(why) = handler.mute(false).await {
-            error!("Failed to unmute user: {:?}", why);
-        }
+        handler.mute(false).await.map_err(BostilError::voice)?;
     }
 
-    Ok(t!("commands.voice.un_mute").to_string())
+    Ok(t!("commands.voice.un_mute", locale = locale).to_string())
 }
 
-pub async fn leave(ctx: &Context, guild: &Guild, _user_id: &UserId) -> CommandResult<String> {
-    let manager = songbird::get(ctx)
-        .await
-        .expect("Songbird Voice client placed in at initialisation.")
-        .clone();
-    let has_handler = manager.get(guild.id).is_some();
-
-    if has_handler {
-        if let Err(why) = manager.remove(guild.id).await {
-            error!("Failed to leave voice channel: {:?}", why);
-        }
-    } else {
-        error!("Bot not connected to a voice channel");
+pub async fn leave(
+    ctx: &Context,
+    guild: &Guild,
+    _user_id: &UserId,
+    locale: &str,
+) -> BostilResult<String> {
+    let manager = manager(ctx).await?;
 
-        return Ok(t!("commands.voice.bot_not_connected").to_string());
+    if manager.get(guild.id).is_none() {
+        return Err(BostilError::user_facing("commands.voice.bot_not_connected"));
     }
 
-    Ok(t!("commands.voice.leave").to_string())
+    manager.remove(guild.id).await.map_err(BostilError::voice)?;
+
+    Ok(t!("commands.voice.leave", locale = locale).to_string())
 }
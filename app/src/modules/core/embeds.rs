@@ -0,0 +1,76 @@
+use bostil_core::{
+    embeds::{EmbedRecord, EmbedStore},
+    errors::{BostilError, BostilResult},
+};
+use diesel::prelude::*;
+
+use crate::{
+    modules::core::{
+        entities::{
+            embed::{Embed, NewEmbed},
+            ChannelIdWrapper, MessageIdWrapper,
+        },
+        helpers::establish_connection,
+    },
+    schema::embeds,
+};
+
+/// Store of the recoverable embeds on the `embeds` table
+pub struct PostgresEmbedStore;
+
+impl EmbedStore for PostgresEmbedStore {
+    fn save(&self, record: &EmbedRecord) -> BostilResult<i64> {
+        let connection = &mut establish_connection();
+        let embed = NewEmbed {
+            name: record.name.clone(),
+            channel_id: ChannelIdWrapper(record.channel_id),
+            message_id: MessageIdWrapper(record.message_id),
+            locale: record.locale.clone(),
+            arguments: record.arguments.clone(),
+        };
+
+        let saved = match record.id {
+            Some(id) => diesel::update(embeds::table.find(id))
+                .set((&embed, embeds::updated_at.eq(diesel::dsl::now)))
+                .returning(embeds::id)
+                .get_result(connection),
+            None => diesel::insert_into(embeds::table)
+                .values(&embed)
+                .returning(embeds::id)
+                .get_result(connection),
+        };
+
+        saved.map_err(BostilError::database)
+    }
+
+    fn load(&self) -> BostilResult<Vec<EmbedRecord>> {
+        let connection = &mut establish_connection();
+
+        embeds::table
+            .select(Embed::as_select())
+            .load::<Embed>(connection)
+            .map(|embeds| {
+                embeds
+                    .into_iter()
+                    .map(|embed| EmbedRecord {
+                        id: Some(embed.id),
+                        name: embed.name,
+                        channel_id: embed.channel_id.0,
+                        message_id: embed.message_id.0,
+                        locale: embed.locale,
+                        arguments: embed.arguments,
+                    })
+                    .collect()
+            })
+            .map_err(BostilError::database)
+    }
+
+    fn delete(&self, id: i64) -> BostilResult<()> {
+        let connection = &mut establish_connection();
+
+        diesel::delete(embeds::table.find(id))
+            .execute(connection)
+            .map(|_| ())
+            .map_err(BostilError::database)
+    }
+}
@@ -0,0 +1,28 @@
+use diesel::prelude::*;
+
+use super::{ChannelIdWrapper, MessageIdWrapper};
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = crate::schema::embeds)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Embed {
+    pub id: i64,
+    pub name: String,
+    pub channel_id: ChannelIdWrapper,
+    pub message_id: MessageIdWrapper,
+    pub locale: String,
+    pub arguments: serde_json::Value,
+    pub created_at: time::OffsetDateTime,
+    pub updated_at: time::OffsetDateTime,
+}
+
+#[derive(Insertable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = crate::schema::embeds)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewEmbed {
+    pub name: String,
+    pub channel_id: ChannelIdWrapper,
+    pub message_id: MessageIdWrapper,
+    pub locale: String,
+    pub arguments: serde_json::Value,
+}
@@ -0,0 +1,24 @@
+use diesel::prelude::*;
+
+use super::GuildIdWrapper;
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = crate::schema::guild_features)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct GuildFeature {
+    pub guild_id: GuildIdWrapper,
+    pub kind: String,
+    pub name: String,
+    pub enabled: bool,
+    pub updated_at: time::OffsetDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = crate::schema::guild_features)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewGuildFeature {
+    pub guild_id: GuildIdWrapper,
+    pub kind: String,
+    pub name: String,
+    pub enabled: bool,
+}
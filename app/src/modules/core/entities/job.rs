@@ -0,0 +1,26 @@
+use diesel::prelude::*;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = crate::schema::scheduled_jobs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ScheduledJob {
+    pub id: i64,
+    pub job: String,
+    pub kind: String,
+    pub expression: String,
+    pub arguments: serde_json::Value,
+    pub next_run: time::OffsetDateTime,
+    pub created_at: time::OffsetDateTime,
+    pub updated_at: time::OffsetDateTime,
+}
+
+#[derive(Insertable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = crate::schema::scheduled_jobs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewScheduledJob {
+    pub job: String,
+    pub kind: String,
+    pub expression: String,
+    pub arguments: serde_json::Value,
+    pub next_run: time::OffsetDateTime,
+}
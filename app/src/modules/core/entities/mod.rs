@@ -2,10 +2,11 @@ use diesel::{
     backend::Backend,
     deserialize::{self, FromSql, FromSqlRow},
     expression::AsExpression,
-    pg::Pg,
-    serialize::{self, ToSql},
-    sql_types::{BigInt, Integer, Nullable},
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, ToSql},
+    sql_types::{BigInt, Nullable},
 };
+use std::io::Write;
 
 use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
 
@@ -172,92 +173,92 @@ where
     }
 }
 
-#[derive(FromSqlRow, AsExpression, Debug, Clone, Copy)]
-#[diesel(sql_type = crate::schema::sql_types::Language)]
-pub enum Language {
-    En,
-    Pt,
+/// Language of a guild, named by its locale file on `public/locales` (eg.: `pt-BR`)
+///
+/// The values of the `language` Postgres enum are the names of the locale files
+#[derive(FromSqlRow, AsExpression, Debug, Clone, Copy, PartialEq, Eq)]
+#[diesel(sql_type = LanguageType)]
+pub struct Language(&'static str);
+
+impl Language {
+    /// The languages with a locale file
+    pub fn all() -> Vec<Language> {
+        rust_i18n::available_locales!()
+            .into_iter()
+            .map(Language)
+            .collect()
+    }
+
+    /// Locale of the language, as named by its locale file
+    pub fn locale(&self) -> &'static str {
+        self.0
+    }
+
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        Self::all()
+            .into_iter()
+            .find(|language| language.locale() == locale)
+    }
 }
 
-impl<DB> FromSql<LanguageType, DB> for Language
-where
-    DB: Backend,
-    String: FromSql<diesel::sql_types::VarChar, DB>,
-{
-    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
-        match String::from_sql(bytes)?.as_str() {
-            "en" => Ok(Language::En),
-            "pt" => Ok(Language::Pt),
-            _ => Err("Unrecognized enum variant".into()),
-        }
+impl ToSql<LanguageType, Pg> for Language {
+    fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.locale().as_bytes())?;
+        Ok(IsNull::No)
     }
 }
 
-impl ToSql<LanguageType, Pg> for Language
-where
-    String: ToSql<diesel::sql_types::VarChar, Pg>,
-{
-    fn to_sql(&self, out: &mut serialize::Output<Pg>) -> serialize::Result {
-        match self {
-            Language::En => <String as ToSql<diesel::sql_types::VarChar, Pg>>::to_sql(
-                &"en".to_string(),
-                &mut out.reborrow(),
-            ),
-            Language::Pt => <String as ToSql<diesel::sql_types::VarChar, Pg>>::to_sql(
-                &"pt".to_string(),
-                &mut out.reborrow(),
-            ),
-        }
+impl FromSql<LanguageType, Pg> for Language {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let locale = std::str::from_utf8(bytes.as_bytes())?;
+
+        Self::from_locale(locale)
+            .ok_or_else(|| format!("Language {} has no locale file", locale).into())
     }
 }
 
-#[repr(i32)]
-#[derive(FromSqlRow, AsExpression, Debug, Clone, Copy)]
-#[diesel(sql_type = crate::schema::sql_types::PollKind)]
+/// Kind of a poll, stored as the `poll_kind` Postgres enum
+#[derive(FromSqlRow, AsExpression, Debug, Clone, Copy, PartialEq, Eq)]
+#[diesel(sql_type = PollKindType)]
 pub enum PollKind {
     SingleChoice,
     MultipleChoice,
 }
 
 impl PollKind {
-    pub fn to_int(&self) -> i32 {
+    pub const ALL: [PollKind; 2] = [PollKind::SingleChoice, PollKind::MultipleChoice];
+
+    /// Name of the kind, as the value of the Postgres enum
+    pub fn name(&self) -> &'static str {
         match self {
-            PollKind::SingleChoice => 0,
-            PollKind::MultipleChoice => 1,
+            PollKind::SingleChoice => "single_choice",
+            PollKind::MultipleChoice => "multiple_choice",
         }
     }
 
-    pub fn from_i32(value: i32) -> Option<Self> {
-        match value {
-            0 => Some(PollKind::SingleChoice),
-            1 => Some(PollKind::MultipleChoice),
-            _ => None,
-        }
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
     }
 }
 
-impl ToSql<PollKindType, Pg> for PollKind
-where
-    i32: ToSql<Integer, Pg>,
-{
+impl ToSql<PollKindType, Pg> for PollKind {
     fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, Pg>) -> serialize::Result {
-        <i32 as ToSql<Integer, Pg>>::to_sql(&self.to_int(), &mut out.reborrow())
+        out.write_all(self.name().as_bytes())?;
+        Ok(IsNull::No)
     }
 }
 
-impl<DB: Backend> FromSql<crate::schema::sql_types::PollKind, DB> for PollKind
-where
-    DB: Backend,
-    i32: FromSql<diesel::sql_types::Integer, DB>,
-{
-    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
-        let value = i32::from_sql(bytes)?;
-        Ok(Self::from_i32(value).ok_or("Unrecognized enum variant")?)
+impl FromSql<PollKindType, Pg> for PollKind {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let name = std::str::from_utf8(bytes.as_bytes())?;
+
+        Self::from_name(name).ok_or_else(|| format!("Unrecognized poll kind: {}", name).into())
     }
 }
 
-#[derive(Debug, FromSqlRow, AsExpression, Clone, Copy)]
-#[diesel(sql_type = crate::schema::sql_types::PollState)]
+/// State of a poll, stored as the `poll_state` Postgres enum
+#[derive(Debug, FromSqlRow, AsExpression, Clone, Copy, PartialEq, Eq)]
+#[diesel(sql_type = PollStateType)]
 pub enum PollState {
     Created,
     Started,
@@ -266,48 +267,48 @@ pub enum PollState {
 }
 
 impl PollState {
-    pub fn from_i32(value: i32) -> Option<Self> {
-        match value {
-            0 => Some(PollState::Created),
-            1 => Some(PollState::Started),
-            2 => Some(PollState::Stopped),
-            3 => Some(PollState::Ended),
-            _ => None,
+    pub const ALL: [PollState; 4] = [
+        PollState::Created,
+        PollState::Started,
+        PollState::Stopped,
+        PollState::Ended,
+    ];
+
+    /// Name of the state, as the value of the Postgres enum
+    pub fn name(&self) -> &'static str {
+        match self {
+            PollState::Created => "created",
+            PollState::Started => "started",
+            PollState::Stopped => "stopped",
+            PollState::Ended => "ended",
         }
     }
 
-    pub fn to_i32(&self) -> i32 {
-        match self {
-            PollState::Created => 0,
-            PollState::Started => 1,
-            PollState::Stopped => 2,
-            PollState::Ended => 3,
-        }
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|state| state.name() == name)
     }
 }
 
-impl ToSql<PollStateType, Pg> for PollState
-where
-    i32: ToSql<Integer, Pg>,
-{
-    fn to_sql(&self, out: &mut serialize::Output<Pg>) -> serialize::Result {
-        <i32 as ToSql<Integer, Pg>>::to_sql(&self.to_i32(), &mut out.reborrow())
+impl ToSql<PollStateType, Pg> for PollState {
+    fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.name().as_bytes())?;
+        Ok(IsNull::No)
     }
 }
 
-impl<DB: Backend> FromSql<PollStateType, DB> for PollState
-where
-    DB: Backend,
-    i32: FromSql<diesel::sql_types::Integer, DB>,
-{
-    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
-        let value = i32::from_sql(bytes)?;
-        Ok(Self::from_i32(value).ok_or("Unrecognized enum variant")?)
+impl FromSql<PollStateType, Pg> for PollState {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let name = std::str::from_utf8(bytes.as_bytes())?;
+
+        Self::from_name(name).ok_or_else(|| format!("Unrecognized poll state: {}", name).into())
     }
 }
 
 pub mod exports {
+    pub use super::embed::Embed;
+    pub use super::feature::GuildFeature;
     pub use super::guild as Guild;
+    pub use super::job::ScheduledJob;
     pub use super::poll::{Poll, PollChoice, PollVote};
     pub use super::user as User;
     pub use super::Language;
@@ -315,6 +316,113 @@ pub mod exports {
     pub use super::PollState;
 }
 
+pub mod embed;
+pub mod feature;
 pub mod guild;
+pub mod job;
 pub mod poll;
 pub mod user;
+
+#[cfg(test)]
+mod tests {
+    use diesel::{dsl::sql, pg::PgConnection, prelude::*, IntoSql};
+    use diesel_migrations::MigrationHarness;
+
+    use super::{Language, PollKind, PollState};
+    use crate::{
+        modules::core::helpers::MIGRATIONS,
+        schema::sql_types::{
+            Language as LanguageType, PollKind as PollKindType, PollState as PollStateType,
+        },
+    };
+
+    /// Connection to the migrated Postgres of `TEST_DATABASE_URL` (eg.: the `database` service of
+    /// `docker-compose.local.yml`), inside a transaction that is never committed
+    fn test_connection() -> PgConnection {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let mut connection =
+            PgConnection::establish(&url).expect("Cannot connect to the test database");
+
+        connection
+            .run_pending_migrations(MIGRATIONS)
+            .expect("Cannot run the migrations");
+        connection.begin_test_transaction().unwrap();
+
+        connection
+    }
+
+    #[test]
+    fn languages_are_the_locale_files() {
+        let mut locales = Language::all()
+            .iter()
+            .map(Language::locale)
+            .collect::<Vec<&str>>();
+        locales.sort();
+
+        assert_eq!(locales, vec!["en-US", "pt-BR"]);
+        assert_eq!(Language::from_locale("fr-FR"), None);
+    }
+
+    #[test]
+    #[ignore = "requires a Postgres database on TEST_DATABASE_URL"]
+    fn language_round_trips() {
+        let connection = &mut test_connection();
+
+        for language in Language::all() {
+            let written = diesel::select(language.into_sql::<LanguageType>())
+                .get_result::<Language>(connection)
+                .unwrap();
+            let read = diesel::select(sql::<LanguageType>(&format!(
+                "'{}'::language",
+                language.locale()
+            )))
+            .get_result::<Language>(connection)
+            .unwrap();
+
+            assert_eq!(written, language);
+            assert_eq!(read, language);
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a Postgres database on TEST_DATABASE_URL"]
+    fn poll_kind_round_trips() {
+        let connection = &mut test_connection();
+
+        for kind in PollKind::ALL {
+            let written = diesel::select(kind.into_sql::<PollKindType>())
+                .get_result::<PollKind>(connection)
+                .unwrap();
+            let read = diesel::select(sql::<PollKindType>(&format!(
+                "'{}'::poll_kind",
+                kind.name()
+            )))
+            .get_result::<PollKind>(connection)
+            .unwrap();
+
+            assert_eq!(written, kind);
+            assert_eq!(read, kind);
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a Postgres database on TEST_DATABASE_URL"]
+    fn poll_state_round_trips() {
+        let connection = &mut test_connection();
+
+        for state in PollState::ALL {
+            let written = diesel::select(state.into_sql::<PollStateType>())
+                .get_result::<PollState>(connection)
+                .unwrap();
+            let read = diesel::select(sql::<PollStateType>(&format!(
+                "'{}'::poll_state",
+                state.name()
+            )))
+            .get_result::<PollState>(connection)
+            .unwrap();
+
+            assert_eq!(written, state);
+            assert_eq!(read, state);
+        }
+    }
+}
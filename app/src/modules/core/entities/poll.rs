@@ -12,7 +12,7 @@ pub struct Poll {
     pub kind: PollKind,
     pub state: PollState,
     pub thread_id: ChannelIdWrapper,
-    pub embed_message_id: MessageIdWrapper,
+    pub embed_message_id: Option<MessageIdWrapper>,
     pub poll_message_id: Option<MessageIdWrapper>,
     pub started_at: Option<time::OffsetDateTime>,
     pub ended_at: Option<time::OffsetDateTime>,
@@ -54,7 +54,7 @@ pub struct PollWithChoicesAndVotes {
     pub kind: PollKind,
     pub state: PollState,
     pub thread_id: ChannelIdWrapper,
-    pub embed_message_id: MessageIdWrapper,
+    pub embed_message_id: Option<MessageIdWrapper>,
     pub poll_message_id: Option<MessageIdWrapper>,
     pub started_at: Option<time::OffsetDateTime>,
     pub ended_at: Option<time::OffsetDateTime>,
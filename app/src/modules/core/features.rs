@@ -0,0 +1,74 @@
+use bostil_core::{
+    errors::{BostilError, BostilResult},
+    features::{FeatureKind, FeatureStore, FeatureToggle},
+};
+use diesel::prelude::*;
+use tracing::warn;
+
+use crate::{
+    modules::core::{
+        entities::{
+            feature::{GuildFeature, NewGuildFeature},
+            GuildIdWrapper,
+        },
+        helpers::establish_connection,
+    },
+    schema::guild_features,
+};
+
+/// Store of the feature toggles on the `guild_features` table
+pub struct PostgresFeatureStore;
+
+impl FeatureStore for PostgresFeatureStore {
+    fn load(&self) -> BostilResult<Vec<FeatureToggle>> {
+        let connection = &mut establish_connection();
+
+        guild_features::table
+            .select(GuildFeature::as_select())
+            .load::<GuildFeature>(connection)
+            .map(|features| {
+                features
+                    .into_iter()
+                    .filter_map(|feature| match FeatureKind::from_name(&feature.kind) {
+                        Some(kind) => Some(FeatureToggle {
+                            guild_id: feature.guild_id.0,
+                            kind,
+                            name: feature.name,
+                            enabled: feature.enabled,
+                        }),
+                        None => {
+                            warn!("Unknown feature kind: {}", feature.kind);
+                            None
+                        }
+                    })
+                    .collect()
+            })
+            .map_err(BostilError::database)
+    }
+
+    fn save(&self, toggle: &FeatureToggle) -> BostilResult<()> {
+        let connection = &mut establish_connection();
+        let feature = NewGuildFeature {
+            guild_id: GuildIdWrapper(toggle.guild_id),
+            kind: toggle.kind.name().to_string(),
+            name: toggle.name.clone(),
+            enabled: toggle.enabled,
+        };
+
+        diesel::insert_into(guild_features::table)
+            .values(&feature)
+            .on_conflict((
+                guild_features::guild_id,
+                guild_features::kind,
+                guild_features::name,
+            ))
+            .do_update()
+            .set((
+                guild_features::enabled.eq(toggle.enabled),
+                guild_features::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(connection)
+            .map(|_| ())
+            .map_err(BostilError::database)
+    }
+}
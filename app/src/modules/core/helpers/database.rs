@@ -2,7 +2,6 @@ use diesel::{pg::PgConnection, Connection};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations};
 use dotenvy::dotenv;
 
-// TODO: implementar algum jeito para que cada servidor tenha seu próprio idioma e não alterar o idioma de todos os servidores
 i18n!("public/locales", fallback = "en-US");
 
 pub fn establish_connection() -> PgConnection {
@@ -16,3 +15,22 @@ pub fn establish_connection() -> PgConnection {
 }
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
+
+/// Point `establish_connection` to the migrated Postgres of `TEST_DATABASE_URL`, for the tests of the runners
+/// that query the database (the rows they write are kept)
+#[cfg(test)]
+pub fn use_test_database() {
+    use diesel_migrations::MigrationHarness;
+    use std::sync::Once;
+
+    static MIGRATED: Once = Once::new();
+
+    MIGRATED.call_once(|| {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        std::env::set_var("DATABASE_URL", &url);
+
+        establish_connection()
+            .run_pending_migrations(MIGRATIONS)
+            .expect("Cannot run the migrations");
+    });
+}
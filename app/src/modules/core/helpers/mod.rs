@@ -1,5 +1,7 @@
 mod database;
 mod http_client;
 
+#[cfg(test)]
+pub use database::use_test_database;
 pub use database::{establish_connection, MIGRATIONS};
 pub use http_client::get_client;
@@ -0,0 +1,88 @@
+use std::time::SystemTime;
+
+use bostil_core::{
+    errors::{BostilError, BostilResult},
+    scheduler::{JobRecord, JobStore, Schedule},
+};
+use diesel::prelude::*;
+use tracing::warn;
+
+use crate::{
+    modules::core::{
+        entities::job::{NewScheduledJob, ScheduledJob},
+        helpers::establish_connection,
+    },
+    schema::scheduled_jobs,
+};
+
+/// Store of the pending jobs on the `scheduled_jobs` table
+pub struct PostgresJobStore;
+
+impl JobStore for PostgresJobStore {
+    fn save(&self, record: &JobRecord) -> BostilResult<i64> {
+        let connection = &mut establish_connection();
+        let job = NewScheduledJob {
+            job: record.job.clone(),
+            kind: record.schedule.kind().to_string(),
+            expression: record.schedule.expression(),
+            arguments: record.arguments.clone(),
+            next_run: time::OffsetDateTime::from(record.next_run),
+        };
+
+        let saved = match record.id {
+            Some(id) => diesel::update(scheduled_jobs::table.find(id))
+                .set((&job, scheduled_jobs::updated_at.eq(diesel::dsl::now)))
+                .returning(scheduled_jobs::id)
+                .get_result(connection),
+            None => diesel::insert_into(scheduled_jobs::table)
+                .values(&job)
+                .returning(scheduled_jobs::id)
+                .get_result(connection),
+        };
+
+        saved.map_err(BostilError::database)
+    }
+
+    fn load(&self) -> BostilResult<Vec<JobRecord>> {
+        let connection = &mut establish_connection();
+
+        scheduled_jobs::table
+            .select(ScheduledJob::as_select())
+            .load::<ScheduledJob>(connection)
+            .map(|jobs| {
+                jobs.into_iter()
+                    .filter_map(|job| {
+                        let next_run = SystemTime::from(job.next_run);
+
+                        let Some(schedule) =
+                            Schedule::from_parts(&job.kind, &job.expression, next_run)
+                        else {
+                            warn!(
+                                "Invalid schedule of job {}: {} {}",
+                                job.id, job.kind, job.expression
+                            );
+                            return None;
+                        };
+
+                        Some(JobRecord {
+                            id: Some(job.id),
+                            job: job.job,
+                            schedule,
+                            arguments: job.arguments,
+                            next_run,
+                        })
+                    })
+                    .collect()
+            })
+            .map_err(BostilError::database)
+    }
+
+    fn delete(&self, id: i64) -> BostilResult<()> {
+        let connection = &mut establish_connection();
+
+        diesel::delete(scheduled_jobs::table.find(id))
+            .execute(connection)
+            .map(|_| ())
+            .map_err(BostilError::database)
+    }
+}
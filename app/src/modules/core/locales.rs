@@ -0,0 +1,155 @@
+use bostil_core::{
+    errors::{BostilError, BostilResult},
+    locales::LocaleStore,
+};
+use diesel::prelude::*;
+use serenity::all::GuildId;
+
+use crate::{
+    modules::core::{
+        entities::{GuildIdWrapper, Language},
+        helpers::establish_connection,
+    },
+    schema::guilds,
+};
+
+/// Text of the key on the locale file, without the fallback to the default locale of `t!`
+pub fn translate(locale: &str, key: &str) -> Option<String> {
+    crate::_RUST_I18N_BACKEND
+        .translate(locale, key)
+        .map(String::from)
+}
+
+/// Store of the guild locales on the `language` column of the `guilds` table
+pub struct PostgresLocaleStore;
+
+impl LocaleStore for PostgresLocaleStore {
+    fn load(&self) -> BostilResult<Vec<(GuildId, String)>> {
+        let connection = &mut establish_connection();
+
+        guilds::table
+            .select((guilds::id, guilds::language))
+            .load::<(GuildIdWrapper, Language)>(connection)
+            .map(|guilds| {
+                guilds
+                    .into_iter()
+                    .map(|(guild_id, language)| (guild_id.0, language.locale().to_string()))
+                    .collect()
+            })
+            .map_err(BostilError::database)
+    }
+
+    fn save(&self, guild_id: GuildId, locale: &str) -> BostilResult<()> {
+        let Some(language) = Language::from_locale(locale) else {
+            return Err(BostilError::validation(format!(
+                "Locale {} has no language",
+                locale
+            )));
+        };
+        let connection = &mut establish_connection();
+
+        diesel::insert_into(guilds::table)
+            .values((
+                guilds::id.eq(GuildIdWrapper(guild_id)),
+                guilds::language.eq(language),
+            ))
+            .on_conflict(guilds::id)
+            .do_update()
+            .set((
+                guilds::language.eq(language),
+                guilds::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(connection)
+            .map(|_| ())
+            .map_err(BostilError::database)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use bostil_core::{
+        collectors::CommandCollector, locales::LOCALES, testing::catalog::CatalogReport,
+    };
+    use serenity::json::Value;
+
+    use super::translate;
+
+    /// Collect the names, descriptions and choices of the fingerprint (and of its options) not localized on a locale
+    fn unlocalized(value: &Value, path: &str, locales: &[String], missing: &mut Vec<String>) {
+        for locale in locales {
+            let name = value["name_localizations"][locale]
+                .as_str()
+                .unwrap_or_default();
+
+            // names are lowercase and without spaces on Discord
+            if name.is_empty()
+                || name != name.to_lowercase()
+                || name.contains(' ')
+                || name.chars().count() > 32
+            {
+                missing.push(format!("{} name [{}]", path, locale));
+            }
+
+            if value["description_localizations"][locale]
+                .as_str()
+                .is_none()
+            {
+                missing.push(format!("{} description [{}]", path, locale));
+            }
+
+            for choice in value["choices"].as_array().into_iter().flatten() {
+                if choice["name_localizations"][locale].as_str().is_none() {
+                    missing.push(format!("{} choice {} [{}]", path, choice["value"], locale));
+                }
+            }
+        }
+
+        for option in value["options"].as_array().into_iter().flatten() {
+            let path = format!("{} {}", path, option["name"].as_str().unwrap_or_default());
+
+            unlocalized(option, &path, locales, missing);
+        }
+    }
+
+    #[test]
+    fn commands_are_localized_on_every_locale() {
+        let locales = rust_i18n::available_locales!()
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        LOCALES.set_available(locales.clone());
+        LOCALES.set_translator(translate);
+
+        let mut collector = CommandCollector::new();
+        collector.store_registered();
+
+        let mut missing = vec![];
+        for fingerprint in collector.get_fingerprints(None) {
+            let path = fingerprint["name"].as_str().unwrap_or_default().to_string();
+
+            unlocalized(&fingerprint, &path, &locales, &mut missing);
+        }
+
+        assert!(missing.is_empty(), "Not localized:\n{}", missing.join("\n"));
+    }
+
+    #[test]
+    fn locale_files_have_every_used_key() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        let report = CatalogReport::check(
+            &root.join("public/locales"),
+            &[root.join("src"), root.join("../core/src")],
+        )
+        .unwrap();
+
+        // the orphaned keys are only reported
+        if !report.orphaned.is_empty() {
+            println!("{}", report);
+        }
+
+        assert!(report.is_consistent(), "{}", report);
+    }
+}
@@ -0,0 +1,60 @@
+use bostil_core::{
+    arguments::InvocationContext, commands::ResolvedCommand, errors::BostilError,
+    middlewares::Middleware, runners::runners::CommandResponse,
+};
+use serenity::async_trait;
+use tracing::{info, warn};
+
+/// Audit log of the executed commands (who, where and how it ended)
+pub struct AuditLogMiddleware;
+
+fn guild_name(invocation: &InvocationContext) -> String {
+    match &invocation.guild {
+        Some(guild) => guild.name.clone(),
+        None => "DM".to_string(),
+    }
+}
+
+#[async_trait]
+impl Middleware for AuditLogMiddleware {
+    async fn after(
+        &self,
+        command: &ResolvedCommand<'_>,
+        invocation: &InvocationContext,
+        _response: &CommandResponse,
+    ) {
+        info!(
+            "Command \"{}\" executed by {} ({}) on {}",
+            command.name,
+            invocation.user.name,
+            invocation.user.id,
+            guild_name(invocation)
+        );
+    }
+
+    async fn on_error(
+        &self,
+        command: &ResolvedCommand<'_>,
+        invocation: &InvocationContext,
+        error: &BostilError,
+    ) {
+        match error.is_user_facing() {
+            true => info!(
+                "Command \"{}\" denied to {} ({}) on {}: {}",
+                command.name,
+                invocation.user.name,
+                invocation.user.id,
+                guild_name(invocation),
+                error
+            ),
+            false => warn!(
+                "Command \"{}\" failed for {} ({}) on {}: {}",
+                command.name,
+                invocation.user.name,
+                invocation.user.id,
+                guild_name(invocation),
+                error
+            ),
+        }
+    }
+}
@@ -1,3 +1,8 @@
 pub mod actions;
+pub mod embeds;
 pub mod entities;
+pub mod features;
 pub mod helpers;
+pub mod jobs;
+pub mod locales;
+pub mod middlewares;
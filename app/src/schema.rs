@@ -1,19 +1,52 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
-    #[derive(diesel::sql_types::SqlType, std::fmt::Debug)]
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType, std::fmt::Debug)]
     #[diesel(postgres_type(name = "language"))]
     pub struct Language;
 
-    #[derive(diesel::sql_types::SqlType, std::fmt::Debug)]
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType, std::fmt::Debug)]
     #[diesel(postgres_type(name = "poll_kind"))]
     pub struct PollKind;
 
-    #[derive(diesel::sql_types::SqlType, std::fmt::Debug)]
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType, std::fmt::Debug)]
     #[diesel(postgres_type(name = "poll_state"))]
     pub struct PollState;
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::modules::core::entities::exports::*;
+
+    embeds (id) {
+        id -> Int8,
+        #[max_length = 50]
+        name -> Varchar,
+        channel_id -> Int8,
+        message_id -> Int8,
+        arguments -> Jsonb,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        #[max_length = 10]
+        locale -> Varchar,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::modules::core::entities::exports::*;
+
+    guild_features (guild_id, kind, name) {
+        guild_id -> Int8,
+        #[max_length = 20]
+        kind -> Varchar,
+        #[max_length = 50]
+        name -> Varchar,
+        enabled -> Bool,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use crate::modules::core::entities::exports::*;
@@ -70,7 +103,7 @@ diesel::table! {
         state -> PollState,
         timer -> Int8,
         thread_id -> Int8,
-        embed_message_id -> Int8,
+        embed_message_id -> Nullable<Int8>,
         poll_message_id -> Nullable<Int8>,
         started_at -> Nullable<Timestamptz>,
         ended_at -> Nullable<Timestamptz>,
@@ -79,6 +112,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::modules::core::entities::exports::*;
+
+    scheduled_jobs (id) {
+        id -> Int8,
+        #[max_length = 50]
+        job -> Varchar,
+        #[max_length = 20]
+        kind -> Varchar,
+        #[max_length = 100]
+        expression -> Varchar,
+        arguments -> Jsonb,
+        next_run -> Timestamptz,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use crate::modules::core::entities::exports::*;
@@ -95,4 +147,13 @@ diesel::table! {
 diesel::joinable!(poll_choices -> polls (poll_id));
 diesel::joinable!(poll_votes -> polls (poll_id));
 
-diesel::allow_tables_to_appear_in_same_query!(guilds, poll_choices, poll_votes, polls, users,);
+diesel::allow_tables_to_appear_in_same_query!(
+    embeds,
+    guild_features,
+    guilds,
+    poll_choices,
+    poll_votes,
+    polls,
+    scheduled_jobs,
+    users,
+);
@@ -1,15 +1,22 @@
-use std::any::Any;
+use std::{
+    any::{type_name, Any},
+    ops::Deref,
+    str::FromStr,
+};
 
 use serenity::{
-    all::{CommandDataOption, Message, ModalInteractionData},
+    all::{CommandDataOption, ComponentInteraction, Message, ModalInteractionData, VoiceState},
     client::Context,
     model::{
         guild::Guild,
-        id::{ChannelId, InteractionId},
+        id::{ChannelId, GuildId, InteractionId, UserId},
         user::User,
+        Permissions,
     },
 };
 
+use crate::locales::LOCALES;
+
 /**
  Arguments to provide to a run function
  - `None`: No arguments
@@ -30,8 +37,18 @@ use serenity::{
    - Value: 7
  - `Message`: message (&message)
    - Value: 8
+ - `Payload`: payload of the custom_id `<name>/<payload>` (&payload)
+   - Value: 9
+ - `Component`: component interaction (&component)
+   - Value: 10
+ - `Locale`: locale of the responses, resolved for the guild and the user (&locale)
+   - Value: 11
+ - `VoiceState`: previous and new voice state of a voice state update (&voice_state)
+   - Value: 12
+ - `GuildId`: id of the guild, also provided when the guild isn't cached (&guild_id)
+   - Value: 13
 */
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ArgumentsLevel {
     None,
     Options,
@@ -42,6 +59,11 @@ pub enum ArgumentsLevel {
     ChannelId,
     ModalSubmitData,
     Message,
+    Payload,
+    Component,
+    Locale,
+    VoiceState,
+    GuildId,
 }
 
 impl ArgumentsLevel {
@@ -56,39 +78,374 @@ impl ArgumentsLevel {
             ArgumentsLevel::ChannelId => 6,
             ArgumentsLevel::ModalSubmitData => 7,
             ArgumentsLevel::Message => 8,
+            ArgumentsLevel::Payload => 9,
+            ArgumentsLevel::Component => 10,
+            ArgumentsLevel::Locale => 11,
+            ArgumentsLevel::VoiceState => 12,
+            ArgumentsLevel::GuildId => 13,
+        }
+    }
+
+    /// Merge the requested arguments with the ones required by a runner, sorted by value and without duplicates
+    pub fn merge(requested: &[ArgumentsLevel], required: &[ArgumentsLevel]) -> Vec<ArgumentsLevel> {
+        let mut merged = requested.to_vec();
+
+        for argument in required {
+            if !merged.contains(argument) {
+                merged.push(*argument);
+            }
         }
+
+        merged.sort_by_key(|argument| argument.value());
+        merged
     }
 
     // function to provide the arguments to the run function
     pub fn provide(
         requested_arguments: &Vec<ArgumentsLevel>,
-        context: &Context,
-        guild: &Guild,
-        user: &User,
-        channel_id: &ChannelId,
-        options: Option<Vec<CommandDataOption>>,
-        interaction_id: Option<InteractionId>,
-        modal_submit_data: Option<&ModalInteractionData>,
-        message: Option<Message>,
+        invocation: &InvocationContext,
     ) -> Vec<Box<dyn Any + Send + Sync>> {
         let mut arguments: Vec<Box<dyn Any + Send + Sync>> = vec![];
 
         for argument in requested_arguments {
             match argument {
                 ArgumentsLevel::None => (),
-                ArgumentsLevel::Options => arguments.push(Box::new(options.clone())),
-                ArgumentsLevel::Context => arguments.push(Box::new(context.clone())),
-                ArgumentsLevel::Guild => arguments.push(Box::new(guild.clone())),
-                ArgumentsLevel::User => arguments.push(Box::new(user.clone())),
-                ArgumentsLevel::InteractionId => arguments.push(Box::new(interaction_id.clone())),
-                ArgumentsLevel::ChannelId => arguments.push(Box::new(channel_id.clone())),
+                ArgumentsLevel::Options => arguments.push(Box::new(invocation.options.clone())),
+                ArgumentsLevel::Context => arguments.push(Box::new(invocation.context.clone())),
+                ArgumentsLevel::Guild => {
+                    if let Some(guild) = &invocation.guild {
+                        arguments.push(Box::new(guild.clone()))
+                    }
+                }
+                ArgumentsLevel::User => arguments.push(Box::new(invocation.user.clone())),
+                ArgumentsLevel::InteractionId => {
+                    if let Some(interaction_id) = invocation.interaction_id {
+                        arguments.push(Box::new(interaction_id))
+                    }
+                }
+                ArgumentsLevel::ChannelId => arguments.push(Box::new(invocation.channel_id)),
                 ArgumentsLevel::ModalSubmitData => {
-                    arguments.push(Box::new(modal_submit_data.unwrap().clone()))
+                    if let Some(modal_submit_data) = &invocation.modal_submit_data {
+                        arguments.push(Box::new(modal_submit_data.clone()))
+                    }
+                }
+                ArgumentsLevel::Message => {
+                    if let Some(message) = &invocation.message {
+                        arguments.push(Box::new(message.clone()))
+                    }
+                }
+                ArgumentsLevel::Payload => {
+                    if let Some(payload) = &invocation.payload {
+                        arguments.push(Box::new(Payload(payload.clone())))
+                    }
+                }
+                ArgumentsLevel::Component => {
+                    if let Some(component) = &invocation.component {
+                        arguments.push(Box::new(component.clone()))
+                    }
+                }
+                ArgumentsLevel::Locale => {
+                    arguments.push(Box::new(Locale(invocation.locale.clone())))
+                }
+                ArgumentsLevel::VoiceState => {
+                    if let Some(voice_state) = &invocation.voice_state {
+                        arguments.push(Box::new(voice_state.clone()))
+                    }
+                }
+                ArgumentsLevel::GuildId => {
+                    if let Some(guild_id) = invocation.guild_id {
+                        arguments.push(Box::new(guild_id))
+                    }
                 }
-                ArgumentsLevel::Message => arguments.push(Box::new(message.clone())),
             }
         }
 
         arguments
     }
 }
+
+/// Typed context of an invocation (command, listener, modal, ...), source of the arguments provided to a runner
+#[derive(Clone)]
+pub struct InvocationContext {
+    pub context: Context,
+    /// Guild of the invocation when it's cached, see `guild_id` for the uncached ones
+    pub guild: Option<Guild>,
+    /// Id of the guild of the invocation, known even when the guild isn't cached
+    pub guild_id: Option<GuildId>,
+    pub user: User,
+    pub channel_id: ChannelId,
+    pub options: Vec<CommandDataOption>,
+    pub interaction_id: Option<InteractionId>,
+    pub modal_submit_data: Option<ModalInteractionData>,
+    pub message: Option<Message>,
+    pub payload: Option<String>,
+    pub component: Option<ComponentInteraction>,
+    pub member_permissions: Option<Permissions>,
+    /// Locale of the responses, the one of the guild by default
+    pub locale: String,
+    pub voice_state: Option<VoiceStateUpdate>,
+}
+
+impl InvocationContext {
+    pub fn new(
+        context: &Context,
+        guild: Option<Guild>,
+        user: &User,
+        channel_id: &ChannelId,
+    ) -> Self {
+        Self {
+            locale: LOCALES.resolve(guild.as_ref().map(|guild| guild.id), None),
+            guild_id: guild.as_ref().map(|guild| guild.id),
+            guild,
+            context: context.clone(),
+            user: user.clone(),
+            channel_id: *channel_id,
+            options: vec![],
+            interaction_id: None,
+            modal_submit_data: None,
+            message: None,
+            payload: None,
+            component: None,
+            member_permissions: None,
+            voice_state: None,
+        }
+    }
+
+    /// Id of the guild of the event (eg.: the one of the interaction), for the guilds that aren't cached
+    pub fn guild_id(mut self, guild_id: Option<GuildId>) -> Self {
+        self.guild_id = guild_id.or(self.guild_id);
+        self
+    }
+
+    pub fn options(mut self, options: Vec<CommandDataOption>) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn interaction_id(mut self, interaction_id: InteractionId) -> Self {
+        self.interaction_id = Some(interaction_id);
+        self
+    }
+
+    pub fn modal_submit_data(mut self, modal_submit_data: ModalInteractionData) -> Self {
+        self.modal_submit_data = Some(modal_submit_data);
+        self
+    }
+
+    pub fn message(mut self, message: Message) -> Self {
+        self.message = Some(message);
+        self
+    }
+
+    pub fn payload(mut self, payload: &str) -> Self {
+        self.payload = Some(payload.to_string());
+        self
+    }
+
+    pub fn component(mut self, component: ComponentInteraction) -> Self {
+        self.component = Some(component);
+        self
+    }
+
+    pub fn member_permissions(mut self, member_permissions: Option<Permissions>) -> Self {
+        self.member_permissions = member_permissions;
+        self
+    }
+
+    /// Locale of the responses, resolved by `LOCALES` (eg.: with the locale of the interaction user)
+    pub fn locale(mut self, locale: &str) -> Self {
+        self.locale = locale.to_string();
+        self
+    }
+
+    pub fn voice_state(mut self, old: Option<VoiceState>, new: VoiceState) -> Self {
+        self.voice_state = Some(VoiceStateUpdate { old, new });
+        self
+    }
+}
+
+/// Voice state update of a user, `old` is `None` when the user wasn't on a voice channel of the guild
+#[derive(Debug, Clone)]
+pub struct VoiceStateUpdate {
+    pub old: Option<VoiceState>,
+    pub new: VoiceState,
+}
+
+impl VoiceStateUpdate {
+    /// Whether the user connected to a voice channel (eg.: not a mute or a move between channels)
+    pub fn has_connected(&self) -> bool {
+        self.old.is_none() && self.new.channel_id.is_some()
+    }
+
+    /// Whether the user disconnected from the voice channel
+    pub fn has_disconnected(&self) -> bool {
+        self.old
+            .as_ref()
+            .is_some_and(|old| old.channel_id.is_some())
+            && self.new.channel_id.is_none()
+    }
+}
+
+/// Locale of the responses of an invocation (eg.: `pt-BR`), used as `t!(key, locale = &locale)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale(pub String);
+
+impl Deref for Locale {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Payload of a custom_id in the `<name>/<payload>` format (eg.: `option_data_poll/<poll_id>`), parsed to `T`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payload<T = String>(pub T);
+
+/// Error when an argument requested by a runner was not provided
+#[derive(Debug, Clone)]
+pub enum ArgumentError {
+    /// The argument of the given type is missing
+    Missing(&'static str),
+    /// The argument of the given type was provided but could not be parsed
+    Invalid(&'static str, String),
+}
+
+impl std::fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgumentError::Missing(name) => write!(f, "Argument {} was not provided", name),
+            ArgumentError::Invalid(name, why) => write!(f, "Argument {} is invalid: {}", name, why),
+        }
+    }
+}
+
+impl std::error::Error for ArgumentError {}
+
+/// Extractor of a typed value from the arguments provided to a runner
+pub trait FromArguments: Sized {
+    /// Arguments levels that must be provided to extract the value
+    fn levels() -> Vec<ArgumentsLevel>;
+    /// Extract the value from the provided arguments
+    fn from_arguments(arguments: &[Box<dyn Any + Send + Sync>]) -> Result<Self, ArgumentError>;
+}
+
+fn find_argument<T: Clone + 'static>(
+    arguments: &[Box<dyn Any + Send + Sync>],
+) -> Result<T, ArgumentError> {
+    arguments
+        .iter()
+        .find_map(|argument| argument.downcast_ref::<T>())
+        .cloned()
+        .ok_or(ArgumentError::Missing(type_name::<T>()))
+}
+
+macro_rules! impl_from_arguments {
+    ($($type:ty => $level:ident),* $(,)?) => {
+        $(
+            impl FromArguments for $type {
+                fn levels() -> Vec<ArgumentsLevel> {
+                    vec![ArgumentsLevel::$level]
+                }
+
+                fn from_arguments(
+                    arguments: &[Box<dyn Any + Send + Sync>],
+                ) -> Result<Self, ArgumentError> {
+                    find_argument::<$type>(arguments)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_arguments! {
+    Vec<CommandDataOption> => Options,
+    Context => Context,
+    Guild => Guild,
+    User => User,
+    InteractionId => InteractionId,
+    ChannelId => ChannelId,
+    ModalInteractionData => ModalSubmitData,
+    Message => Message,
+    ComponentInteraction => Component,
+    Locale => Locale,
+    VoiceStateUpdate => VoiceState,
+    GuildId => GuildId,
+}
+
+impl FromArguments for UserId {
+    fn levels() -> Vec<ArgumentsLevel> {
+        vec![ArgumentsLevel::User]
+    }
+
+    fn from_arguments(arguments: &[Box<dyn Any + Send + Sync>]) -> Result<Self, ArgumentError> {
+        find_argument::<User>(arguments).map(|user| user.id)
+    }
+}
+
+impl<T> FromArguments for Payload<T>
+where
+    T: FromStr + 'static,
+    T::Err: std::fmt::Display,
+{
+    fn levels() -> Vec<ArgumentsLevel> {
+        vec![ArgumentsLevel::Payload]
+    }
+
+    fn from_arguments(arguments: &[Box<dyn Any + Send + Sync>]) -> Result<Self, ArgumentError> {
+        let Payload(payload) = find_argument::<Payload<String>>(arguments)?;
+
+        payload
+            .parse::<T>()
+            .map(Payload)
+            .map_err(|why| ArgumentError::Invalid(type_name::<T>(), why.to_string()))
+    }
+}
+
+/// Optional arguments are never missing (eg.: message of a listener that is not a message event)
+impl<T: FromArguments> FromArguments for Option<T> {
+    fn levels() -> Vec<ArgumentsLevel> {
+        T::levels()
+    }
+
+    fn from_arguments(arguments: &[Box<dyn Any + Send + Sync>]) -> Result<Self, ArgumentError> {
+        Ok(T::from_arguments(arguments).ok())
+    }
+}
+
+impl FromArguments for () {
+    fn levels() -> Vec<ArgumentsLevel> {
+        vec![]
+    }
+
+    fn from_arguments(_arguments: &[Box<dyn Any + Send + Sync>]) -> Result<Self, ArgumentError> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_from_arguments_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: FromArguments),+> FromArguments for ($($name,)+) {
+            fn levels() -> Vec<ArgumentsLevel> {
+                let mut levels = vec![];
+                $(levels = ArgumentsLevel::merge(&levels, &$name::levels());)+
+                levels
+            }
+
+            fn from_arguments(
+                arguments: &[Box<dyn Any + Send + Sync>],
+            ) -> Result<Self, ArgumentError> {
+                Ok(($($name::from_arguments(arguments)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_arguments_tuple!(A);
+impl_from_arguments_tuple!(A, B);
+impl_from_arguments_tuple!(A, B, C);
+impl_from_arguments_tuple!(A, B, C, D);
+impl_from_arguments_tuple!(A, B, C, D, E);
+impl_from_arguments_tuple!(A, B, C, D, E, F);
+impl_from_arguments_tuple!(A, B, C, D, E, F, G);
+impl_from_arguments_tuple!(A, B, C, D, E, F, G, H);
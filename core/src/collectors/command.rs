@@ -1,18 +1,56 @@
-use serenity::builder::CreateCommand;
+use std::sync::Arc;
 
-use crate::commands::{Command, CommandContext};
+use serde_json::Value;
+use serenity::{
+    all::{Command as RegisteredCommand, GuildId},
+    http::Http,
+};
+
+use crate::{
+    arguments::{ArgumentsLevel, InvocationContext},
+    commands::{Command, CommandContext, ResolvedCommand},
+    errors::BostilResult,
+    features::{FeatureKind, FEATURES},
+    help::CommandHelp,
+    middlewares::{CooldownsMiddleware, FeaturesMiddleware, Middleware, PreconditionsMiddleware},
+    registration::{CommandFingerprint, CommandsDiff},
+    runners::runners::CommandResult,
+};
+
+/// Command registered by the `#[command]` macro (or `register_command!`), collected on startup
+pub struct CommandRegistration(pub fn() -> Command);
+
+inventory::collect!(CommandRegistration);
+
+/// Register a command static to be collected by `CommandCollector::store_registered`
+#[macro_export]
+macro_rules! register_command {
+    ($command:path) => {
+        $crate::__private::inventory::submit! {
+            $crate::collectors::CommandRegistration(|| $command.to_command())
+        }
+    };
+}
 
 #[derive(Clone)]
 pub struct CommandCollector {
     pub commands: Vec<Command>,
     pub length: usize,
+    /// Middlewares executed around every command, in the registration order
+    pub middlewares: Vec<Arc<dyn Middleware>>,
 }
 
 impl CommandCollector {
+    /// Collector with the built-in middlewares (features, preconditions and cooldowns checks)
     pub fn new() -> Self {
         Self {
             commands: Vec::new(),
             length: 0,
+            middlewares: vec![
+                Arc::new(FeaturesMiddleware),
+                Arc::new(PreconditionsMiddleware),
+                Arc::new(CooldownsMiddleware),
+            ],
         }
     }
 
@@ -22,21 +60,165 @@ impl CommandCollector {
         self.length += 1;
     }
 
+    /// Register a middleware executed around every command, after the ones already registered
+    pub fn add_middleware(&mut self, middleware: impl Middleware + 'static) {
+        self.middlewares.push(Arc::new(middleware));
+    }
+
+    /// Run a resolved command through the middleware chain
+    ///
+    /// The `before` hooks are executed in order, then the runner, then the `after` (or `on_error`) hooks in reverse order
+    ///
+    /// Only the middlewares whose `before` hook was executed are unwound, so a middleware after the one that
+    /// denied the command never sees it
+    pub async fn run<'a>(
+        &self,
+        command: &ResolvedCommand<'_>,
+        invocation: &InvocationContext,
+    ) -> CommandResult<'a> {
+        let (entered, result) = self.run_chain(command, invocation).await;
+
+        for middleware in self.middlewares[..entered].iter().rev() {
+            match &result {
+                Ok(response) => middleware.after(command, invocation, response).await,
+                Err(why) => middleware.on_error(command, invocation, why).await,
+            }
+        }
+
+        result
+    }
+
+    /// Run the `before` hooks and the runner, returning how many middlewares were entered with the result
+    async fn run_chain<'a>(
+        &self,
+        command: &ResolvedCommand<'_>,
+        invocation: &InvocationContext,
+    ) -> (usize, CommandResult<'a>) {
+        for (index, middleware) in self.middlewares.iter().enumerate() {
+            if let Err(why) = middleware.before(command, invocation).await {
+                // the middleware that denied the command is unwound too, so it sees its own error
+                return (index + 1, Err(why));
+            }
+        }
+
+        let result = command
+            .runner
+            .run(&ArgumentsLevel::provide(command.arguments, invocation))
+            .await;
+
+        (self.middlewares.len(), result)
+    }
+
+    /// Help of all the commands, ordered by category and name
+    pub fn help(&self, locale: &str) -> Vec<CommandHelp> {
+        let mut help = self
+            .commands
+            .iter()
+            .map(|command| CommandHelp::new(command, locale))
+            .collect::<Vec<CommandHelp>>();
+
+        help.sort_by(|a, b| {
+            (a.category.position(), &a.name).cmp(&(b.category.position(), &b.name))
+        });
+
+        help
+    }
+
+    /// Help of the command with the given name (or its name on the locale)
+    pub fn find_help(&self, name: &str, locale: &str) -> Option<CommandHelp> {
+        self.help(locale)
+            .into_iter()
+            .find(|help| help.name == name)
+            .or_else(|| {
+                self.commands
+                    .iter()
+                    .find(|command| command.name == name)
+                    .map(|command| CommandHelp::new(command, locale))
+            })
+    }
+
+    /// Store all the commands registered through `#[command]` or `register_command!`
+    pub fn store_registered(&mut self) {
+        for registration in inventory::iter::<CommandRegistration> {
+            self.store_command((registration.0)());
+        }
+    }
+
     /// Get the fingerprints of all the commands in the collector
     ///
     /// Args:
     /// - `context` - The context to filter the commands by
     ///
     /// Returns:
-    /// - A vector of fingerprints of the commands
-    pub fn get_fingerprints(self, context: Option<CommandContext>) -> Vec<CreateCommand> {
+    /// - A vector of the localized fingerprints of the commands
+    pub fn get_fingerprints(self, context: Option<CommandContext>) -> Vec<Value> {
         self.commands
             .iter()
             .filter(|command| match context {
                 Some(context) => command.context == context,
                 None => true,
             })
-            .map(|command| command.fingerprint.clone().unwrap())
-            .collect::<Vec<CreateCommand>>()
+            .map(|command| command.localized_fingerprint())
+            .collect::<Vec<Value>>()
+    }
+
+    /// Compare the collected commands against the ones registered on Discord
+    ///
+    /// The commands disabled on the guild are left out, so they're unregistered from it
+    ///
+    /// Args:
+    /// - `guild_id` - The guild of the guild commands, `None` for the global commands
+    ///
+    /// Returns:
+    /// - The commands that must be created, edited or deleted
+    pub async fn diff_commands(
+        &self,
+        http: &Http,
+        guild_id: Option<GuildId>,
+    ) -> BostilResult<CommandsDiff> {
+        let (context, registered) = match guild_id {
+            Some(guild_id) => (
+                CommandContext::Guild,
+                guild_id.get_commands_with_localizations(http).await?,
+            ),
+            None => (
+                CommandContext::Global,
+                RegisteredCommand::get_global_commands_with_localizations(http).await?,
+            ),
+        };
+
+        let fingerprints = self
+            .commands
+            .iter()
+            .filter(|command| {
+                command.context == context
+                    && FEATURES.is_enabled(guild_id, FeatureKind::Command, &command.name)
+            })
+            .map(|command| CommandFingerprint::new(command.localized_fingerprint()))
+            .collect::<Vec<CommandFingerprint>>();
+
+        Ok(CommandsDiff::new(guild_id, fingerprints, &registered))
+    }
+
+    /// Register only the commands that changed, on dry-run the diff is computed but not applied
+    pub async fn sync_commands(
+        &self,
+        http: &Http,
+        guild_id: Option<GuildId>,
+        dry_run: bool,
+    ) -> BostilResult<CommandsDiff> {
+        let diff = self.diff_commands(http, guild_id).await?;
+
+        if !dry_run && !diff.is_empty() {
+            diff.apply(http).await?;
+        }
+
+        Ok(diff)
+    }
+}
+
+impl Default for CommandCollector {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -1,5 +1,20 @@
 use crate::listeners::{Listener, ListenerKind};
 
+/// Listener registered by the `#[listener]` macro (or `register_listener!`), collected on startup
+pub struct ListenerRegistration(pub fn() -> Listener);
+
+inventory::collect!(ListenerRegistration);
+
+/// Register a listener static to be collected by `ListenerCollector::store_registered`
+#[macro_export]
+macro_rules! register_listener {
+    ($listener:path) => {
+        $crate::__private::inventory::submit! {
+            $crate::collectors::ListenerRegistration(|| $listener.to_listener())
+        }
+    };
+}
+
 #[derive(Clone)]
 pub struct ListenerCollector {
     pub listeners: Vec<Listener>,
@@ -20,6 +35,13 @@ impl ListenerCollector {
         self.length += 1;
     }
 
+    /// Store all the listeners registered through `#[listener]` or `register_listener!`
+    pub fn store_registered(&mut self) {
+        for registration in inventory::iter::<ListenerRegistration> {
+            self.store_listener((registration.0)());
+        }
+    }
+
     /// Get all the listeners in the collector of a specific kind
     pub fn filter_listeners(&self, kind: ListenerKind) -> Vec<Listener> {
         self.listeners
@@ -29,3 +51,9 @@ impl ListenerCollector {
             .collect()
     }
 }
+
+impl Default for ListenerCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
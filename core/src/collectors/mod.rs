@@ -1,5 +1,5 @@
 mod command;
 mod listener;
 
-pub use command::CommandCollector;
-pub use listener::ListenerCollector;
+pub use command::{CommandCollector, CommandRegistration};
+pub use listener::{ListenerCollector, ListenerRegistration};
@@ -1,7 +1,19 @@
-use super::arguments::ArgumentsLevel;
-use crate::runners::runners::CommandRunnerFn;
+use super::arguments::{ArgumentsLevel, InvocationContext};
+use crate::cooldowns::{Cooldown, CooldownError, COOLDOWNS};
+use crate::errors::BostilError;
+use crate::locales::LOCALES;
+use crate::preconditions::{Precondition, PreconditionError};
+use crate::runners::runners::{
+    AutocompleteFn, AutocompleteProviderFn, CommandResult, CommandRunnerFn,
+};
 
-use serenity::builder::CreateCommand;
+use serde_json::{Map, Value};
+use serenity::{
+    all::{CommandDataOption, CommandDataOptionValue, CommandOptionType},
+    async_trait,
+    builder::{AutocompleteChoice, CreateCommand, CreateCommandOption},
+};
+use std::any::Any;
 
 /// Context of the command that can be used in a guild or global
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
@@ -11,7 +23,7 @@ pub enum CommandContext {
 }
 
 /// Category is a type of funcionalities that the command is used (eg.: Fun, Moderation, ...)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CommandCategory {
     Fun,
     Moderation,
@@ -35,6 +47,38 @@ impl CommandCategory {
             _ => Self::General,
         }
     }
+
+    /// All the categories, in the order they're listed on the help
+    pub const ALL: [CommandCategory; 7] = [
+        Self::General,
+        Self::Fun,
+        Self::Music,
+        Self::Voice,
+        Self::Misc,
+        Self::Moderation,
+        Self::Admin,
+    ];
+
+    /// Position of the category on the help
+    pub fn position(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|category| category == self)
+            .unwrap_or_default()
+    }
+
+    /// Identifier of the category (eg.: on locale keys)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Fun => "fun",
+            Self::Moderation => "moderation",
+            Self::Music => "music",
+            Self::Misc => "misc",
+            Self::Voice => "voice",
+            Self::Admin => "admin",
+            Self::General => "general",
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -54,6 +98,16 @@ pub struct Command {
     pub runner: Box<dyn CommandRunnerFn + Send + Sync>,
     /// Fingerprint is resgiter struct for application command
     pub fingerprint: Option<CreateCommand>,
+    /// Subcommands of the command (eg.: `/poll setup`)
+    pub subcommands: Vec<SubCommand>,
+    /// Groups of subcommands of the command (eg.: `/poll manage close`)
+    pub groups: Vec<SubCommandGroup>,
+    /// Autocomplete providers of the command options
+    pub autocompletes: Vec<Autocomplete>,
+    /// Preconditions checked before running the command (and its subcommands)
+    pub preconditions: Vec<Precondition>,
+    /// Cooldowns of the command (shared by its subcommands)
+    pub cooldowns: Vec<Cooldown>,
 }
 
 impl Command {
@@ -66,11 +120,8 @@ impl Command {
         runner: Box<dyn CommandRunnerFn + Send + Sync>,
         fingerprint: Option<CreateCommand>,
     ) -> Self {
-        let sorted_arguments = {
-            let mut sorted_arguments = arguments.clone();
-            sorted_arguments.sort_by(|a, b| a.value().cmp(&b.value()));
-            sorted_arguments
-        };
+        // arguments required by typed runners are always provided
+        let sorted_arguments = ArgumentsLevel::merge(&arguments, &runner.arguments());
 
         Self {
             runner,
@@ -80,10 +131,468 @@ impl Command {
             arguments: sorted_arguments,
             description: description.to_string(),
             name: name.to_string(),
+            subcommands: vec![],
+            groups: vec![],
+            autocompletes: vec![],
+            preconditions: vec![],
+            cooldowns: vec![],
         }
     }
 
+    /// Command that only routes to its subcommands and groups (it can't be run by itself)
+    pub fn with_subcommands(
+        name: &str,
+        description: &str,
+        context: CommandContext,
+        category: CommandCategory,
+        fingerprint: Option<CreateCommand>,
+    ) -> Self {
+        Self::new(
+            name,
+            description,
+            context,
+            category,
+            vec![],
+            Box::new(SubCommandsRunner),
+            fingerprint,
+        )
+    }
+
+    /// Add a subcommand to the command
+    pub fn add_subcommand(mut self, subcommand: SubCommand) -> Self {
+        self.subcommands.push(subcommand);
+        self
+    }
+
+    /// Add a group of subcommands to the command
+    pub fn add_group(mut self, group: SubCommandGroup) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    /// Add an autocomplete provider to an option of the command
+    ///
+    /// The option must be declared with `set_autocomplete(true)` on the fingerprint
+    pub fn add_autocomplete(mut self, autocomplete: Autocomplete) -> Self {
+        self.autocompletes.push(autocomplete);
+        self
+    }
+
+    /// Add a precondition to the command, checked before running it (or any of its subcommands)
+    pub fn add_precondition(mut self, precondition: Precondition) -> Self {
+        self.preconditions.push(precondition);
+        self
+    }
+
+    /// Add a cooldown to the command, shared by all of its subcommands
+    pub fn add_cooldown(mut self, cooldown: Cooldown) -> Self {
+        self.cooldowns.push(cooldown);
+        self
+    }
+
     pub fn to_command(&self) -> Command {
         self.clone()
     }
+
+    /// Build the fingerprint of the command with the options of its subcommands and groups
+    pub fn build_fingerprint(&self) -> CreateCommand {
+        let fingerprint = match self.fingerprint.clone() {
+            Some(fingerprint) => fingerprint,
+            None => CreateCommand::new(self.name.clone()).description(self.description.clone()),
+        };
+
+        let fingerprint = match Precondition::default_member_permissions(&self.preconditions) {
+            Some(permissions) => fingerprint.default_member_permissions(permissions),
+            None => fingerprint,
+        };
+
+        // guild commands are never on DMs, Discord doesn't return their dm_permission
+        let fingerprint = match (
+            &self.context,
+            self.preconditions.contains(&Precondition::GuildOnly),
+        ) {
+            (CommandContext::Global, true) => fingerprint.dm_permission(false),
+            _ => fingerprint,
+        };
+
+        let fingerprint = self
+            .subcommands
+            .iter()
+            .fold(fingerprint, |fingerprint, subcommand| {
+                fingerprint.add_option(subcommand.build_fingerprint())
+            });
+
+        self.groups.iter().fold(fingerprint, |fingerprint, group| {
+            fingerprint.add_option(group.build_fingerprint())
+        })
+    }
+
+    /// Fingerprint of the command as sent to Discord, with its names, descriptions and choices
+    /// localized on every available locale
+    ///
+    /// The keys are nested on `fingerprints.<command>`, the options (and subcommands) on `options.<option>`
+    /// and the choices on `choices.<value>` (eg.: `fingerprints.language.options.choose_language.choices.pt-BR`)
+    pub fn localized_fingerprint(&self) -> Value {
+        let mut fingerprint = serde_json::to_value(self.build_fingerprint()).unwrap_or_default();
+
+        localize(&mut fingerprint, &format!("fingerprints.{}", self.name));
+
+        fingerprint
+    }
+
+    /// Resolve the runner (the command itself or a subcommand) that will handle the given options
+    pub fn resolve(
+        &self,
+        options: &[CommandDataOption],
+    ) -> Result<ResolvedCommand<'_>, CommandResolveError> {
+        if self.subcommands.is_empty() && self.groups.is_empty() {
+            return Ok(ResolvedCommand {
+                name: self.name.clone(),
+                arguments: &self.arguments,
+                runner: self.runner.as_ref(),
+                autocompletes: &self.autocompletes,
+                preconditions: self.preconditions.clone(),
+                cooldowns: self.cooldowns.clone(),
+                root_name: self.name.clone(),
+                options: options.to_vec(),
+            });
+        }
+
+        let Some(option) = options.first() else {
+            return Err(CommandResolveError::UnknownSubcommand(self.name.clone()));
+        };
+        let path = format!("{} {}", self.name, option.name);
+
+        let resolved_command = match &option.value {
+            CommandDataOptionValue::SubCommand(nested_options) => self
+                .subcommands
+                .iter()
+                .find(|subcommand| subcommand.name == option.name)
+                .map(|subcommand| subcommand.resolve(path.clone(), nested_options))
+                .ok_or(CommandResolveError::UnknownSubcommand(path)),
+            CommandDataOptionValue::SubCommandGroup(nested_options) => self
+                .groups
+                .iter()
+                .find(|group| group.name == option.name)
+                .ok_or(CommandResolveError::UnknownSubcommand(path.clone()))
+                .and_then(|group| group.resolve(path, nested_options)),
+            _ => Err(CommandResolveError::UnknownSubcommand(self.name.clone())),
+        };
+
+        // preconditions of the command are checked before the ones of its subcommands
+        resolved_command.map(|mut resolved_command| {
+            resolved_command.preconditions =
+                [self.preconditions.clone(), resolved_command.preconditions].concat();
+            resolved_command.cooldowns = self.cooldowns.clone();
+            resolved_command.root_name = self.name.clone();
+
+            resolved_command
+        })
+    }
+}
+
+/// Localize the name and description of a serialized fingerprint (or option), its choices and its options
+fn localize(value: &mut Value, key: &str) {
+    localize_field(value, "name", &format!("{}.name", key));
+    localize_field(value, "description", &format!("{}.description", key));
+
+    if let Some(choices) = value.get_mut("choices").and_then(Value::as_array_mut) {
+        for choice in choices {
+            // numeric choices are keyed by their number
+            let choice_value = match &choice["value"] {
+                Value::String(choice_value) => choice_value.clone(),
+                choice_value => choice_value.to_string(),
+            };
+            let choice_key = format!("{}.choices.{}", key, choice_value);
+
+            localize_field(choice, "name", &choice_key);
+        }
+    }
+
+    if let Some(options) = value.get_mut("options").and_then(Value::as_array_mut) {
+        for option in options {
+            let option_key = format!(
+                "{}.options.{}",
+                key,
+                option["name"].as_str().unwrap_or_default()
+            );
+
+            localize(option, &option_key);
+        }
+    }
+}
+
+/// Set the localizations of the field from the key, replacing the ones already on the fingerprint
+fn localize_field(value: &mut Value, field: &str, key: &str) {
+    let localizations = LOCALES.localizations(key);
+
+    if localizations.is_empty() {
+        return;
+    }
+
+    let field = format!("{}_localizations", field);
+
+    if !value[&field].is_object() {
+        value[&field] = Value::Object(Map::new());
+    }
+
+    for (locale, localized) in localizations {
+        value[&field][locale] = Value::String(localized);
+    }
+}
+
+/// Subcommand of an application command, a leaf of the command tree with its own runner
+#[derive(Clone)]
+pub struct SubCommand {
+    /// Name is the identifier of the subcommand (unique within its parent)
+    pub name: String,
+    /// Description is a short description of the subcommand
+    pub description: String,
+    /// Arguments is a list of arguments that the subcommand uses on Runner
+    pub arguments: Vec<ArgumentsLevel>,
+    /// Runner is a function that will be executed when the subcommand is called
+    pub runner: Box<dyn CommandRunnerFn + Send + Sync>,
+    /// Fingerprint is the register struct of the subcommand option (with its own options)
+    pub fingerprint: Option<CreateCommandOption>,
+    /// Autocomplete providers of the subcommand options
+    pub autocompletes: Vec<Autocomplete>,
+    /// Preconditions checked before running the subcommand
+    pub preconditions: Vec<Precondition>,
+}
+
+impl SubCommand {
+    pub fn new(
+        name: &str,
+        description: &str,
+        arguments: Vec<ArgumentsLevel>,
+        runner: Box<dyn CommandRunnerFn + Send + Sync>,
+        fingerprint: Option<CreateCommandOption>,
+    ) -> Self {
+        Self {
+            fingerprint,
+            arguments: ArgumentsLevel::merge(&arguments, &runner.arguments()),
+            runner,
+            description: description.to_string(),
+            name: name.to_string(),
+            autocompletes: vec![],
+            preconditions: vec![],
+        }
+    }
+
+    /// Add an autocomplete provider to an option of the subcommand
+    ///
+    /// The option must be declared with `set_autocomplete(true)` on the fingerprint
+    pub fn add_autocomplete(mut self, autocomplete: Autocomplete) -> Self {
+        self.autocompletes.push(autocomplete);
+        self
+    }
+
+    /// Add a precondition to the subcommand, checked after the ones of its parent command
+    pub fn add_precondition(mut self, precondition: Precondition) -> Self {
+        self.preconditions.push(precondition);
+        self
+    }
+
+    pub fn build_fingerprint(&self) -> CreateCommandOption {
+        match self.fingerprint.clone() {
+            Some(fingerprint) => fingerprint,
+            None => CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                self.name.clone(),
+                self.description.clone(),
+            ),
+        }
+    }
+
+    fn resolve(&self, name: String, options: &[CommandDataOption]) -> ResolvedCommand<'_> {
+        ResolvedCommand {
+            name,
+            arguments: &self.arguments,
+            runner: self.runner.as_ref(),
+            autocompletes: &self.autocompletes,
+            preconditions: self.preconditions.clone(),
+            cooldowns: vec![],
+            // set by the root command that resolved it
+            root_name: String::new(),
+            options: options.to_vec(),
+        }
+    }
+}
+
+/// Group of subcommands of an application command
+#[derive(Clone)]
+pub struct SubCommandGroup {
+    /// Name is the identifier of the group (unique within its parent)
+    pub name: String,
+    /// Description is a short description of the group
+    pub description: String,
+    /// Subcommands of the group
+    pub subcommands: Vec<SubCommand>,
+    /// Fingerprint is the register struct of the group option (without the subcommands)
+    pub fingerprint: Option<CreateCommandOption>,
+}
+
+impl SubCommandGroup {
+    pub fn new(
+        name: &str,
+        description: &str,
+        subcommands: Vec<SubCommand>,
+        fingerprint: Option<CreateCommandOption>,
+    ) -> Self {
+        Self {
+            subcommands,
+            fingerprint,
+            description: description.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn build_fingerprint(&self) -> CreateCommandOption {
+        let fingerprint = match self.fingerprint.clone() {
+            Some(fingerprint) => fingerprint,
+            None => CreateCommandOption::new(
+                CommandOptionType::SubCommandGroup,
+                self.name.clone(),
+                self.description.clone(),
+            ),
+        };
+
+        self.subcommands
+            .iter()
+            .fold(fingerprint, |fingerprint, subcommand| {
+                fingerprint.add_sub_option(subcommand.build_fingerprint())
+            })
+    }
+
+    fn resolve(
+        &self,
+        path: String,
+        options: &[CommandDataOption],
+    ) -> Result<ResolvedCommand<'_>, CommandResolveError> {
+        let Some(option) = options.first() else {
+            return Err(CommandResolveError::UnknownSubcommand(path));
+        };
+        let path = format!("{} {}", path, option.name);
+
+        match &option.value {
+            CommandDataOptionValue::SubCommand(nested_options) => self
+                .subcommands
+                .iter()
+                .find(|subcommand| subcommand.name == option.name)
+                .map(|subcommand| subcommand.resolve(path.clone(), nested_options))
+                .ok_or(CommandResolveError::UnknownSubcommand(path)),
+            _ => Err(CommandResolveError::UnknownSubcommand(path)),
+        }
+    }
+}
+
+/// Leaf of the command tree that handles an interaction
+pub struct ResolvedCommand<'a> {
+    /// Full name of the resolved command (eg.: `poll setup`)
+    pub name: String,
+    /// Arguments that the resolved runner uses
+    pub arguments: &'a Vec<ArgumentsLevel>,
+    /// Runner of the resolved command
+    pub runner: &'a (dyn CommandRunnerFn + Send + Sync),
+    /// Autocomplete providers of the resolved command options
+    pub autocompletes: &'a Vec<Autocomplete>,
+    /// Preconditions of the resolved command (the ones of its parents included)
+    pub preconditions: Vec<Precondition>,
+    /// Cooldowns of the root command
+    pub cooldowns: Vec<Cooldown>,
+    /// Name of the root command (eg.: `poll` for `poll setup`), key of its cooldowns and feature toggle
+    pub root_name: String,
+    /// Options given to the resolved command (the nested options of a subcommand)
+    pub options: Vec<CommandDataOption>,
+}
+
+impl ResolvedCommand<'_> {
+    /// Maximum number of choices accepted by Discord on an autocomplete response
+    pub const MAX_AUTOCOMPLETE_CHOICES: usize = 25;
+
+    /// Check the preconditions of the resolved command, failing on the first that is not satisfied
+    pub async fn check_preconditions(
+        &self,
+        invocation: &InvocationContext,
+    ) -> Result<(), PreconditionError> {
+        Precondition::check_all(&self.preconditions, invocation).await
+    }
+
+    /// Check the cooldowns of the root command, starting them when none is active
+    pub fn check_cooldowns(&self, invocation: &InvocationContext) -> Result<(), CooldownError> {
+        COOLDOWNS.check(&self.root_name, &self.cooldowns, invocation)
+    }
+
+    /// Suggest the choices of the focused option, `None` when the option has no autocomplete provider
+    pub async fn suggest(&self, invocation: &InvocationContext) -> Option<Vec<AutocompleteChoice>> {
+        let (name, value) = self.options.iter().find_map(|option| match &option.value {
+            CommandDataOptionValue::Autocomplete { value, .. } => Some((&option.name, value)),
+            _ => None,
+        })?;
+
+        let autocomplete = self
+            .autocompletes
+            .iter()
+            .find(|autocomplete| &autocomplete.option == name)?;
+
+        let mut choices = autocomplete.provider.suggest(invocation, value).await;
+        choices.truncate(Self::MAX_AUTOCOMPLETE_CHOICES);
+
+        Some(choices)
+    }
+}
+
+/// Autocomplete provider of a command option
+#[derive(Clone)]
+pub struct Autocomplete {
+    /// Name of the option that is autocompleted
+    pub option: String,
+    /// Provider of the suggested choices
+    pub provider: Box<dyn AutocompleteProviderFn + Send + Sync>,
+}
+
+impl Autocomplete {
+    pub fn new(option: &str, provider: Box<dyn AutocompleteProviderFn + Send + Sync>) -> Self {
+        Self {
+            provider,
+            option: option.to_string(),
+        }
+    }
+
+    /// Autocomplete with a synchronous provider (eg.: filtering a static list)
+    pub fn from_fn(option: &str, provider: AutocompleteFn) -> Self {
+        Self::new(option, Box::new(provider))
+    }
+}
+
+/// Error when resolving the command tree of an interaction
+#[derive(Debug, Clone)]
+pub enum CommandResolveError {
+    /// The interaction references a subcommand (full name) that is not registered
+    UnknownSubcommand(String),
+}
+
+impl std::fmt::Display for CommandResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandResolveError::UnknownSubcommand(name) => {
+                write!(f, "Unknown subcommand {}", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommandResolveError {}
+
+/// Runner of a command with subcommands, it's never resolved by the dispatcher
+#[derive(Clone)]
+struct SubCommandsRunner;
+
+#[async_trait]
+impl CommandRunnerFn for SubCommandsRunner {
+    async fn run<'a>(&self, _arguments: &[Box<dyn Any + Send + Sync>]) -> CommandResult<'a> {
+        Err(BostilError::validation(
+            "Command with subcommands can't be run without a subcommand",
+        ))
+    }
 }
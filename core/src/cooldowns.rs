@@ -0,0 +1,357 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::arguments::InvocationContext;
+
+/// Shared store of the cooldowns of the commands and listeners
+pub static COOLDOWNS: Lazy<CooldownStore> = Lazy::new(CooldownStore::new);
+
+/// Target of a cooldown, each target has its own cooldown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CooldownBucket {
+    /// Every user has its own cooldown
+    User,
+    /// Every channel has its own cooldown
+    Channel,
+    /// Every guild has its own cooldown (the channel is used outside of a guild)
+    Guild,
+    /// A single cooldown shared by everyone
+    Global,
+}
+
+/// Cooldown (rate limit) of a command or listener
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cooldown {
+    pub bucket: CooldownBucket,
+    pub duration: Duration,
+}
+
+impl Cooldown {
+    pub fn new(bucket: CooldownBucket, duration: Duration) -> Self {
+        Self { bucket, duration }
+    }
+
+    pub fn per_user(seconds: u64) -> Self {
+        Self::new(CooldownBucket::User, Duration::from_secs(seconds))
+    }
+
+    pub fn per_channel(seconds: u64) -> Self {
+        Self::new(CooldownBucket::Channel, Duration::from_secs(seconds))
+    }
+
+    pub fn per_guild(seconds: u64) -> Self {
+        Self::new(CooldownBucket::Guild, Duration::from_secs(seconds))
+    }
+
+    /// Identifier of the target of the invocation on the bucket of the cooldown
+    pub fn target(&self, invocation: &InvocationContext) -> u64 {
+        match self.bucket {
+            CooldownBucket::User => invocation.user.id.get(),
+            CooldownBucket::Channel => invocation.channel_id.get(),
+            CooldownBucket::Guild => match invocation.guild_id {
+                Some(guild_id) => guild_id.get(),
+                None => invocation.channel_id.get(),
+            },
+            CooldownBucket::Global => 0,
+        }
+    }
+}
+
+type CooldownKey = (String, CooldownBucket, u64);
+
+/// Thread-safe store of when the cooldowns expire, keyed by name, bucket and target
+pub struct CooldownStore {
+    expirations: Mutex<HashMap<CooldownKey, Instant>>,
+}
+
+impl CooldownStore {
+    /// Number of entries after which the expired ones are removed
+    const PRUNE_THRESHOLD: usize = 1024;
+
+    pub fn new() -> Self {
+        Self {
+            expirations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn expirations(&self, now: Instant) -> MutexGuard<'_, HashMap<CooldownKey, Instant>> {
+        let mut expirations = match self.expirations.lock() {
+            Ok(expirations) => expirations,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if expirations.len() > Self::PRUNE_THRESHOLD {
+            expirations.retain(|_, expiration| *expiration > now);
+        }
+
+        expirations
+    }
+
+    /// Start the cooldown of the target if it's not active, otherwise fail with the remaining time
+    pub fn acquire(
+        &self,
+        name: &str,
+        cooldown: &Cooldown,
+        target: u64,
+    ) -> Result<(), CooldownError> {
+        let now = Instant::now();
+        let mut expirations = self.expirations(now);
+
+        let key = (name.to_string(), cooldown.bucket, target);
+
+        if let Some(expiration) = expirations.get(&key) {
+            if *expiration > now {
+                return Err(CooldownError {
+                    remaining: *expiration - now,
+                });
+            }
+        }
+
+        expirations.insert(key, now + cooldown.duration);
+
+        Ok(())
+    }
+
+    /// Check all the cooldowns of an invocation, they're only started when none of them is active
+    pub fn check(
+        &self,
+        name: &str,
+        cooldowns: &Vec<Cooldown>,
+        invocation: &InvocationContext,
+    ) -> Result<(), CooldownError> {
+        let now = Instant::now();
+        let mut expirations = self.expirations(now);
+
+        let remaining = cooldowns
+            .iter()
+            .filter_map(|cooldown| {
+                let key = (
+                    name.to_string(),
+                    cooldown.bucket,
+                    cooldown.target(invocation),
+                );
+
+                expirations
+                    .get(&key)
+                    .filter(|expiration| **expiration > now)
+                    .map(|expiration| *expiration - now)
+            })
+            .max();
+
+        if let Some(remaining) = remaining {
+            return Err(CooldownError { remaining });
+        }
+
+        for cooldown in cooldowns {
+            let key = (
+                name.to_string(),
+                cooldown.bucket,
+                cooldown.target(invocation),
+            );
+
+            expirations.insert(key, now + cooldown.duration);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for CooldownStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error when a cooldown is still active
+#[derive(Debug, Clone, Copy)]
+pub struct CooldownError {
+    /// Time until the cooldown expires
+    pub remaining: Duration,
+}
+
+impl CooldownError {
+    /// Remaining seconds, rounded up (eg.: to show "try again in N seconds")
+    pub fn remaining_seconds(&self) -> u64 {
+        let seconds = self.remaining.as_secs();
+
+        match self.remaining.subsec_nanos() {
+            0 => seconds,
+            _ => seconds + 1,
+        }
+    }
+}
+
+impl std::fmt::Display for CooldownError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cooldown active for {} seconds",
+            self.remaining_seconds()
+        )
+    }
+}
+
+impl std::error::Error for CooldownError {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Cooldown, CooldownBucket, CooldownError, CooldownStore};
+
+    #[test]
+    fn active_cooldown_denies_the_target() {
+        let store = CooldownStore::new();
+        let cooldown = Cooldown::per_user(10);
+
+        assert!(store.acquire("ping", &cooldown, 1).is_ok());
+
+        let error = store.acquire("ping", &cooldown, 1).unwrap_err();
+
+        assert!(error.remaining <= Duration::from_secs(10));
+        assert_eq!(error.remaining_seconds(), 10);
+    }
+
+    #[test]
+    fn expired_cooldown_is_started_again() {
+        let store = CooldownStore::new();
+        let cooldown = Cooldown::new(CooldownBucket::User, Duration::ZERO);
+
+        assert!(store.acquire("ping", &cooldown, 1).is_ok());
+        assert!(store.acquire("ping", &cooldown, 1).is_ok());
+    }
+
+    #[test]
+    fn targets_have_their_own_cooldown() {
+        let store = CooldownStore::new();
+        let cooldown = Cooldown::per_user(10);
+
+        assert!(store.acquire("ping", &cooldown, 1).is_ok());
+        assert!(store.acquire("ping", &cooldown, 2).is_ok());
+        assert!(store.acquire("ping", &cooldown, 1).is_err());
+    }
+
+    #[test]
+    fn buckets_and_names_have_their_own_cooldown() {
+        let store = CooldownStore::new();
+
+        assert!(store.acquire("ping", &Cooldown::per_user(10), 1).is_ok());
+        assert!(store.acquire("ping", &Cooldown::per_channel(10), 1).is_ok());
+        assert!(store.acquire("ping", &Cooldown::per_guild(10), 1).is_ok());
+        assert!(store.acquire("jingle", &Cooldown::per_user(10), 1).is_ok());
+
+        assert!(store
+            .acquire("ping", &Cooldown::per_channel(10), 1)
+            .is_err());
+    }
+
+    #[test]
+    fn expired_entries_are_pruned() {
+        let store = CooldownStore::new();
+        let expired = Cooldown::new(CooldownBucket::Global, Duration::ZERO);
+
+        for target in 0..=CooldownStore::PRUNE_THRESHOLD as u64 {
+            store.acquire("ping", &expired, target).unwrap();
+        }
+
+        // the next access removes the expired entries before adding its own
+        store.acquire("ping", &Cooldown::per_user(10), 1).unwrap();
+
+        assert_eq!(store.expirations.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn remaining_seconds_are_rounded_up() {
+        let error = CooldownError {
+            remaining: Duration::from_millis(1500),
+        };
+
+        assert_eq!(error.remaining_seconds(), 2);
+        assert_eq!(error.to_string(), "Cooldown active for 2 seconds");
+        assert_eq!(
+            CooldownError {
+                remaining: Duration::from_secs(3)
+            }
+            .remaining_seconds(),
+            3
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    mod check {
+        use std::time::Duration;
+
+        use crate::{
+            cooldowns::{Cooldown, CooldownStore},
+            testing::{fakes, TestHarness},
+        };
+
+        #[tokio::test]
+        async fn reports_the_longest_remaining_cooldown() {
+            let store = CooldownStore::new();
+            let harness = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+            let invocation = harness.invocation();
+            let cooldowns = vec![Cooldown::per_user(5), Cooldown::per_guild(30)];
+
+            assert!(store.check("ping", &cooldowns, &invocation).is_ok());
+
+            let error = store.check("ping", &cooldowns, &invocation).unwrap_err();
+
+            assert!(error.remaining > Duration::from_secs(5));
+            assert_eq!(error.remaining_seconds(), 30);
+        }
+
+        #[tokio::test]
+        async fn cooldowns_are_only_started_when_none_is_active() {
+            let store = CooldownStore::new();
+            let harness = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+            let invocation = harness.invocation();
+
+            // the user bucket is active, so the channel one isn't started by the denied check
+            store
+                .acquire("ping", &Cooldown::per_user(10), harness.user.id.get())
+                .unwrap();
+
+            assert!(store
+                .check(
+                    "ping",
+                    &vec![Cooldown::per_user(10), Cooldown::per_channel(10)],
+                    &invocation,
+                )
+                .is_err());
+            assert!(store
+                .check("ping", &vec![Cooldown::per_channel(10)], &invocation)
+                .is_ok());
+        }
+
+        #[tokio::test]
+        async fn guild_cooldown_is_shared_by_its_users() {
+            let store = CooldownStore::new();
+            let first = TestHarness::new().await.guild(fakes::guild(1, "Bostil"));
+            let second = TestHarness::new()
+                .await
+                .guild(fakes::guild(1, "Bostil"))
+                .user(fakes::user(2, "other"));
+
+            let per_guild = vec![Cooldown::per_guild(10)];
+            let per_user = vec![Cooldown::per_user(10)];
+
+            assert!(store.check("ping", &per_guild, &first.invocation()).is_ok());
+            assert!(store
+                .check("ping", &per_guild, &second.invocation())
+                .is_err());
+
+            assert!(store
+                .check("jingle", &per_user, &first.invocation())
+                .is_ok());
+            assert!(store
+                .check("jingle", &per_user, &second.invocation())
+                .is_ok());
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use std::{any::Any, panic::AssertUnwindSafe};
+
+use serenity::{
+    all::{ComponentInteraction, Guild, GuildId, Message, ModalInteraction, Reaction, VoiceState},
+    builder::{
+        CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+    futures::{future::join_all, FutureExt},
+};
+use tracing::{debug, error};
+
+use crate::{
+    arguments::{ArgumentsLevel, InvocationContext},
+    collectors::ListenerCollector,
+    cooldowns::COOLDOWNS,
+    errors::BostilError,
+    features::FEATURES,
+    listeners::{Listener, ListenerKind},
+    locales::LOCALES,
+};
+
+/// Dispatcher of the gateway events to the listeners (and integrations) of a collector
+#[derive(Clone)]
+pub struct ListenerDispatcher {
+    pub collector: ListenerCollector,
+}
+
+impl ListenerDispatcher {
+    pub fn new(collector: ListenerCollector) -> Self {
+        Self { collector }
+    }
+
+    /// Run every listener of the given kind concurrently, a panic on a listener doesn't affect the others
+    pub async fn dispatch(&self, kind: ListenerKind, invocation: &InvocationContext) {
+        let listeners = self.collector.filter_listeners(kind);
+
+        if listeners.is_empty() {
+            return;
+        }
+
+        debug!(
+            "Dispatching {:?} event to {} listeners",
+            kind,
+            listeners.len()
+        );
+
+        join_all(
+            listeners
+                .iter()
+                .map(|listener| Self::run_listener(listener, invocation)),
+        )
+        .await;
+    }
+
+    /// Run the listener when it's enabled and not on cooldown, its error is logged and returned
+    /// (to be shown to the user of an interaction), a panic is only logged
+    async fn run_listener(
+        listener: &Listener,
+        invocation: &InvocationContext,
+    ) -> Option<BostilError> {
+        let guild_id = invocation.guild_id;
+
+        if !FEATURES.is_enabled(guild_id, listener.feature, &listener.name) {
+            debug!("Listener {} skipped: disabled on the guild", listener.name);
+            return None;
+        }
+
+        if let Err(why) = COOLDOWNS.check(&listener.name, &listener.cooldowns, invocation) {
+            debug!("Listener {} skipped: {}", listener.name, why);
+            return None;
+        }
+
+        let arguments = ArgumentsLevel::provide(&listener.arguments, invocation);
+
+        match AssertUnwindSafe(listener.runner.run(&arguments))
+            .catch_unwind()
+            .await
+        {
+            Ok(Ok(())) => None,
+            Ok(Err(why)) => {
+                error!("Listener {} failed: {}", listener.name, why);
+                Some(why)
+            }
+            Err(why) => {
+                error!(
+                    "Listener {} panicked: {}",
+                    listener.name,
+                    panic_message(&why)
+                );
+                None
+            }
+        }
+    }
+
+    /// Dispatch a chat message to the `Message` listeners
+    pub async fn message(&self, ctx: &Context, message: &Message) {
+        let invocation = InvocationContext::new(
+            ctx,
+            cached_guild(ctx, message.guild_id),
+            &message.author,
+            &message.channel_id,
+        )
+        .guild_id(message.guild_id)
+        .locale(&LOCALES.resolve(message.guild_id, None))
+        .message(message.clone());
+
+        self.dispatch(ListenerKind::Message, &invocation).await;
+    }
+
+    /// Dispatch an added reaction to the `Reaction` listeners, the reacted message is provided as argument
+    pub async fn reaction_add(&self, ctx: &Context, reaction: &Reaction) {
+        let user = match reaction.user(ctx).await {
+            Ok(user) => user,
+            Err(why) => {
+                error!("Cannot get user of reaction: {}", why);
+                return;
+            }
+        };
+
+        let mut invocation = InvocationContext::new(
+            ctx,
+            cached_guild(ctx, reaction.guild_id),
+            &user,
+            &reaction.channel_id,
+        )
+        .guild_id(reaction.guild_id)
+        .locale(&LOCALES.resolve(reaction.guild_id, None));
+
+        match reaction.message(ctx).await {
+            Ok(message) => invocation = invocation.message(message),
+            Err(why) => error!("Cannot get message of reaction: {}", why),
+        }
+
+        self.dispatch(ListenerKind::Reaction, &invocation).await;
+    }
+
+    /// Dispatch a voice state update to the `VoiceState` listeners, the channel is the new one (or the left one)
+    ///
+    /// The previous and the new voice states are provided as a `VoiceStateUpdate` argument
+    pub async fn voice_state_update(
+        &self,
+        ctx: &Context,
+        old: &Option<VoiceState>,
+        new: &VoiceState,
+    ) {
+        let Some(channel_id) = new
+            .channel_id
+            .or(old.as_ref().and_then(|old| old.channel_id))
+        else {
+            return;
+        };
+
+        let user = match &new.member {
+            Some(member) => member.user.clone(),
+            None => match new.user_id.to_user(ctx).await {
+                Ok(user) => user,
+                Err(why) => {
+                    error!("Cannot get user of voice state: {}", why);
+                    return;
+                }
+            },
+        };
+
+        let invocation =
+            InvocationContext::new(ctx, cached_guild(ctx, new.guild_id), &user, &channel_id)
+                .guild_id(new.guild_id)
+                .locale(&LOCALES.resolve(new.guild_id, None))
+                .voice_state(old.clone(), new.clone());
+
+        self.dispatch(ListenerKind::VoiceState, &invocation).await;
+    }
+
+    /// Route a modal submission to the `Modal` listeners named by its custom_id (`<name>/<payload>`)
+    ///
+    /// The submission is acknowledged before running the listeners, when no listener matches it's left unanswered.
+    /// A listener error is shown to the user as an ephemeral follow-up
+    pub async fn modal(
+        &self,
+        ctx: &Context,
+        submit: &ModalInteraction,
+    ) -> Result<(), ListenerRouteError> {
+        let (_, payload) = split_custom_id(&submit.data.custom_id);
+        let listeners = self.routed_listeners(ListenerKind::Modal, &submit.data.custom_id)?;
+
+        if let Err(why) = submit.defer(&ctx.http).await {
+            error!("Cannot defer modal submit: {}", why);
+        }
+
+        let mut invocation = InvocationContext::new(
+            ctx,
+            cached_guild(ctx, submit.guild_id),
+            &submit.user,
+            &submit.channel_id,
+        )
+        .guild_id(submit.guild_id)
+        .locale(&LOCALES.resolve(submit.guild_id, Some(&submit.locale)))
+        .interaction_id(submit.id)
+        .modal_submit_data(submit.data.clone());
+
+        if let Some(payload) = payload {
+            invocation = invocation.payload(payload);
+        }
+
+        if let Some(why) = Self::run_routed(&listeners, &invocation).await {
+            let followup = CreateInteractionResponseFollowup::new()
+                .content(why.localized(&invocation.locale, "interactions.error"))
+                .ephemeral(true);
+
+            if let Err(why) = submit.create_followup(&ctx.http, followup).await {
+                error!("Cannot respond to the failed modal submit: {}", why);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Route a component interaction (button, select menu) to the `Component` listeners named by its custom_id (`<name>/<payload>`)
+    ///
+    /// The interaction isn't acknowledged, the listeners must respond to it (eg.: with a modal).
+    /// A listener error is shown to the user as an ephemeral response instead
+    pub async fn component(
+        &self,
+        ctx: &Context,
+        component: &ComponentInteraction,
+    ) -> Result<(), ListenerRouteError> {
+        let (_, payload) = split_custom_id(&component.data.custom_id);
+        let listeners =
+            self.routed_listeners(ListenerKind::Component, &component.data.custom_id)?;
+
+        let mut invocation = InvocationContext::new(
+            ctx,
+            cached_guild(ctx, component.guild_id),
+            &component.user,
+            &component.channel_id,
+        )
+        .guild_id(component.guild_id)
+        .locale(&LOCALES.resolve(component.guild_id, Some(&component.locale)))
+        .interaction_id(component.id)
+        .message(*component.message.clone())
+        .component(component.clone());
+
+        if let Some(payload) = payload {
+            invocation = invocation.payload(payload);
+        }
+
+        if let Some(why) = Self::run_routed(&listeners, &invocation).await {
+            let response = CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(why.localized(&invocation.locale, "interactions.error"))
+                    .ephemeral(true),
+            );
+
+            if let Err(why) = component.create_response(&ctx.http, response).await {
+                error!(
+                    "Cannot respond to the failed component interaction: {}",
+                    why
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run the listeners routed by the custom_id, the first error is the one shown to the user
+    async fn run_routed(
+        listeners: &[Listener],
+        invocation: &InvocationContext,
+    ) -> Option<BostilError> {
+        join_all(
+            listeners
+                .iter()
+                .map(|listener| Self::run_listener(listener, invocation)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .next()
+    }
+
+    /// Listeners of the given kind named by the custom_id
+    fn routed_listeners(
+        &self,
+        kind: ListenerKind,
+        custom_id: &str,
+    ) -> Result<Vec<Listener>, ListenerRouteError> {
+        let (name, _) = split_custom_id(custom_id);
+        let listeners = self
+            .collector
+            .filter_listeners(kind)
+            .into_iter()
+            .filter(|listener| listener.name == name)
+            .collect::<Vec<Listener>>();
+
+        match listeners.is_empty() {
+            true => Err(ListenerRouteError::UnknownCustomId(custom_id.to_string())),
+            false => Ok(listeners),
+        }
+    }
+}
+
+/// Error when routing an interaction to its listener
+#[derive(Debug, Clone)]
+pub enum ListenerRouteError {
+    /// No listener is named by the custom_id of the interaction
+    UnknownCustomId(String),
+}
+
+impl std::fmt::Display for ListenerRouteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenerRouteError::UnknownCustomId(custom_id) => {
+                write!(f, "No listener found for custom_id {}", custom_id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ListenerRouteError {}
+
+/// Split a custom_id in the `<name>/<payload>` format, the payload is optional
+pub fn split_custom_id(custom_id: &str) -> (&str, Option<&str>) {
+    match custom_id.split_once('/') {
+        Some((name, payload)) => (name, Some(payload)),
+        None => (custom_id, None),
+    }
+}
+
+fn cached_guild(ctx: &Context, guild_id: Option<GuildId>) -> Option<Guild> {
+    guild_id.and_then(|guild_id| ctx.cache.guild(guild_id).map(|guild| guild.clone()))
+}
+
+pub(crate) fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -1,29 +1,64 @@
+use once_cell::sync::Lazy;
+use serde_json::Value;
 use serenity::{
-    builder::{CreateEmbed, CreateMessage, EditMessage},
+    all::{ChannelId, MessageId},
+    builder::{CreateActionRow, CreateEmbed, CreateMessage, EditMessage},
     client::Context,
+    http::{HttpError, StatusCode},
     model::channel::GuildChannel,
     model::channel::Message,
 };
-use std::any::Any;
-use tracing::{error, info};
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, OnceLock,
+    },
+    time::Duration,
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    errors::{BostilError, BostilResult},
+    locales::LOCALES,
+};
+
+/// Shared registry of the embeds, keeps the sent ones attached to their messages
+pub static EMBEDS: Lazy<EmbedRegistry> = Lazy::new(EmbedRegistry::new);
 
 pub trait EmbedLifetime {
-    /// Function to create the embed (BUILDER)
-    fn build(&self, arguments: &Vec<Box<dyn Any + Send + Sync>>) -> CreateEmbed;
+    /// Function to create the embed on the locale of the guild it's sent to (BUILDER)
+    fn build(&self, locale: &str, arguments: &[Box<dyn Any + Send + Sync>]) -> CreateEmbed;
     /// Function to run when the embed is being updated
-    fn on_update(&self, arguments: &Vec<Box<dyn Any + Send + Sync>>) -> CreateEmbed {
-        self.build(arguments)
+    fn on_update(&self, locale: &str, arguments: &[Box<dyn Any + Send + Sync>]) -> CreateEmbed {
+        self.build(locale, arguments)
     }
-    /// Function to run when the embed is being sent (after build)
-    fn after_sent(&self, _arguments: &Vec<Box<dyn Any + Send + Sync>>) {}
-    /// Function to check if the embed should be updated
-    fn should_update(&self, _arguments: &Vec<Box<dyn Any + Send + Sync>>) -> bool {
+    /// Function to run when the embed is sent (after build), with the id of the sent message
+    fn after_sent(&self, _message_id: MessageId, _arguments: &[Box<dyn Any + Send + Sync>]) {}
+    /// Function to check if the embed should be updated (evaluated by the scheduler)
+    fn should_update(&self, _arguments: &[Box<dyn Any + Send + Sync>]) -> bool {
         false
     }
-    /// Function to check if the embed should be removed
-    fn should_delete(&self, _arguments: &Vec<Box<dyn Any + Send + Sync>>) -> bool {
+    /// Function to check if the embed should be removed (evaluated by the scheduler)
+    fn should_delete(&self, _arguments: &[Box<dyn Any + Send + Sync>]) -> bool {
         false
     }
+    /// Function to stop tracking the embed, the message is kept (evaluated by the scheduler)
+    fn should_detach(&self, _arguments: &[Box<dyn Any + Send + Sync>]) -> bool {
+        false
+    }
+    /// Function to create the components of the message, `None` keeps the current ones
+    fn components(
+        &self,
+        _arguments: &[Box<dyn Any + Send + Sync>],
+    ) -> Option<Vec<CreateActionRow>> {
+        None
+    }
+    /// Function to recover the arguments from the stored ones (required by recoverable embeds)
+    fn recover(&self, _stored: &Value) -> Option<Vec<Box<dyn Any + Send + Sync>>> {
+        None
+    }
 }
 
 pub struct ApplicationEmbed {
@@ -45,6 +80,7 @@ pub struct ApplicationEmbed {
 }
 
 impl ApplicationEmbed {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: &str,
         description: Option<&str>,
@@ -60,42 +96,53 @@ impl ApplicationEmbed {
             arguments,
             database_id,
             message_id,
-            is_recoverable: match is_recoverable {
-                Some(val) => val,
-                None => false,
-            },
+            is_recoverable: is_recoverable.unwrap_or_default(),
             name: name.to_string(),
-            description: match description {
-                Some(desc) => Some(desc.to_string()),
-                None => None,
-            },
-            message: match message {
-                Some(msg) => Some(msg.to_string()),
-                None => None,
-            },
+            description: description.map(String::from),
+            message: message.map(String::from),
         }
     }
 
-    pub async fn send_message(&self, ctx: &Context, channel: &GuildChannel) -> Result<Message, ()> {
-        match channel
-            .send_message(
-                &ctx.http,
-                CreateMessage::default()
-                    .content(self.message.clone().unwrap())
-                    .embed(self.lifetime.build(&self.arguments)),
-            )
+    pub async fn send_message(
+        &self,
+        ctx: &Context,
+        channel: &GuildChannel,
+    ) -> BostilResult<Message> {
+        let locale = LOCALES.resolve(Some(channel.guild_id), None);
+
+        self.send_message_with(ctx, channel.id, &locale, &self.arguments)
             .await
-        {
+    }
+
+    /// Send the embed built on the locale with the given arguments instead of the default ones
+    pub async fn send_message_with(
+        &self,
+        ctx: &Context,
+        channel_id: ChannelId,
+        locale: &str,
+        arguments: &[Box<dyn Any + Send + Sync>],
+    ) -> BostilResult<Message> {
+        let mut message = CreateMessage::default()
+            .content(self.message.clone().unwrap_or_default())
+            .embed(self.lifetime.build(locale, arguments));
+
+        if let Some(components) = self.lifetime.components(arguments) {
+            message = message.components(components);
+        }
+
+        match channel_id.send_message(&ctx.http, message).await {
             Ok(sent_message) => {
                 info!("Embed {} sent", self.name);
 
+                self.lifetime.after_sent(sent_message.id, arguments);
+
                 Ok(sent_message)
             }
 
-            Err(_) => {
+            Err(why) => {
                 error!("Embed {} not sent", self.name);
 
-                Err(())
+                Err(why.into())
             }
         }
     }
@@ -104,11 +151,13 @@ impl ApplicationEmbed {
         &self,
         ctx: &Context,
         mut sent_message: Message,
-    ) -> Result<Message, ()> {
+    ) -> BostilResult<Message> {
+        let locale = LOCALES.resolve(sent_message.guild_id, None);
+
         match sent_message
             .edit(
                 &ctx.http,
-                EditMessage::default().embed(self.lifetime.on_update(&self.arguments)),
+                EditMessage::default().embed(self.lifetime.on_update(&locale, &self.arguments)),
             )
             .await
         {
@@ -118,17 +167,44 @@ impl ApplicationEmbed {
                 Ok(sent_message.clone())
             }
 
-            Err(_) => {
+            Err(why) => {
                 info!("Embed {} not updated", self.name);
 
-                Err(())
+                Err(why.into())
             }
         }
     }
 
-    pub async fn delete_message(&self, ctx: &Context, sent_message: Message) -> Result<(), ()> {
-        sent_message.delete(&ctx.http).await.map_err(|_| {
+    /// Update the sent embed on the locale with the given arguments instead of the default ones
+    pub async fn update_message_with(
+        &self,
+        ctx: &Context,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        locale: &str,
+        arguments: &[Box<dyn Any + Send + Sync>],
+    ) -> BostilResult<Message> {
+        let mut message = EditMessage::default().embed(self.lifetime.on_update(locale, arguments));
+
+        if let Some(components) = self.lifetime.components(arguments) {
+            message = message.components(components);
+        }
+
+        channel_id
+            .edit_message(&ctx.http, message_id, message)
+            .await
+            .map_err(|why| {
+                error!("Embed {} not updated", self.name);
+
+                why.into()
+            })
+    }
+
+    pub async fn delete_message(&self, ctx: &Context, sent_message: Message) -> BostilResult<()> {
+        sent_message.delete(&ctx.http).await.map_err(|why| {
             error!("Embed failed to delete");
+
+            why.into()
         })
     }
 }
@@ -139,7 +215,506 @@ impl std::fmt::Display for ApplicationEmbed {
             f,
             "Embed: {} \n {}",
             self.name,
-            self.description.clone().unwrap()
+            self.description.clone().unwrap_or_default()
         )
     }
 }
+
+/// Embed registered by `register_embed!`, collected by `EmbedRegistry::store_registered`
+pub struct EmbedRegistration(pub fn() -> &'static ApplicationEmbed);
+
+inventory::collect!(EmbedRegistration);
+
+/// Register an embed static (`Lazy` or `lazy_static`) to be re-attached by its name
+#[macro_export]
+macro_rules! register_embed {
+    ($embed:path) => {
+        $crate::__private::inventory::submit! {
+            $crate::embeds::EmbedRegistration(|| &*$embed)
+        }
+    };
+}
+
+/// Recoverable embed sent to a channel, as persisted on the store
+#[derive(Debug, Clone)]
+pub struct EmbedRecord {
+    /// The identifier of the record on the store, `None` before it's saved
+    pub id: Option<i64>,
+    /// The name of the `ApplicationEmbed`
+    pub name: String,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    /// The locale the embed is built on, kept so the updates use the same one
+    pub locale: String,
+    /// The arguments of the embed, as given to `EmbedLifetime::recover`
+    pub arguments: Value,
+}
+
+/// Persistence of the recoverable embeds (eg.: a database table)
+pub trait EmbedStore: Send + Sync {
+    /// Insert or update (when it has an id) the record, returning its id
+    fn save(&self, record: &EmbedRecord) -> BostilResult<i64>;
+    /// All the stored records
+    fn load(&self) -> BostilResult<Vec<EmbedRecord>>;
+    fn delete(&self, id: i64) -> BostilResult<()>;
+}
+
+/// Embed attached to its sent message, with its current arguments
+///
+/// The arguments are shared, so the lifetime and the requests use them without holding the lock of the registry
+#[derive(Clone)]
+struct AttachedEmbed {
+    embed: &'static ApplicationEmbed,
+    record: EmbedRecord,
+    arguments: Arc<Vec<Box<dyn Any + Send + Sync>>>,
+}
+
+/// Action on an attached embed decided by its lifetime
+enum EmbedAction {
+    Update(MessageId),
+    Delete(MessageId),
+    Detach(MessageId),
+}
+
+/// Registry of the embeds by name, tracks the sent ones and re-attaches the recoverable ones after a restart
+///
+/// The scheduler of the registry (see `EmbedRegistry::start_scheduler`) updates and deletes the attached embeds
+/// following `EmbedLifetime::should_update` and `EmbedLifetime::should_delete`
+pub struct EmbedRegistry {
+    embeds: Mutex<HashMap<String, &'static ApplicationEmbed>>,
+    attached: Mutex<HashMap<MessageId, AttachedEmbed>>,
+    store: OnceLock<Arc<dyn EmbedStore>>,
+    scheduler_started: AtomicBool,
+}
+
+impl EmbedRegistry {
+    pub fn new() -> Self {
+        Self {
+            embeds: Mutex::new(HashMap::new()),
+            attached: Mutex::new(HashMap::new()),
+            store: OnceLock::new(),
+            scheduler_started: AtomicBool::new(false),
+        }
+    }
+
+    fn embeds(&self) -> MutexGuard<'_, HashMap<String, &'static ApplicationEmbed>> {
+        match self.embeds.lock() {
+            Ok(embeds) => embeds,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn attached(&self) -> MutexGuard<'_, HashMap<MessageId, AttachedEmbed>> {
+        match self.attached.lock() {
+            Ok(attached) => attached,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Register an embed, the recoverable ones are re-attached by its name
+    pub fn register(&self, embed: &'static ApplicationEmbed) {
+        self.embeds().insert(embed.name.clone(), embed);
+    }
+
+    /// Register all the embeds registered through `register_embed!`
+    pub fn store_registered(&self) {
+        for registration in inventory::iter::<EmbedRegistration> {
+            self.register((registration.0)());
+        }
+    }
+
+    /// Set the store of the recoverable embeds (only the first store is kept)
+    pub fn set_store(&self, store: impl EmbedStore + 'static) {
+        if self.store.set(Arc::new(store)).is_err() {
+            warn!("Embed store already set");
+        }
+    }
+
+    fn find(&self, name: &str) -> BostilResult<&'static ApplicationEmbed> {
+        self.embeds()
+            .get(name)
+            .copied()
+            .ok_or_else(|| BostilError::validation(format!("Embed {} not registered", name)))
+    }
+
+    fn recover(
+        embed: &ApplicationEmbed,
+        stored: &Value,
+    ) -> BostilResult<Vec<Box<dyn Any + Send + Sync>>> {
+        embed.lifetime.recover(stored).ok_or_else(|| {
+            BostilError::validation(format!("Cannot recover arguments of embed {}", embed.name))
+        })
+    }
+
+    /// Send a registered embed built on the locale with the recovered arguments, recoverable embeds are persisted
+    pub async fn send(
+        &self,
+        ctx: &Context,
+        name: &str,
+        channel_id: ChannelId,
+        locale: &str,
+        arguments: Value,
+    ) -> BostilResult<Message> {
+        let embed = self.find(name)?;
+        let recovered = Self::recover(embed, &arguments)?;
+
+        let message = embed
+            .send_message_with(ctx, channel_id, locale, &recovered)
+            .await?;
+
+        let mut record = EmbedRecord {
+            id: None,
+            name: embed.name.clone(),
+            channel_id,
+            message_id: message.id,
+            locale: locale.to_string(),
+            arguments,
+        };
+
+        if embed.is_recoverable {
+            match self.store.get() {
+                Some(store) => record.id = Some(store.save(&record)?),
+                None => warn!("Embed {} sent without a store, it won't be recovered", name),
+            }
+        }
+
+        self.attach(embed, record, recovered);
+
+        Ok(message)
+    }
+
+    /// Send an embed built on the locale with the given arguments, tracked by the scheduler (it isn't persisted)
+    pub async fn send_with(
+        &self,
+        ctx: &Context,
+        embed: &'static ApplicationEmbed,
+        channel_id: ChannelId,
+        locale: &str,
+        arguments: Vec<Box<dyn Any + Send + Sync>>,
+    ) -> BostilResult<Message> {
+        let message = embed
+            .send_message_with(ctx, channel_id, locale, &arguments)
+            .await?;
+
+        self.track(embed, &message, locale, arguments);
+
+        Ok(message)
+    }
+
+    /// Track an embed sent outside of the registry (eg.: as an interaction response), it isn't persisted
+    pub fn track(
+        &self,
+        embed: &'static ApplicationEmbed,
+        message: &Message,
+        locale: &str,
+        arguments: Vec<Box<dyn Any + Send + Sync>>,
+    ) {
+        let record = EmbedRecord {
+            id: None,
+            name: embed.name.clone(),
+            channel_id: message.channel_id,
+            message_id: message.id,
+            locale: locale.to_string(),
+            arguments: Value::Null,
+        };
+
+        self.attach(embed, record, arguments);
+    }
+
+    fn attach(
+        &self,
+        embed: &'static ApplicationEmbed,
+        record: EmbedRecord,
+        arguments: Vec<Box<dyn Any + Send + Sync>>,
+    ) {
+        self.attached().insert(
+            record.message_id,
+            AttachedEmbed {
+                embed,
+                record,
+                arguments: Arc::new(arguments),
+            },
+        );
+    }
+
+    /// Update an attached embed, with new arguments when given (they're persisted)
+    pub async fn update(
+        &self,
+        ctx: &Context,
+        message_id: MessageId,
+        arguments: Option<Value>,
+    ) -> BostilResult<Message> {
+        // the embed is cloned out, so the lock isn't held during the requests
+        let Some(mut attached) = self.attached().get(&message_id).cloned() else {
+            return Err(Self::not_attached(message_id));
+        };
+
+        if let Some(arguments) = arguments {
+            attached.arguments = Arc::new(Self::recover(attached.embed, &arguments)?);
+            attached.record.arguments = arguments;
+
+            if let (Some(_), Some(store)) = (attached.record.id, self.store.get()) {
+                store.save(&attached.record)?;
+            }
+        }
+
+        let updated = attached
+            .embed
+            .update_message_with(
+                ctx,
+                attached.record.channel_id,
+                message_id,
+                &attached.record.locale,
+                &attached.arguments,
+            )
+            .await;
+
+        // the embed stays attached even when the update failed, unless it was forgotten meanwhile
+        if let Some(current) = self.attached().get_mut(&message_id) {
+            *current = attached;
+        }
+
+        updated
+    }
+
+    /// Delete the message of an attached embed and forget it
+    pub async fn delete(&self, ctx: &Context, message_id: MessageId) -> BostilResult<()> {
+        let record = self.attached_record(message_id)?;
+
+        record
+            .channel_id
+            .delete_message(&ctx.http, message_id)
+            .await?;
+
+        self.forget(&record)
+    }
+
+    /// Re-attach the stored embeds whose message still exists, the others are removed from the store
+    ///
+    /// Returns the number of re-attached embeds
+    pub async fn restore(&self, ctx: &Context) -> BostilResult<usize> {
+        let Some(store) = self.store.get() else {
+            return Ok(0);
+        };
+
+        let mut restored = 0;
+
+        for record in store.load()? {
+            let recovered = self.find(&record.name).and_then(|embed| {
+                Self::recover(embed, &record.arguments).map(|arguments| (embed, arguments))
+            });
+
+            let (embed, arguments) = match recovered {
+                Ok(recovered) => recovered,
+                Err(why) => {
+                    warn!("Cannot restore embed {}: {}", record.name, why);
+                    self.forget(&record)?;
+                    continue;
+                }
+            };
+
+            match ctx
+                .http
+                .get_message(record.channel_id, record.message_id)
+                .await
+            {
+                Ok(_) => {
+                    debug!("Embed {} restored on {}", record.name, record.message_id);
+
+                    self.attach(embed, record, arguments);
+                    restored += 1;
+                }
+                Err(serenity::Error::Http(HttpError::UnsuccessfulRequest(response)))
+                    if response.status_code == StatusCode::NOT_FOUND =>
+                {
+                    debug!("Message of embed {} was deleted", record.name);
+
+                    self.forget(&record)?;
+                }
+                Err(why) => error!("Cannot restore embed {}: {}", record.name, why),
+            }
+        }
+
+        Ok(restored)
+    }
+
+    /// Evaluate the lifetime of every attached embed, updating and deleting their messages
+    pub async fn tick(&self, ctx: &Context) {
+        let message_ids = self.attached().keys().copied().collect::<Vec<MessageId>>();
+
+        self.evaluate(ctx, message_ids).await
+    }
+
+    /// Evaluate the lifetime of the embed attached to the message right away (eg.: after a vote)
+    pub async fn refresh(&self, ctx: &Context, message_id: MessageId) {
+        self.evaluate(ctx, vec![message_id]).await
+    }
+
+    async fn evaluate(&self, ctx: &Context, message_ids: Vec<MessageId>) {
+        // the embeds are cloned out, so the lifetime and the requests run without the lock held
+        let attached = {
+            let attached = self.attached();
+
+            message_ids
+                .into_iter()
+                .filter_map(|message_id| Some((message_id, attached.get(&message_id)?.clone())))
+                .collect::<Vec<(MessageId, AttachedEmbed)>>()
+        };
+
+        let actions = attached
+            .into_iter()
+            .filter_map(|(message_id, attached)| {
+                let lifetime = &attached.embed.lifetime;
+
+                if lifetime.should_delete(&attached.arguments) {
+                    Some(EmbedAction::Delete(message_id))
+                } else if lifetime.should_update(&attached.arguments) {
+                    Some(EmbedAction::Update(message_id))
+                } else if lifetime.should_detach(&attached.arguments) {
+                    Some(EmbedAction::Detach(message_id))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<EmbedAction>>();
+
+        for action in actions {
+            let (message_id, result) = match action {
+                EmbedAction::Update(message_id) => (
+                    message_id,
+                    self.update(ctx, message_id, None).await.map(|_| ()),
+                ),
+                EmbedAction::Delete(message_id) => (message_id, self.delete(ctx, message_id).await),
+                EmbedAction::Detach(message_id) => (message_id, self.detach(message_id)),
+            };
+
+            if let Err(why) = result {
+                error!("Cannot refresh embed of message {}: {}", message_id, why);
+            }
+        }
+    }
+
+    /// Start the background task evaluating the attached embeds on every period (only the first call starts it)
+    pub fn start_scheduler(&'static self, ctx: &Context, period: Duration) {
+        if self.scheduler_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let ctx = ctx.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            loop {
+                interval.tick().await;
+                self.tick(&ctx).await;
+            }
+        });
+
+        info!("Embed scheduler started, every {:?}", period);
+    }
+
+    /// Stop tracking the embed attached to the message, the message is kept
+    pub fn detach(&self, message_id: MessageId) -> BostilResult<()> {
+        let record = self.attached_record(message_id)?;
+
+        self.forget(&record)
+    }
+
+    /// Argument of the embed attached to the message, at the given position
+    pub fn argument<T: Clone + 'static>(&self, message_id: MessageId, index: usize) -> Option<T> {
+        self.attached()
+            .get(&message_id)?
+            .arguments
+            .get(index)?
+            .downcast_ref::<T>()
+            .cloned()
+    }
+
+    /// Whether the message has an attached embed
+    pub fn is_attached(&self, message_id: MessageId) -> bool {
+        self.attached().contains_key(&message_id)
+    }
+
+    fn attached_record(&self, message_id: MessageId) -> BostilResult<EmbedRecord> {
+        self.attached()
+            .get(&message_id)
+            .map(|attached| attached.record.clone())
+            .ok_or_else(|| Self::not_attached(message_id))
+    }
+
+    fn not_attached(message_id: MessageId) -> BostilError {
+        BostilError::validation(format!("No embed attached to message {}", message_id))
+    }
+
+    fn forget(&self, record: &EmbedRecord) -> BostilResult<()> {
+        self.attached().remove(&record.message_id);
+
+        match (record.id, self.store.get()) {
+            (Some(id), Some(store)) => store.delete(id),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for EmbedRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::any::Any;
+
+    use once_cell::sync::Lazy;
+    use serenity::{all::MessageId, builder::CreateEmbed};
+
+    use super::{ApplicationEmbed, EmbedLifetime, EMBEDS};
+    use crate::testing::{fakes, TestHarness};
+
+    const MESSAGE_ID: u64 = 4201;
+
+    /// Lifetime reading the registry from its callbacks, as the lifetimes checking other embeds do
+    struct ReentrantLifetime;
+
+    impl EmbedLifetime for ReentrantLifetime {
+        fn build(&self, _locale: &str, _arguments: &[Box<dyn Any + Send + Sync>]) -> CreateEmbed {
+            CreateEmbed::default().title("Reentrant")
+        }
+
+        fn should_update(&self, _arguments: &[Box<dyn Any + Send + Sync>]) -> bool {
+            EMBEDS.is_attached(MessageId::new(MESSAGE_ID))
+        }
+    }
+
+    static REENTRANT_EMBED: Lazy<ApplicationEmbed> = Lazy::new(|| {
+        ApplicationEmbed::new(
+            "Reentrant",
+            None,
+            None,
+            vec![],
+            Box::new(ReentrantLifetime),
+            None,
+            None,
+            None,
+        )
+    });
+
+    #[tokio::test]
+    async fn lifetime_runs_without_the_registry_locked() {
+        let harness = TestHarness::new().await;
+        let message = fakes::message(MESSAGE_ID, 3, &harness.user, "");
+
+        EMBEDS.track(&REENTRANT_EMBED, &message, "en-US", vec![]);
+        EMBEDS.refresh(&harness.context, message.id).await;
+
+        let requests = harness.requests();
+
+        // the embed stays attached after its update
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "PATCH");
+        assert_eq!(requests[0].body["embeds"][0]["title"], "Reentrant");
+        assert!(EMBEDS.is_attached(message.id));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    arguments::ArgumentError,
+    commands::CommandResolveError,
+    cooldowns::CooldownError,
+    locales::{DEFAULT_LOCALE, LOCALES},
+    preconditions::{Precondition, PreconditionError},
+};
+
+/// Result of the runners and embeds of the bot
+pub type BostilResult<T> = Result<T, BostilError>;
+
+/// Error of the runners and embeds of the bot
+///
+/// Only `UserFacing` errors are shown to the user (as a localized message), the others are internal and just logged
+#[derive(Debug)]
+pub enum BostilError {
+    /// Error on a request to Discord (HTTP, gateway, cache, ...)
+    Discord(Box<serenity::Error>),
+    /// Error on a database query or connection
+    Database(String),
+    /// Error on a voice connection or track
+    Voice(String),
+    /// Invalid data given to a runner (eg.: missing argument or option)
+    Validation(String),
+    /// Error shown to the user, the message is the locale key interpolated with the arguments
+    UserFacing {
+        key: String,
+        arguments: Vec<(String, String)>,
+    },
+}
+
+impl BostilError {
+    /// User-facing error with the message of the given locale key
+    pub fn user_facing(key: &str) -> Self {
+        BostilError::UserFacing {
+            key: key.to_string(),
+            arguments: vec![],
+        }
+    }
+
+    /// Add an interpolation argument to a user-facing error (eg.: `%{thread_id}`)
+    pub fn with(self, name: &str, value: impl ToString) -> Self {
+        match self {
+            BostilError::UserFacing { key, mut arguments } => {
+                arguments.push((name.to_string(), value.to_string()));
+
+                BostilError::UserFacing { key, arguments }
+            }
+            error => error,
+        }
+    }
+
+    pub fn database(why: impl std::fmt::Display) -> Self {
+        BostilError::Database(why.to_string())
+    }
+
+    pub fn voice(why: impl std::fmt::Display) -> Self {
+        BostilError::Voice(why.to_string())
+    }
+
+    pub fn validation(why: impl std::fmt::Display) -> Self {
+        BostilError::Validation(why.to_string())
+    }
+
+    pub fn is_user_facing(&self) -> bool {
+        matches!(self, BostilError::UserFacing { .. })
+    }
+
+    /// Message shown to the user on the locale, the internal errors show the message of the fallback key
+    pub fn localized(&self, locale: &str, fallback_key: &str) -> String {
+        let (key, arguments) = match self {
+            BostilError::UserFacing { key, arguments } => (key.as_str(), arguments.as_slice()),
+            _ => (fallback_key, [].as_slice()),
+        };
+
+        let message = LOCALES
+            .translate(locale, key)
+            .or_else(|| LOCALES.translate(DEFAULT_LOCALE, key))
+            .unwrap_or_else(|| key.to_string());
+
+        arguments.iter().fold(message, |message, (name, value)| {
+            message.replace(&format!("%{{{}}}", name), value)
+        })
+    }
+}
+
+impl std::fmt::Display for BostilError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BostilError::Discord(why) => write!(f, "Discord error: {}", why),
+            BostilError::Database(why) => write!(f, "Database error: {}", why),
+            BostilError::Voice(why) => write!(f, "Voice error: {}", why),
+            BostilError::Validation(why) => write!(f, "Validation error: {}", why),
+            BostilError::UserFacing { key, arguments } => {
+                write!(f, "User-facing error: {} {:?}", key, arguments)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BostilError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BostilError::Discord(why) => Some(why.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<serenity::Error> for BostilError {
+    fn from(why: serenity::Error) -> Self {
+        BostilError::Discord(Box::new(why))
+    }
+}
+
+impl From<songbird::error::JoinError> for BostilError {
+    fn from(why: songbird::error::JoinError) -> Self {
+        BostilError::Voice(why.to_string())
+    }
+}
+
+impl From<ArgumentError> for BostilError {
+    fn from(why: ArgumentError) -> Self {
+        BostilError::Validation(why.to_string())
+    }
+}
+
+impl From<CommandResolveError> for BostilError {
+    fn from(why: CommandResolveError) -> Self {
+        match why {
+            CommandResolveError::UnknownSubcommand(subcommand) => {
+                BostilError::user_facing("commands.unknown_subcommand")
+                    .with("subcommand", subcommand)
+            }
+        }
+    }
+}
+
+impl From<PreconditionError> for BostilError {
+    fn from(why: PreconditionError) -> Self {
+        let error = BostilError::user_facing(&format!("commands.preconditions.{}", why.key()));
+
+        match why.0 {
+            Precondition::RequiresPermissions(permissions) => {
+                error.with("permissions", permissions.get_permission_names().join(", "))
+            }
+            _ => error,
+        }
+    }
+}
+
+impl From<CooldownError> for BostilError {
+    fn from(why: CooldownError) -> Self {
+        BostilError::user_facing("commands.cooldown").with("seconds", why.remaining_seconds())
+    }
+}
+
+/// Conversion of the value returned by a listener function (`()` or a result) to a listener result
+pub trait IntoListenerResult {
+    fn into_listener_result(self) -> BostilResult<()>;
+}
+
+impl IntoListenerResult for () {
+    fn into_listener_result(self) -> BostilResult<()> {
+        Ok(())
+    }
+}
+
+impl<E: Into<BostilError>> IntoListenerResult for Result<(), E> {
+    fn into_listener_result(self) -> BostilResult<()> {
+        self.map_err(Into::into)
+    }
+}
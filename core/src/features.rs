@@ -0,0 +1,163 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use once_cell::sync::Lazy;
+use serenity::all::GuildId;
+use tracing::{info, warn};
+
+use crate::errors::BostilResult;
+
+/// Shared registry of the features disabled on each guild
+pub static FEATURES: Lazy<FeatureRegistry> = Lazy::new(FeatureRegistry::new);
+
+/// Kind of a feature that can be toggled on a guild
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeatureKind {
+    Command,
+    Listener,
+    Integration,
+}
+
+impl FeatureKind {
+    pub const ALL: [FeatureKind; 3] = [
+        FeatureKind::Command,
+        FeatureKind::Listener,
+        FeatureKind::Integration,
+    ];
+
+    /// Name of the kind, used to persist it
+    pub fn name(&self) -> &'static str {
+        match self {
+            FeatureKind::Command => "command",
+            FeatureKind::Listener => "listener",
+            FeatureKind::Integration => "integration",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// State of a feature on a guild, features without a toggle are enabled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureToggle {
+    pub guild_id: GuildId,
+    pub kind: FeatureKind,
+    /// Name of the command, listener or integration
+    pub name: String,
+    pub enabled: bool,
+}
+
+/// Persistence of the feature toggles
+pub trait FeatureStore: Send + Sync {
+    /// All the stored toggles
+    fn load(&self) -> BostilResult<Vec<FeatureToggle>>;
+    /// Insert or update the toggle of the feature on the guild
+    fn save(&self, toggle: &FeatureToggle) -> BostilResult<()>;
+}
+
+type FeatureKey = (GuildId, FeatureKind, String);
+
+/// Registry of the feature toggles, keeps the disabled features in memory
+/// to be consulted by the dispatchers without hitting the store
+pub struct FeatureRegistry {
+    disabled: RwLock<HashSet<FeatureKey>>,
+    store: OnceLock<Arc<dyn FeatureStore>>,
+}
+
+impl FeatureRegistry {
+    pub fn new() -> Self {
+        Self {
+            disabled: RwLock::new(HashSet::new()),
+            store: OnceLock::new(),
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashSet<FeatureKey>> {
+        match self.disabled.read() {
+            Ok(disabled) => disabled,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashSet<FeatureKey>> {
+        match self.disabled.write() {
+            Ok(disabled) => disabled,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Set the store of the toggles, only the first store is kept
+    pub fn set_store(&self, store: impl FeatureStore + 'static) {
+        if self.store.set(Arc::new(store)).is_err() {
+            warn!("Feature store already set");
+        }
+    }
+
+    /// Replace the cached toggles by the stored ones
+    pub fn load(&self) -> BostilResult<()> {
+        let Some(store) = self.store.get() else {
+            return Ok(());
+        };
+
+        let disabled = store
+            .load()?
+            .into_iter()
+            .filter(|toggle| !toggle.enabled)
+            .map(|toggle| (toggle.guild_id, toggle.kind, toggle.name))
+            .collect::<HashSet<FeatureKey>>();
+
+        info!("Loaded {} disabled features", disabled.len());
+
+        *self.write() = disabled;
+
+        Ok(())
+    }
+
+    /// Check if the feature is enabled, features are always enabled outside of guilds
+    pub fn is_enabled(&self, guild_id: Option<GuildId>, kind: FeatureKind, name: &str) -> bool {
+        match guild_id {
+            Some(guild_id) => !self.read().contains(&(guild_id, kind, name.to_string())),
+            None => true,
+        }
+    }
+
+    /// Enable or disable the feature on the guild, persisting it when a store is set
+    pub fn set(&self, toggle: FeatureToggle) -> BostilResult<()> {
+        if let Some(store) = self.store.get() {
+            store.save(&toggle)?;
+        }
+
+        let key = (toggle.guild_id, toggle.kind, toggle.name);
+
+        match toggle.enabled {
+            true => self.write().remove(&key),
+            false => self.write().insert(key),
+        };
+
+        Ok(())
+    }
+
+    /// Names of the features of the kind disabled on the guild, sorted
+    pub fn disabled(&self, guild_id: GuildId, kind: FeatureKind) -> Vec<String> {
+        let mut names = self
+            .read()
+            .iter()
+            .filter(|(guild, feature, _)| *guild == guild_id && *feature == kind)
+            .map(|(_, _, name)| name.clone())
+            .collect::<Vec<String>>();
+
+        names.sort();
+
+        names
+    }
+}
+
+impl Default for FeatureRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,113 @@
+use serde_json::Value;
+use serenity::all::CommandOptionType;
+
+use crate::commands::{Command, CommandCategory};
+
+/// Option of a command usage
+#[derive(Debug, Clone)]
+pub struct OptionHelp {
+    pub name: String,
+    pub description: String,
+    pub kind: CommandOptionType,
+    pub required: bool,
+}
+
+/// Usage of a command or of one of its subcommands (eg.: `/poll setup <name> [description]`)
+#[derive(Debug, Clone)]
+pub struct UsageHelp {
+    /// Names of the command and subcommands (eg.: `poll setup`)
+    pub path: String,
+    pub description: String,
+    pub options: Vec<OptionHelp>,
+}
+
+impl UsageHelp {
+    /// Syntax of the usage, required options between `<>` and optional ones between `[]`
+    pub fn syntax(&self) -> String {
+        self.options
+            .iter()
+            .fold(format!("/{}", self.path), |syntax, option| {
+                match option.required {
+                    true => format!("{} <{}>", syntax, option.name),
+                    false => format!("{} [{}]", syntax, option.name),
+                }
+            })
+    }
+}
+
+/// Help of a collected command, built from its fingerprint (as registered on Discord)
+///
+/// Names and descriptions are localized to the given locale when the fingerprint has localizations
+#[derive(Debug, Clone)]
+pub struct CommandHelp {
+    pub name: String,
+    pub description: String,
+    pub category: CommandCategory,
+    /// Usage of the command, or of each subcommand when it has subcommands
+    pub usages: Vec<UsageHelp>,
+}
+
+impl CommandHelp {
+    pub fn new(command: &Command, locale: &str) -> Self {
+        let fingerprint = command.localized_fingerprint();
+        let name = localized(&fingerprint, "name", locale);
+
+        Self {
+            usages: usages(&name, &fingerprint, locale),
+            description: localized(&fingerprint, "description", locale),
+            category: command.category,
+            name,
+        }
+    }
+}
+
+/// Field of the fingerprint in the locale, the default one when it isn't localized
+fn localized(value: &Value, field: &str, locale: &str) -> String {
+    value[format!("{}_localizations", field)][locale]
+        .as_str()
+        .or(value[field].as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn kind(option: &Value) -> CommandOptionType {
+    CommandOptionType::from(option["type"].as_u64().unwrap_or_default() as u8)
+}
+
+fn usages(path: &str, value: &Value, locale: &str) -> Vec<UsageHelp> {
+    let options = value["options"].as_array().cloned().unwrap_or_default();
+
+    let is_subcommand = |option: &Value| {
+        matches!(
+            kind(option),
+            CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup
+        )
+    };
+
+    if !options.iter().any(is_subcommand) {
+        return vec![UsageHelp {
+            path: path.to_string(),
+            description: localized(value, "description", locale),
+            options: options
+                .iter()
+                .map(|option| OptionHelp {
+                    name: localized(option, "name", locale),
+                    description: localized(option, "description", locale),
+                    kind: kind(option),
+                    required: option["required"].as_bool().unwrap_or_default(),
+                })
+                .collect(),
+        }];
+    }
+
+    // groups are expanded to their subcommands
+    options
+        .iter()
+        .filter(|option| is_subcommand(option))
+        .flat_map(|option| {
+            let path = format!("{} {}", path, localized(option, "name", locale));
+
+            usages(&path, option, locale)
+        })
+        .collect()
+}
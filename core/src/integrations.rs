@@ -2,6 +2,7 @@ use tracing::info;
 
 use crate::{
     arguments::ArgumentsLevel,
+    features::FeatureKind,
     listeners::{Listener, ListenerKind},
     runners::runners::ListenerRunnerFn,
 };
@@ -34,14 +35,14 @@ impl Integration {
     ) -> Self {
         Self {
             kind,
-            arguments: arguments.clone(),
+            arguments: ArgumentsLevel::merge(&arguments, &runner.arguments()),
             name: name.to_string(),
             description: description.to_string(),
             runner: {
                 info!("Running {} integration", name);
 
-                if callback.is_some() {
-                    callback.unwrap()((name.to_string(), description.to_string(), arguments, kind));
+                if let Some(callback) = callback {
+                    callback((name.to_string(), description.to_string(), arguments, kind));
                 }
 
                 runner.clone()
@@ -64,6 +65,8 @@ impl From<Integration> for Listener {
             kind: integration.kind,
             arguments: integration.arguments,
             runner: integration.runner,
+            cooldowns: vec![],
+            feature: FeatureKind::Integration,
         }
     }
 }
@@ -77,6 +80,8 @@ impl From<&Integration> for Listener {
             kind: integration.kind,
             arguments: integration.arguments.clone(),
             runner: integration.runner.clone(),
+            cooldowns: vec![],
+            feature: FeatureKind::Integration,
         }
     }
 }
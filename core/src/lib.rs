@@ -1,9 +1,31 @@
-extern crate proc_macro;
-
 pub mod arguments;
 pub mod collectors;
 pub mod commands;
+pub mod cooldowns;
+pub mod dispatchers;
 pub mod embeds;
+pub mod errors;
+pub mod features;
+pub mod help;
 pub mod integrations;
 pub mod listeners;
+pub mod locales;
+pub mod middlewares;
+pub mod pagination;
+pub mod preconditions;
+pub mod registration;
 pub mod runners;
+pub mod scheduler;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use bostil_macros::{command, listener};
+
+/// Items used by the code generated by the macros (not public API)
+#[doc(hidden)]
+pub mod __private {
+    pub use inventory;
+    pub use once_cell::sync::Lazy;
+    pub use serenity;
+    pub use serenity::async_trait;
+}
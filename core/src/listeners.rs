@@ -1,4 +1,7 @@
-use crate::{arguments::ArgumentsLevel, runners::runners::ListenerRunnerFn};
+use crate::{
+    arguments::ArgumentsLevel, cooldowns::Cooldown, features::FeatureKind,
+    runners::runners::ListenerRunnerFn,
+};
 
 /// ListenerKind is an enum that represents the different types of listeners that can be used in the bot.
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
@@ -11,6 +14,8 @@ pub enum ListenerKind {
     VoiceState,
     /// Modal is a listener that listens to modal submissions
     Modal,
+    /// Component is a listener that listens to component interactions (buttons, select menus)
+    Component,
 }
 
 #[derive(Clone)]
@@ -20,6 +25,10 @@ pub struct Listener {
     pub kind: ListenerKind,
     pub arguments: Vec<ArgumentsLevel>,
     pub runner: Box<dyn ListenerRunnerFn + Send + Sync>,
+    /// Cooldowns of the listener, it's not run while one of them is active
+    pub cooldowns: Vec<Cooldown>,
+    /// Kind of the feature toggled on the guilds, `Integration` when converted from an integration
+    pub feature: FeatureKind,
 }
 
 impl Listener {
@@ -32,13 +41,21 @@ impl Listener {
     ) -> Self {
         Self {
             kind,
-            arguments,
+            arguments: ArgumentsLevel::merge(&arguments, &runner.arguments()),
             runner,
             name: name.to_string(),
             description: description.to_string(),
+            cooldowns: vec![],
+            feature: FeatureKind::Listener,
         }
     }
 
+    /// Add a cooldown to the listener
+    pub fn add_cooldown(mut self, cooldown: Cooldown) -> Self {
+        self.cooldowns.push(cooldown);
+        self
+    }
+
     pub fn to_listener(&self) -> Self {
         self.clone()
     }
@@ -0,0 +1,182 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use once_cell::sync::Lazy;
+use serenity::all::GuildId;
+use tracing::{info, warn};
+
+use crate::errors::{BostilError, BostilResult};
+
+/// Locale of the responses when neither the guild nor the user has one available
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Shared registry of the locales of the guilds
+pub static LOCALES: Lazy<LocaleRegistry> = Lazy::new(LocaleRegistry::new);
+
+/// Persistence of the locales chosen by the guilds
+pub trait LocaleStore: Send + Sync {
+    /// Locales of all the guilds that chose one
+    fn load(&self) -> BostilResult<Vec<(GuildId, String)>>;
+    /// Insert or update the locale of the guild
+    fn save(&self, guild_id: GuildId, locale: &str) -> BostilResult<()>;
+}
+
+/// Text of a key on a locale file (`locale`, `key`), `None` when the locale file doesn't have the key
+///
+/// The locale files are embedded by the application, so the lookup is set by it
+pub type Translator = fn(&str, &str) -> Option<String>;
+
+/// Registry of the available locales and the ones of the guilds, kept in memory to resolve
+/// the locale of every invocation without hitting the store
+pub struct LocaleRegistry {
+    available: RwLock<Vec<String>>,
+    guilds: RwLock<HashMap<GuildId, String>>,
+    store: OnceLock<Arc<dyn LocaleStore>>,
+    translator: OnceLock<Translator>,
+}
+
+impl LocaleRegistry {
+    pub fn new() -> Self {
+        Self {
+            available: RwLock::new(vec![DEFAULT_LOCALE.to_string()]),
+            guilds: RwLock::new(HashMap::new()),
+            store: OnceLock::new(),
+            translator: OnceLock::new(),
+        }
+    }
+
+    fn read_guilds(&self) -> RwLockReadGuard<'_, HashMap<GuildId, String>> {
+        match self.guilds.read() {
+            Ok(guilds) => guilds,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn write_guilds(&self) -> RwLockWriteGuard<'_, HashMap<GuildId, String>> {
+        match self.guilds.write() {
+            Ok(guilds) => guilds,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Set the store of the guild locales, only the first store is kept
+    pub fn set_store(&self, store: impl LocaleStore + 'static) {
+        if self.store.set(Arc::new(store)).is_err() {
+            warn!("Locale store already set");
+        }
+    }
+
+    /// Set the lookup of the keys on the locale files, only the first translator is kept
+    pub fn set_translator(&self, translator: Translator) {
+        if self.translator.set(translator).is_err() {
+            warn!("Locale translator already set");
+        }
+    }
+
+    /// Text of the key on the locale file, without falling back to another locale
+    pub fn translate(&self, locale: &str, key: &str) -> Option<String> {
+        self.translator
+            .get()
+            .and_then(|translator| translator(locale, key))
+    }
+
+    /// Texts of the key on every available locale that has it (eg.: `[("pt-BR", "ajuda")]`)
+    pub fn localizations(&self, key: &str) -> Vec<(String, String)> {
+        self.available()
+            .into_iter()
+            .filter_map(|locale| {
+                self.translate(&locale, key)
+                    .map(|localized| (locale, localized))
+            })
+            .collect()
+    }
+
+    /// Set the locales that have a locale file (eg.: `en-US`, `pt-BR`)
+    pub fn set_available(&self, locales: Vec<String>) {
+        match self.available.write() {
+            Ok(mut available) => *available = locales,
+            Err(poisoned) => *poisoned.into_inner() = locales,
+        }
+    }
+
+    /// The locales that have a locale file
+    pub fn available(&self) -> Vec<String> {
+        match self.available.read() {
+            Ok(available) => available.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Available locale matching the given one, by its language when the region isn't available (eg.: `en-GB` -> `en-US`)
+    pub fn find(&self, locale: &str) -> Option<String> {
+        let available = self.available();
+        let language = locale.split('-').next().unwrap_or(locale);
+
+        available
+            .iter()
+            .find(|available| available.eq_ignore_ascii_case(locale))
+            .or_else(|| {
+                available
+                    .iter()
+                    .find(|available| available.split('-').next() == Some(language))
+            })
+            .cloned()
+    }
+
+    /// Replace the cached guild locales by the stored ones
+    pub fn load(&self) -> BostilResult<()> {
+        let Some(store) = self.store.get() else {
+            return Ok(());
+        };
+
+        let guilds = store
+            .load()?
+            .into_iter()
+            .collect::<HashMap<GuildId, String>>();
+
+        info!("Loaded the locales of {} guilds", guilds.len());
+
+        *self.write_guilds() = guilds;
+
+        Ok(())
+    }
+
+    /// Locale chosen by the guild
+    pub fn guild_locale(&self, guild_id: GuildId) -> Option<String> {
+        self.read_guilds().get(&guild_id).cloned()
+    }
+
+    /// Set the locale of the guild, persisting it when a store is set
+    pub fn set(&self, guild_id: GuildId, locale: &str) -> BostilResult<()> {
+        let Some(locale) = self.find(locale).filter(|found| found == locale) else {
+            return Err(BostilError::validation(format!(
+                "Locale {} is not available",
+                locale
+            )));
+        };
+
+        if let Some(store) = self.store.get() {
+            store.save(guild_id, &locale)?;
+        }
+
+        self.write_guilds().insert(guild_id, locale);
+
+        Ok(())
+    }
+
+    /// Locale of an invocation: the one of the guild, then the one of the user, then `DEFAULT_LOCALE`
+    pub fn resolve(&self, guild_id: Option<GuildId>, user_locale: Option<&str>) -> String {
+        guild_id
+            .and_then(|guild_id| self.guild_locale(guild_id))
+            .or_else(|| user_locale.and_then(|locale| self.find(locale)))
+            .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+    }
+}
+
+impl Default for LocaleRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
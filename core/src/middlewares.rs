@@ -0,0 +1,92 @@
+use serenity::async_trait;
+
+use crate::{
+    arguments::InvocationContext,
+    commands::ResolvedCommand,
+    errors::{BostilError, BostilResult},
+    features::{FeatureKind, FEATURES},
+    runners::runners::CommandResponse,
+};
+
+/// Hooks executed around the runner of every command of a collector
+///
+/// The `before` hooks run in the registration order and the first error stops the command,
+/// the `after` and `on_error` hooks run in the reverse order, only on the middlewares whose `before` hook ran
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Executed before the runner, an error denies the command (the runner isn't executed)
+    async fn before(
+        &self,
+        _command: &ResolvedCommand<'_>,
+        _invocation: &InvocationContext,
+    ) -> BostilResult<()> {
+        Ok(())
+    }
+
+    /// Executed after the runner succeeded, before the response is sent
+    async fn after(
+        &self,
+        _command: &ResolvedCommand<'_>,
+        _invocation: &InvocationContext,
+        _response: &CommandResponse,
+    ) {
+    }
+
+    /// Executed when a `before` hook or the runner failed
+    async fn on_error(
+        &self,
+        _command: &ResolvedCommand<'_>,
+        _invocation: &InvocationContext,
+        _error: &BostilError,
+    ) {
+    }
+}
+
+/// Middleware that denies the commands disabled on the guild of the invocation
+pub struct FeaturesMiddleware;
+
+#[async_trait]
+impl Middleware for FeaturesMiddleware {
+    async fn before(
+        &self,
+        command: &ResolvedCommand<'_>,
+        invocation: &InvocationContext,
+    ) -> BostilResult<()> {
+        let guild_id = invocation.guild_id;
+
+        // toggles apply to the root command, with all its subcommands
+        match FEATURES.is_enabled(guild_id, FeatureKind::Command, &command.root_name) {
+            true => Ok(()),
+            false => Err(BostilError::user_facing("commands.features.disabled")
+                .with("command", &command.root_name)),
+        }
+    }
+}
+
+/// Middleware that checks the preconditions of the command
+pub struct PreconditionsMiddleware;
+
+#[async_trait]
+impl Middleware for PreconditionsMiddleware {
+    async fn before(
+        &self,
+        command: &ResolvedCommand<'_>,
+        invocation: &InvocationContext,
+    ) -> BostilResult<()> {
+        Ok(command.check_preconditions(invocation).await?)
+    }
+}
+
+/// Middleware that checks (and starts) the cooldowns of the command
+pub struct CooldownsMiddleware;
+
+#[async_trait]
+impl Middleware for CooldownsMiddleware {
+    async fn before(
+        &self,
+        command: &ResolvedCommand<'_>,
+        invocation: &InvocationContext,
+    ) -> BostilResult<()> {
+        Ok(command.check_cooldowns(invocation)?)
+    }
+}
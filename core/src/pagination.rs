@@ -0,0 +1,632 @@
+//! Paginated embed with previous/next/jump components, owned by the user that requested it
+//!
+//! The pages are rendered on demand from a `PageSource`, the paginator is tracked by the `EMBEDS` registry
+//! so its components are disabled by the scheduler after the timeout.
+
+use once_cell::sync::Lazy;
+use serenity::{
+    all::{
+        ButtonStyle, CommandInteraction, ComponentInteraction, ComponentInteractionDataKind,
+        Message, UserId,
+    },
+    builder::{
+        CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+        CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind,
+        CreateSelectMenuOption, EditInteractionResponse,
+    },
+    client::Context,
+    model::Colour,
+};
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    embeds::{ApplicationEmbed, EmbedLifetime, EMBEDS},
+    errors::{BostilError, BostilResult},
+    locales::{DEFAULT_LOCALE, LOCALES},
+};
+
+/// Name of the custom_id of the paginator components (`paginator/<action>`)
+pub const PAGINATOR_CUSTOM_ID: &str = "paginator";
+/// Inactivity after which the paginator stops changing pages
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+/// Discord limit of fields of an embed and of options of a select menu
+const MAX_ITEMS: usize = 25;
+
+/// Source of the pages of a paginator
+pub trait PageSource: Send + Sync {
+    /// Number of pages, an empty source has a single (empty) page
+    fn pages(&self) -> usize;
+    /// Embed of the page (starting at 0)
+    fn render(&self, page: usize) -> CreateEmbed;
+}
+
+/// Pages built from embed fields, split in pages of up to 25 fields
+#[derive(Debug, Clone)]
+pub struct FieldPages {
+    pub title: String,
+    pub description: Option<String>,
+    pub colour: Option<Colour>,
+    /// Fields of the embeds (name, value, inline)
+    pub fields: Vec<(String, String, bool)>,
+    pub per_page: usize,
+}
+
+impl FieldPages {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            description: None,
+            colour: None,
+            fields: vec![],
+            per_page: MAX_ITEMS,
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn colour(mut self, colour: impl Into<Colour>) -> Self {
+        self.colour = Some(colour.into());
+        self
+    }
+
+    pub fn field(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+        inline: bool,
+    ) -> Self {
+        self.fields.push((name.into(), value.into(), inline));
+        self
+    }
+
+    /// Fields per page, between 1 and 25
+    pub fn per_page(mut self, per_page: usize) -> Self {
+        self.per_page = per_page.clamp(1, MAX_ITEMS);
+        self
+    }
+}
+
+impl PageSource for FieldPages {
+    fn pages(&self) -> usize {
+        self.fields.len().div_ceil(self.per_page).max(1)
+    }
+
+    fn render(&self, page: usize) -> CreateEmbed {
+        let mut embed = CreateEmbed::default().title(self.title.clone()).fields(
+            self.fields
+                .iter()
+                .skip(page * self.per_page)
+                .take(self.per_page)
+                .cloned(),
+        );
+
+        if let Some(description) = &self.description {
+            embed = embed.description(description.clone());
+        }
+
+        if let Some(colour) = self.colour {
+            embed = embed.colour(colour);
+        }
+
+        embed
+    }
+}
+
+/// Pages built from embeds rendered beforehand (eg.: one embed per queue entry)
+impl PageSource for Vec<CreateEmbed> {
+    fn pages(&self) -> usize {
+        self.len().max(1)
+    }
+
+    fn render(&self, page: usize) -> CreateEmbed {
+        self.get(page).cloned().unwrap_or_default()
+    }
+}
+
+/// State of a sent paginator, shared with the `EMBEDS` registry
+struct PaginatorState {
+    source: Arc<dyn PageSource>,
+    owner: Option<UserId>,
+    page: AtomicUsize,
+    timeout: Duration,
+    last_interaction: Mutex<Instant>,
+    /// The components were disabled after the timeout
+    closed: AtomicBool,
+}
+
+impl PaginatorState {
+    fn pages(&self) -> usize {
+        self.source.pages().max(1)
+    }
+
+    fn page(&self) -> usize {
+        self.page.load(Ordering::SeqCst).min(self.pages() - 1)
+    }
+
+    fn is_expired(&self) -> bool {
+        match self.last_interaction.lock() {
+            Ok(last_interaction) => last_interaction.elapsed() >= self.timeout,
+            Err(_) => true,
+        }
+    }
+
+    fn render(&self) -> CreateEmbed {
+        let page = self.page();
+
+        self.source
+            .render(page)
+            .footer(CreateEmbedFooter::new(format!(
+                "{}/{}",
+                page + 1,
+                self.pages()
+            )))
+    }
+
+    fn components(&self) -> Vec<CreateActionRow> {
+        let pages = self.pages();
+
+        if pages == 1 {
+            return vec![];
+        }
+
+        let page = self.page();
+        let disabled = self.is_expired();
+
+        let button = |action: &str, label: &str| {
+            CreateButton::new(format!("{}/{}", PAGINATOR_CUSTOM_ID, action))
+                .style(ButtonStyle::Secondary)
+                .label(label)
+                .disabled(disabled)
+        };
+
+        let mut components = vec![CreateActionRow::Buttons(vec![
+            button("first", "⏮").disabled(disabled || page == 0),
+            button("previous", "◀").disabled(disabled || page == 0),
+            button("page", &format!("{}/{}", page + 1, pages)).disabled(true),
+            button("next", "▶").disabled(disabled || page + 1 == pages),
+            button("last", "⏭").disabled(disabled || page + 1 == pages),
+        ])];
+
+        // the jump menu shows up to 25 pages around the current one
+        if pages > 2 {
+            let start = page
+                .saturating_sub(MAX_ITEMS / 2)
+                .min(pages.saturating_sub(MAX_ITEMS));
+            let options = (start..pages.min(start + MAX_ITEMS))
+                .map(|option| {
+                    CreateSelectMenuOption::new((option + 1).to_string(), option.to_string())
+                        .default_selection(option == page)
+                })
+                .collect();
+
+            components.push(CreateActionRow::SelectMenu(
+                CreateSelectMenu::new(
+                    format!("{}/jump", PAGINATOR_CUSTOM_ID),
+                    CreateSelectMenuKind::String { options },
+                )
+                .disabled(disabled),
+            ));
+        }
+
+        components
+    }
+
+    /// Move to the page of the action, returning whether the page changed
+    fn navigate(&self, action: &str, component: &ComponentInteraction) -> bool {
+        let page = self.page();
+        let last = self.pages() - 1;
+
+        let target = match action {
+            "first" => 0,
+            "previous" => page.saturating_sub(1),
+            "next" => (page + 1).min(last),
+            "last" => last,
+            "jump" => match &component.data.kind {
+                ComponentInteractionDataKind::StringSelect { values } => values
+                    .first()
+                    .and_then(|value| value.parse::<usize>().ok())
+                    .unwrap_or(page)
+                    .min(last),
+                _ => page,
+            },
+            _ => page,
+        };
+
+        if let Ok(mut last_interaction) = self.last_interaction.lock() {
+            *last_interaction = Instant::now();
+        }
+
+        self.page.store(target, Ordering::SeqCst);
+
+        target != page
+    }
+}
+
+/// Lifetime of the paginated embeds, the argument is the `PaginatorState`
+struct PaginatedLifetime;
+
+impl PaginatedLifetime {
+    fn state(arguments: &[Box<dyn Any + Send + Sync>]) -> Option<&Arc<PaginatorState>> {
+        arguments.first()?.downcast_ref::<Arc<PaginatorState>>()
+    }
+}
+
+impl EmbedLifetime for PaginatedLifetime {
+    /// The pages are rendered by the command, already on its locale
+    fn build(&self, _locale: &str, arguments: &[Box<dyn Any + Send + Sync>]) -> CreateEmbed {
+        Self::state(arguments)
+            .map(|state| state.render())
+            .unwrap_or_default()
+    }
+
+    fn components(&self, arguments: &[Box<dyn Any + Send + Sync>]) -> Option<Vec<CreateActionRow>> {
+        Self::state(arguments).map(|state| state.components())
+    }
+
+    /// Updated once after the timeout, to disable the components
+    fn should_update(&self, arguments: &[Box<dyn Any + Send + Sync>]) -> bool {
+        Self::state(arguments)
+            .is_some_and(|state| state.is_expired() && !state.closed.swap(true, Ordering::SeqCst))
+    }
+
+    fn should_detach(&self, arguments: &[Box<dyn Any + Send + Sync>]) -> bool {
+        Self::state(arguments).is_none_or(|state| state.closed.load(Ordering::SeqCst))
+    }
+}
+
+pub static PAGINATED_EMBED: Lazy<ApplicationEmbed> = Lazy::new(|| {
+    ApplicationEmbed::new(
+        "Paginated",
+        Some("Embed with pages changed by its components"),
+        None,
+        vec![],
+        Box::new(PaginatedLifetime),
+        None,
+        None,
+        None,
+    )
+});
+
+/// Paged view of a `PageSource`, sent to a channel or as the response of a command
+#[derive(Clone)]
+pub struct Paginator {
+    pub source: Arc<dyn PageSource>,
+    /// Only the owner can change the pages, anyone can when `None`
+    pub owner: Option<UserId>,
+    pub timeout: Duration,
+    /// Initial page (starting at 0)
+    pub page: usize,
+}
+
+impl Paginator {
+    pub fn new(source: impl PageSource + 'static) -> Self {
+        Self {
+            source: Arc::new(source),
+            owner: None,
+            timeout: DEFAULT_TIMEOUT,
+            page: 0,
+        }
+    }
+
+    pub fn owner(mut self, owner: UserId) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn page(mut self, page: usize) -> Self {
+        self.page = page;
+        self
+    }
+
+    fn state(&self) -> Arc<PaginatorState> {
+        Arc::new(PaginatorState {
+            source: self.source.clone(),
+            owner: self.owner,
+            page: AtomicUsize::new(self.page),
+            timeout: self.timeout,
+            last_interaction: Mutex::new(Instant::now()),
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    /// Send the paginator to the channel, its pages are already rendered on their locale
+    pub async fn send(
+        &self,
+        ctx: &Context,
+        channel_id: serenity::all::ChannelId,
+    ) -> BostilResult<Message> {
+        EMBEDS
+            .send_with(
+                ctx,
+                &PAGINATED_EMBED,
+                channel_id,
+                DEFAULT_LOCALE,
+                vec![Box::new(self.state())],
+            )
+            .await
+    }
+
+    /// Send the paginator as the response of a (deferred) command
+    pub async fn edit_response(
+        &self,
+        ctx: &Context,
+        command: &CommandInteraction,
+    ) -> Result<Message, serenity::Error> {
+        let state = self.state();
+
+        let message = command
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .embed(state.render())
+                    .components(state.components()),
+            )
+            .await?;
+
+        EMBEDS.track(
+            &PAGINATED_EMBED,
+            &message,
+            &LOCALES.resolve(command.guild_id, Some(&command.locale)),
+            vec![Box::new(state)],
+        );
+
+        Ok(message)
+    }
+}
+
+impl std::fmt::Debug for Paginator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Paginator")
+            .field("pages", &self.source.pages())
+            .field("owner", &self.owner)
+            .field("timeout", &self.timeout)
+            .field("page", &self.page)
+            .finish()
+    }
+}
+
+/// Change the page of the paginator where the component was clicked (`paginator/<action>`)
+///
+/// Fails with a user-facing error when the paginator expired (`interactions.paginator.expired`)
+/// or the user isn't its owner (`interactions.paginator.not_owner`)
+pub async fn handle_component(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    action: &str,
+) -> BostilResult<()> {
+    let state = EMBEDS
+        .argument::<Arc<PaginatorState>>(component.message.id, 0)
+        .filter(|state| !state.is_expired())
+        .ok_or_else(|| BostilError::user_facing("interactions.paginator.expired"))?;
+
+    if state.owner.is_some_and(|owner| owner != component.user.id) {
+        return Err(BostilError::user_facing("interactions.paginator.not_owner"));
+    }
+
+    let response = match state.navigate(action, component) {
+        true => CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .embed(state.render())
+                .components(state.components()),
+        ),
+        false => CreateInteractionResponse::Acknowledge,
+    };
+
+    component.create_response(&ctx.http, response).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::Value;
+
+    use super::{FieldPages, PageSource, Paginator, PaginatorState};
+
+    fn fields(count: usize) -> FieldPages {
+        (0..count).fold(FieldPages::new("Queue"), |pages, index| {
+            pages.field(index.to_string(), "value", false)
+        })
+    }
+
+    fn components(state: &PaginatorState) -> Value {
+        serde_json::to_value(state.components()).unwrap()
+    }
+
+    #[test]
+    fn fields_are_split_in_pages() {
+        assert_eq!(fields(0).pages(), 1);
+        assert_eq!(fields(25).pages(), 1);
+        assert_eq!(fields(26).pages(), 2);
+        assert_eq!(fields(10).per_page(3).pages(), 4);
+        assert_eq!(fields(10).per_page(0).pages(), 10);
+    }
+
+    #[test]
+    fn page_renders_its_fields() {
+        let embed = serde_json::to_value(fields(10).per_page(3).render(3)).unwrap();
+
+        assert_eq!(embed["fields"].as_array().unwrap().len(), 1);
+        assert_eq!(embed["fields"][0]["name"], "9");
+    }
+
+    #[test]
+    fn single_page_has_no_components() {
+        let state = Paginator::new(fields(3)).state();
+
+        assert!(state.components().is_empty());
+        assert_eq!(
+            serde_json::to_value(state.render()).unwrap()["footer"]["text"],
+            "1/1"
+        );
+    }
+
+    #[test]
+    fn boundaries_disable_their_buttons() {
+        let first = components(&Paginator::new(fields(3).per_page(1)).state());
+        let last = components(&Paginator::new(fields(3).per_page(1)).page(2).state());
+
+        let disabled = |components: &Value| {
+            components[0]["components"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|button| button["disabled"] == true)
+                .collect::<Vec<bool>>()
+        };
+
+        assert_eq!(disabled(&first), vec![true, true, true, false, false]);
+        assert_eq!(disabled(&last), vec![false, false, true, true, true]);
+        // the jump menu is shown with more than 2 pages
+        assert_eq!(first[1]["components"][0]["custom_id"], "paginator/jump");
+    }
+
+    #[test]
+    fn out_of_range_page_shows_the_last_one() {
+        let state = Paginator::new(fields(3).per_page(1)).page(10).state();
+
+        assert_eq!(state.page(), 2);
+    }
+
+    #[test]
+    fn expired_paginator_disables_its_components() {
+        let state = Paginator::new(fields(2).per_page(1))
+            .timeout(Duration::ZERO)
+            .state();
+
+        let buttons = components(&state)[0]["components"].clone();
+
+        assert!(buttons
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|button| button["disabled"] == true));
+    }
+
+    #[cfg(feature = "testing")]
+    mod components {
+        use std::sync::Arc;
+
+        use crate::{
+            embeds::EMBEDS,
+            errors::BostilError,
+            pagination::{handle_component, Paginator, PAGINATED_EMBED},
+            testing::{fakes, TestHarness},
+        };
+
+        use super::fields;
+
+        #[test]
+        fn buttons_navigate_the_pages() {
+            let user = fakes::user(1, "owner");
+            let message = fakes::message(2, 3, &user, "");
+            let state = Paginator::new(fields(5).per_page(1)).state();
+
+            let navigate = |action: &str| {
+                let custom_id = format!("paginator/{}", action);
+                let changed = state.navigate(
+                    action,
+                    &fakes::button_interaction(&custom_id, &user, &message),
+                );
+
+                (changed, state.page())
+            };
+
+            assert_eq!(navigate("previous"), (false, 0));
+            assert_eq!(navigate("next"), (true, 1));
+            assert_eq!(navigate("last"), (true, 4));
+            assert_eq!(navigate("next"), (false, 4));
+            assert_eq!(navigate("first"), (true, 0));
+
+            let jump = fakes::select_interaction("paginator/jump", &["3"], &user, &message);
+
+            assert!(state.navigate("jump", &jump));
+            assert_eq!(state.page(), 3);
+        }
+
+        #[tokio::test]
+        async fn only_the_owner_changes_the_pages() {
+            let harness = TestHarness::new().await;
+            let owner = harness.user.clone();
+            let message = fakes::message(4101, 3, &owner, "");
+            let state = Paginator::new(fields(5).per_page(1))
+                .owner(owner.id)
+                .state();
+
+            EMBEDS.track(
+                &PAGINATED_EMBED,
+                &message,
+                "en-US",
+                vec![Box::new(Arc::clone(&state))],
+            );
+
+            let stranger = fakes::user(2, "stranger");
+            let result = handle_component(
+                &harness.context,
+                &fakes::button_interaction("paginator/next", &stranger, &message),
+                "next",
+            )
+            .await;
+
+            assert!(matches!(
+                result,
+                Err(BostilError::UserFacing { key, .. }) if key == "interactions.paginator.not_owner"
+            ));
+            assert_eq!(state.page(), 0);
+
+            handle_component(
+                &harness.context,
+                &fakes::button_interaction("paginator/next", &owner, &message),
+                "next",
+            )
+            .await
+            .unwrap();
+
+            let requests = harness.requests();
+
+            // the message is updated with the next page
+            assert_eq!(state.page(), 1);
+            assert_eq!(requests.len(), 1);
+            assert_eq!(requests[0].body["type"], 7);
+            assert_eq!(
+                requests[0].body["data"]["embeds"][0]["footer"]["text"],
+                "2/5"
+            );
+        }
+
+        #[tokio::test]
+        async fn untracked_paginator_expired() {
+            let harness = TestHarness::new().await;
+            let message = fakes::message(4102, 3, &harness.user, "");
+
+            let result = handle_component(
+                &harness.context,
+                &fakes::button_interaction("paginator/next", &harness.user, &message),
+                "next",
+            )
+            .await;
+
+            assert!(matches!(
+                result,
+                Err(BostilError::UserFacing { key, .. }) if key == "interactions.paginator.expired"
+            ));
+        }
+    }
+}
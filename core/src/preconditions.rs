@@ -0,0 +1,124 @@
+use serenity::model::Permissions;
+
+use crate::arguments::InvocationContext;
+
+/// Precondition that must be satisfied before a command runner is executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precondition {
+    /// The command can only be used inside a guild
+    GuildOnly,
+    /// The member must have the given permissions (also used as the command default member permissions)
+    RequiresPermissions(Permissions),
+    /// The user must be connected to a voice channel
+    UserInVoice,
+    /// The bot must be connected to a voice channel
+    BotInVoice,
+    /// The user must be the owner of the bot application (or a member of its team)
+    OwnerOnly,
+}
+
+impl Precondition {
+    /// Check the precondition against the invocation, the error is the reason of the denial
+    pub async fn check(&self, invocation: &InvocationContext) -> Result<(), PreconditionError> {
+        let satisfied = match self {
+            Precondition::GuildOnly => invocation.guild_id.is_some(),
+            Precondition::RequiresPermissions(permissions) => invocation
+                .member_permissions
+                .is_some_and(|member_permissions| member_permissions.contains(*permissions)),
+            Precondition::UserInVoice => in_voice(invocation, invocation.user.id),
+            Precondition::BotInVoice => {
+                let bot_id = invocation.context.cache.current_user().id;
+
+                in_voice(invocation, bot_id)
+            }
+            Precondition::OwnerOnly => {
+                match invocation.context.http.get_current_application_info().await {
+                    Ok(application) => {
+                        let is_owner = application
+                            .owner
+                            .is_some_and(|owner| owner.id == invocation.user.id);
+                        let is_team_member = application.team.is_some_and(|team| {
+                            team.members
+                                .iter()
+                                .any(|member| member.user.id == invocation.user.id)
+                        });
+
+                        is_owner || is_team_member
+                    }
+                    Err(_) => false,
+                }
+            }
+        };
+
+        match satisfied {
+            true => Ok(()),
+            false => Err(PreconditionError(*self)),
+        }
+    }
+
+    /// Check all the preconditions, failing on the first that is not satisfied
+    pub async fn check_all(
+        preconditions: &Vec<Precondition>,
+        invocation: &InvocationContext,
+    ) -> Result<(), PreconditionError> {
+        for precondition in preconditions {
+            precondition.check(invocation).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Permissions required by the preconditions, `None` when there is no permission precondition
+    pub fn default_member_permissions(preconditions: &[Precondition]) -> Option<Permissions> {
+        preconditions
+            .iter()
+            .filter_map(|precondition| match precondition {
+                Precondition::RequiresPermissions(permissions) => Some(*permissions),
+                _ => None,
+            })
+            .reduce(|required, permissions| required | permissions)
+    }
+}
+
+fn in_voice(invocation: &InvocationContext, user_id: serenity::model::id::UserId) -> bool {
+    invocation.guild.as_ref().is_some_and(|guild| {
+        guild
+            .voice_states
+            .get(&user_id)
+            .is_some_and(|voice_state| voice_state.channel_id.is_some())
+    })
+}
+
+/// Error when a precondition of a command is not satisfied
+#[derive(Debug, Clone, Copy)]
+pub struct PreconditionError(pub Precondition);
+
+impl PreconditionError {
+    /// Locale key of the denial message (eg.: `guild_only`)
+    pub fn key(&self) -> &'static str {
+        match self.0 {
+            Precondition::GuildOnly => "guild_only",
+            Precondition::RequiresPermissions(_) => "requires_permissions",
+            Precondition::UserInVoice => "user_in_voice",
+            Precondition::BotInVoice => "bot_in_voice",
+            Precondition::OwnerOnly => "owner_only",
+        }
+    }
+}
+
+impl std::fmt::Display for PreconditionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Precondition::RequiresPermissions(permissions) => {
+                write!(
+                    f,
+                    "Precondition requires_permissions ({}) not satisfied",
+                    permissions
+                )
+            }
+            _ => write!(f, "Precondition {} not satisfied", self.key()),
+        }
+    }
+}
+
+impl std::error::Error for PreconditionError {}
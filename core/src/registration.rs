@@ -0,0 +1,401 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    hash::{Hash, Hasher},
+};
+
+use serde_json::Value;
+use serenity::{
+    all::{Command, CommandId, GuildId},
+    http::Http,
+};
+
+/// Fields of a command compared with the registered one, with their default value on Discord
+const COMMAND_FIELDS: [(&str, Option<Value>); 9] = [
+    ("name", None),
+    ("name_localizations", None),
+    ("description", None),
+    ("description_localizations", None),
+    ("options", None),
+    ("default_member_permissions", None),
+    ("dm_permission", Some(Value::Bool(true))),
+    ("type", None),
+    ("nsfw", Some(Value::Bool(false))),
+];
+
+/// Fields of a command option (or choice) compared with the registered one, with their default value on Discord
+const OPTION_FIELDS: [(&str, Option<Value>); 15] = [
+    ("type", None),
+    ("name", None),
+    ("name_localizations", None),
+    ("description", None),
+    ("description_localizations", None),
+    ("value", None),
+    ("required", Some(Value::Bool(false))),
+    ("choices", None),
+    ("options", None),
+    ("channel_types", None),
+    ("min_value", None),
+    ("max_value", None),
+    ("min_length", None),
+    ("max_length", None),
+    ("autocomplete", Some(Value::Bool(false))),
+];
+
+/// Fingerprint of a command with the stable hash of its data
+#[derive(Debug, Clone)]
+pub struct CommandFingerprint {
+    pub name: String,
+    pub hash: u64,
+    /// Serialized (and localized) fingerprint, as sent on the registration
+    pub data: Value,
+}
+
+impl CommandFingerprint {
+    pub fn new(data: Value) -> Self {
+        Self {
+            name: data["name"].as_str().unwrap_or_default().to_string(),
+            hash: hash_command(data.clone()),
+            data,
+        }
+    }
+}
+
+/// Stable hash of a command (fingerprint or registered), only the fields sent on the registration are considered
+pub fn hash_command(value: Value) -> u64 {
+    let mut normalized = normalize(value, &COMMAND_FIELDS);
+
+    // a chat input command is the default type
+    if normalized.get("type") == Some(&Value::from(1)) {
+        normalized.remove("type");
+    }
+
+    let mut hasher = DefaultHasher::new();
+    // the map is ordered by key, so the serialization is stable
+    Value::Object(normalized.into_iter().collect())
+        .to_string()
+        .hash(&mut hasher);
+
+    hasher.finish()
+}
+
+/// Keep only the given fields, dropping the empty ones and the ones with the default value
+fn normalize(value: Value, fields: &[(&str, Option<Value>)]) -> BTreeMap<String, Value> {
+    let Value::Object(object) = value else {
+        return BTreeMap::new();
+    };
+
+    let mut normalized = BTreeMap::new();
+
+    for (field, default) in fields {
+        let value = match object.get(*field) {
+            Some(Value::Array(items)) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| match item {
+                        Value::Object(_) => Value::Object(
+                            normalize(item.clone(), &OPTION_FIELDS)
+                                .into_iter()
+                                .collect(),
+                        ),
+                        item => item.clone(),
+                    })
+                    .collect(),
+            ),
+            // localizations are maps, sorted by key to keep the serialization stable
+            Some(Value::Object(map)) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect::<BTreeMap<String, Value>>()
+                    .into_iter()
+                    .collect(),
+            ),
+            Some(value) => value.clone(),
+            None => continue,
+        };
+
+        let is_empty = match &value {
+            Value::Null => true,
+            Value::Array(items) => items.is_empty(),
+            Value::Object(object) => object.is_empty(),
+            _ => false,
+        };
+
+        if is_empty || default.as_ref() == Some(&value) {
+            continue;
+        }
+
+        normalized.insert(field.to_string(), value);
+    }
+
+    normalized
+}
+
+/// Changes needed to make the registered commands (of a guild or global) match the collected ones
+#[derive(Debug, Clone)]
+pub struct CommandsDiff {
+    /// Guild of the commands, `None` for the global commands
+    pub guild_id: Option<GuildId>,
+    /// Collected commands that aren't registered
+    pub create: Vec<CommandFingerprint>,
+    /// Registered commands whose fingerprint changed
+    pub edit: Vec<(CommandId, CommandFingerprint)>,
+    /// Registered commands that aren't collected anymore
+    pub delete: Vec<(CommandId, String)>,
+    /// Names of the registered commands that didn't change
+    pub unchanged: Vec<String>,
+}
+
+impl CommandsDiff {
+    /// Compare the collected fingerprints against the registered commands, matching them by name
+    pub fn new(
+        guild_id: Option<GuildId>,
+        fingerprints: Vec<CommandFingerprint>,
+        registered: &Vec<Command>,
+    ) -> Self {
+        let mut diff = Self {
+            guild_id,
+            create: vec![],
+            edit: vec![],
+            delete: vec![],
+            unchanged: vec![],
+        };
+
+        for fingerprint in fingerprints {
+            match registered
+                .iter()
+                .find(|command| command.name == fingerprint.name)
+            {
+                Some(command) => {
+                    let hash = hash_command(serde_json::to_value(command).unwrap_or_default());
+
+                    match hash == fingerprint.hash {
+                        true => diff.unchanged.push(fingerprint.name),
+                        false => diff.edit.push((command.id, fingerprint)),
+                    }
+                }
+                None => diff.create.push(fingerprint),
+            }
+        }
+
+        for command in registered {
+            let collected = diff.unchanged.contains(&command.name)
+                || diff.edit.iter().any(|(id, _)| *id == command.id);
+
+            if !collected {
+                diff.delete.push((command.id, command.name.clone()));
+            }
+        }
+
+        diff
+    }
+
+    /// Whether the registered commands already match the collected ones
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.edit.is_empty() && self.delete.is_empty()
+    }
+
+    /// Create, edit and delete the changed commands (the unchanged ones aren't touched)
+    pub async fn apply(&self, http: &Http) -> Result<(), serenity::Error> {
+        for fingerprint in self.create.iter() {
+            match self.guild_id {
+                Some(guild_id) => {
+                    http.create_guild_command(guild_id, &fingerprint.data)
+                        .await?;
+                }
+                None => {
+                    http.create_global_command(&fingerprint.data).await?;
+                }
+            }
+        }
+
+        for (command_id, fingerprint) in self.edit.iter() {
+            match self.guild_id {
+                Some(guild_id) => {
+                    http.edit_guild_command(guild_id, *command_id, &fingerprint.data)
+                        .await?;
+                }
+                None => {
+                    http.edit_global_command(*command_id, &fingerprint.data)
+                        .await?;
+                }
+            }
+        }
+
+        for (command_id, _) in self.delete.iter() {
+            match self.guild_id {
+                Some(guild_id) => guild_id.delete_command(http, *command_id).await?,
+                None => Command::delete_global_command(http, *command_id).await?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for CommandsDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.guild_id {
+            Some(guild_id) => write!(f, "Commands of guild {}:", guild_id)?,
+            None => write!(f, "Global commands:")?,
+        }
+
+        for fingerprint in self.create.iter() {
+            write!(f, "\n  + {}", fingerprint.name)?;
+        }
+
+        for (command_id, fingerprint) in self.edit.iter() {
+            write!(f, "\n  ~ {} ({})", fingerprint.name, command_id)?;
+        }
+
+        for (command_id, name) in self.delete.iter() {
+            write!(f, "\n  - {} ({})", name, command_id)?;
+        }
+
+        write!(f, "\n  {} unchanged", self.unchanged.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use serenity::all::{Command, CommandId, GuildId};
+
+    use super::{hash_command, CommandFingerprint, CommandsDiff};
+    use crate::{
+        commands::{self, CommandCategory, CommandContext},
+        preconditions::Precondition,
+    };
+
+    fn ping() -> Value {
+        json!({
+            "name": "ping",
+            "description": "Check if the bot is alive",
+            "name_localizations": { "pt-BR": "ping" },
+        })
+    }
+
+    /// Command as registered on Discord, with the fields it fills by default
+    fn registered(id: u64, data: Value) -> Command {
+        let mut command = json!({
+            "id": id.to_string(),
+            "application_id": "1",
+            "version": "1",
+            "type": 1,
+            "dm_permission": true,
+            "nsfw": false,
+            "options": [],
+        });
+
+        for (field, value) in data.as_object().unwrap() {
+            command[field] = value.clone();
+        }
+
+        serde_json::from_value(command).unwrap()
+    }
+
+    /// Command as registered on a guild, Discord doesn't return the dm_permission of guild commands
+    fn registered_on_guild(id: u64, guild_id: u64, data: Value) -> Command {
+        let mut command = serde_json::to_value(registered(id, data)).unwrap();
+        command["guild_id"] = json!(guild_id.to_string());
+        command.as_object_mut().unwrap().remove("dm_permission");
+
+        serde_json::from_value(command).unwrap()
+    }
+
+    #[test]
+    fn hash_ignores_the_discord_defaults() {
+        let fingerprint = CommandFingerprint::new(ping());
+        let command = serde_json::to_value(registered(10, ping())).unwrap();
+
+        assert_eq!(hash_command(command), fingerprint.hash);
+    }
+
+    #[test]
+    fn hash_ignores_the_order_of_the_localizations() {
+        let first =
+            json!({ "name": "ping", "name_localizations": { "pt-BR": "ping", "en-US": "ping" } });
+        let second =
+            json!({ "name": "ping", "name_localizations": { "en-US": "ping", "pt-BR": "ping" } });
+
+        assert_eq!(hash_command(first), hash_command(second));
+    }
+
+    #[test]
+    fn unregistered_command_is_created() {
+        let diff = CommandsDiff::new(None, vec![CommandFingerprint::new(ping())], &vec![]);
+
+        assert_eq!(diff.create.len(), 1);
+        assert_eq!(diff.create[0].name, "ping");
+        assert!(diff.edit.is_empty() && diff.delete.is_empty());
+    }
+
+    #[test]
+    fn unchanged_command_is_not_touched() {
+        let diff = CommandsDiff::new(
+            None,
+            vec![CommandFingerprint::new(ping())],
+            &vec![registered(10, ping())],
+        );
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged, vec!["ping".to_string()]);
+    }
+
+    #[test]
+    fn changed_command_is_edited() {
+        let mut changed = ping();
+        changed["description"] = json!("Test the latency to the server");
+
+        let diff = CommandsDiff::new(
+            None,
+            vec![CommandFingerprint::new(changed)],
+            &vec![registered(10, ping())],
+        );
+
+        assert_eq!(diff.edit.len(), 1);
+        assert_eq!(diff.edit[0].0, CommandId::new(10));
+        assert!(diff.create.is_empty() && diff.delete.is_empty());
+    }
+
+    #[test]
+    fn uncollected_command_is_deleted() {
+        let jingle = json!({ "name": "jingle", "description": "Play a jingle" });
+
+        let diff = CommandsDiff::new(
+            None,
+            vec![CommandFingerprint::new(ping())],
+            &vec![registered(10, ping()), registered(11, jingle)],
+        );
+
+        assert_eq!(
+            diff.delete,
+            vec![(CommandId::new(11), "jingle".to_string())]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "Global commands:\n  - jingle (11)\n  1 unchanged"
+        );
+    }
+
+    #[test]
+    fn unchanged_guild_only_command_on_guild_is_not_touched() {
+        let command = commands::Command::with_subcommands(
+            "poll",
+            "Manage the polls",
+            CommandContext::Guild,
+            CommandCategory::Misc,
+            None,
+        )
+        .add_precondition(Precondition::GuildOnly);
+        let fingerprint = command.localized_fingerprint();
+
+        let diff = CommandsDiff::new(
+            Some(GuildId::new(1)),
+            vec![CommandFingerprint::new(fingerprint.clone())],
+            &vec![registered_on_guild(10, 1, fingerprint)],
+        );
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged, vec!["poll".to_string()]);
+    }
+}
@@ -0,0 +1,33 @@
+use dyn_clone::DynClone;
+use serenity::{async_trait, builder::AutocompleteChoice};
+
+use crate::arguments::InvocationContext;
+
+/// Function that suggests the choices of an autocomplete option while the user types its value
+#[async_trait]
+pub trait AutocompleteProviderFn: DynClone {
+    async fn suggest(&self, invocation: &InvocationContext, value: &str)
+        -> Vec<AutocompleteChoice>;
+}
+
+dyn_clone::clone_trait_object!(AutocompleteProviderFn);
+
+/// Synchronous autocomplete provider (eg.: filtering a static list)
+pub type AutocompleteFn = fn(&InvocationContext, &str) -> Vec<AutocompleteChoice>;
+
+#[async_trait]
+impl AutocompleteProviderFn for AutocompleteFn {
+    async fn suggest(
+        &self,
+        invocation: &InvocationContext,
+        value: &str,
+    ) -> Vec<AutocompleteChoice> {
+        self(invocation, value)
+    }
+}
+
+impl std::fmt::Debug for dyn AutocompleteProviderFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<AutocompleteProviderFn>")
+    }
+}
@@ -1,32 +1,96 @@
 use dyn_clone::DynClone;
+use serde::Serialize;
 use serenity::{
     all::Embed,
     async_trait,
-    builder::{CreateEmbed, EditInteractionResponse},
-    framework::standard::CommandResult as SerenityCommandResult,
+    builder::{
+        CreateActionRow, CreateAttachment, CreateEmbed, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, CreateModal, EditInteractionResponse,
+    },
+};
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    arguments::{ArgumentsLevel, FromArguments},
+    errors::BostilResult,
+    pagination::Paginator,
 };
-use std::any::Any;
 
 /// CommandResponse is a type of response that the command can return
 #[derive(Debug, Clone)]
 pub enum CommandResponse {
     String(String),
-    Embed(Embed),
+    Embed(Box<Embed>),
+    /// Multiple embeds on the same message
+    Embeds(Vec<CreateEmbed>),
+    /// Message with files attached
+    Attachments {
+        content: Option<String>,
+        files: Vec<CreateAttachment>,
+    },
+    /// Message with components (buttons, select menus), handled by the `Component` listeners
+    Components {
+        content: String,
+        components: Vec<CreateActionRow>,
+    },
     Message(EditInteractionResponse),
+    /// Paged view, its components change the pages
+    Paginated(Paginator),
+    /// Response only shown to the user who ran the command
+    Ephemeral(Box<CommandResponse>),
+    /// Modal opened to the user, handled by the `Modal` listeners
+    ///
+    /// It must be the first response, so it can't be returned by runners slow enough to get the interaction deferred
+    Modal(CreateModal),
+    /// Response followed by other messages
+    FollowUps {
+        response: Box<CommandResponse>,
+        followups: Vec<CreateInteractionResponseFollowup>,
+    },
     None,
 }
 
 /// CommandResult is a type of result (ok or error) that the command can return
-pub type CommandResult<'a> = SerenityCommandResult<CommandResponse>;
+pub type CommandResult<'a> = BostilResult<CommandResponse>;
 
 /// Function that will be executed when the command is called
 #[async_trait]
 pub trait CommandRunnerFn: DynClone {
-    async fn run<'a>(&self, arguments: &Vec<Box<dyn Any + Send + Sync>>) -> CommandResult<'a>;
+    async fn run<'a>(&self, arguments: &[Box<dyn Any + Send + Sync>]) -> CommandResult<'a>;
+    /// Arguments that the runner requires to be provided
+    fn arguments(&self) -> Vec<ArgumentsLevel> {
+        vec![]
+    }
 }
 
 dyn_clone::clone_trait_object!(CommandRunnerFn);
 
+/// Function that will be executed when the command is called, receiving its arguments already extracted
+///
+/// The arguments levels are derived from `Arguments`, so they are always provided to the runner
+#[async_trait]
+pub trait TypedCommandRunnerFn: DynClone {
+    type Arguments: FromArguments + Send;
+
+    async fn run_typed<'a>(&self, arguments: Self::Arguments) -> CommandResult<'a>;
+}
+
+#[async_trait]
+impl<T> CommandRunnerFn for T
+where
+    T: TypedCommandRunnerFn + Send + Sync,
+{
+    async fn run<'a>(&self, arguments: &[Box<dyn Any + Send + Sync>]) -> CommandResult<'a> {
+        let arguments = T::Arguments::from_arguments(arguments)?;
+
+        self.run_typed(arguments).await
+    }
+
+    fn arguments(&self) -> Vec<ArgumentsLevel> {
+        T::Arguments::levels()
+    }
+}
+
 impl std::fmt::Debug for dyn CommandRunnerFn {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "<RunnerFn>")
@@ -34,58 +98,223 @@ impl std::fmt::Debug for dyn CommandRunnerFn {
 }
 
 impl CommandResponse {
-    pub fn to_embed(&self) -> CreateEmbed {
+    /// Make the response only visible to the user who ran the command
+    pub fn ephemeral(self) -> Self {
         match self {
-            CommandResponse::String(string) => CreateEmbed::default().description(string.clone()),
-            CommandResponse::Embed(command_embed) => CreateEmbed::from(command_embed.clone()),
-            _ => CreateEmbed::default(),
+            CommandResponse::FollowUps {
+                response,
+                followups,
+            } => CommandResponse::FollowUps {
+                response: Box::new(response.ephemeral()),
+                followups,
+            },
+            CommandResponse::Ephemeral(response) => CommandResponse::Ephemeral(response),
+            response => CommandResponse::Ephemeral(Box::new(response)),
         }
     }
 
-    pub fn to_string(&self) -> String {
+    /// Add a message sent after the response
+    pub fn followup(self, followup: CreateInteractionResponseFollowup) -> Self {
         match self {
-            CommandResponse::String(string) => string.clone(),
-            CommandResponse::Embed(embed) => embed.description.clone().unwrap(),
-            _ => "".to_string(),
+            CommandResponse::FollowUps {
+                response,
+                mut followups,
+            } => {
+                followups.push(followup);
+
+                CommandResponse::FollowUps {
+                    response,
+                    followups,
+                }
+            }
+            response => CommandResponse::FollowUps {
+                response: Box::new(response),
+                followups: vec![followup],
+            },
         }
     }
-}
 
-impl PartialEq for CommandResponse {
-    fn eq(&self, other: &Self) -> bool {
+    /// Split the response from its follow-up messages
+    pub fn split_followups(self) -> (Self, Vec<CreateInteractionResponseFollowup>) {
         match self {
-            CommandResponse::String(string) => match other {
-                CommandResponse::String(other_string) => string == other_string,
-                _ => false,
-            },
-            CommandResponse::Embed(embed) => match other {
-                CommandResponse::Embed(other_embed) => {
-                    Some(embed.title.clone()) == Some(other_embed.title.clone())
+            CommandResponse::FollowUps {
+                response,
+                followups,
+            } => (*response, followups),
+            response => (response, vec![]),
+        }
+    }
+
+    pub fn is_ephemeral(&self) -> bool {
+        match self {
+            CommandResponse::Ephemeral(_) => true,
+            CommandResponse::FollowUps { response, .. } => response.is_ephemeral(),
+            _ => false,
+        }
+    }
+
+    /// Response as the first response of the interaction, empty for `Message`, `Paginated`, `Modal` and `None`
+    pub fn to_response_message(&self) -> CreateInteractionResponseMessage {
+        let message = CreateInteractionResponseMessage::new();
+
+        match self {
+            CommandResponse::String(string) => message.content(string.clone()),
+            CommandResponse::Embed(embed) => message.embed((**embed).clone().into()),
+            CommandResponse::Embeds(embeds) => message.embeds(embeds.clone()),
+            CommandResponse::Attachments { content, files } => {
+                let message = message.add_files(files.clone());
+
+                match content {
+                    Some(content) => message.content(content.clone()),
+                    None => message,
                 }
-                _ => false,
-            },
-            _ => match other {
-                CommandResponse::None => true,
-                _ => false,
-            },
+            }
+            CommandResponse::Components {
+                content,
+                components,
+            } => message
+                .content(content.clone())
+                .components(components.clone()),
+            CommandResponse::Ephemeral(response) => response.to_response_message().ephemeral(true),
+            CommandResponse::FollowUps { response, .. } => response.to_response_message(),
+            _ => message,
         }
     }
-    fn ne(&self, other: &Self) -> bool {
+
+    /// Response as the edit of a deferred interaction, empty for `Paginated`, `Ephemeral`, `Modal` and `None`
+    pub fn to_edit_response(&self) -> EditInteractionResponse {
+        let edit = EditInteractionResponse::new();
+
         match self {
-            CommandResponse::String(string) => match other {
-                CommandResponse::String(other_string) => string != other_string,
-                _ => true,
-            },
-            CommandResponse::Embed(embed) => match other {
-                CommandResponse::Embed(other_embed) => {
-                    Some(embed.title.clone()) != Some(other_embed.title.clone())
+            CommandResponse::String(string) => edit.content(string.clone()),
+            CommandResponse::Embed(embed) => edit.embed((**embed).clone().into()),
+            CommandResponse::Embeds(embeds) => edit.embeds(embeds.clone()),
+            CommandResponse::Attachments { content, files } => {
+                let edit = files
+                    .iter()
+                    .cloned()
+                    .fold(edit, |edit, file| edit.new_attachment(file));
+
+                match content {
+                    Some(content) => edit.content(content.clone()),
+                    None => edit,
                 }
-                _ => true,
-            },
-            _ => match other {
-                CommandResponse::None => false,
-                _ => true,
-            },
+            }
+            CommandResponse::Components {
+                content,
+                components,
+            } => edit.content(content.clone()).components(components.clone()),
+            CommandResponse::Message(message) => message.clone(),
+            CommandResponse::FollowUps { response, .. } => response.to_edit_response(),
+            _ => edit,
+        }
+    }
+
+    /// Response as a follow-up message, empty for `Message`, `Paginated`, `Modal` and `None`
+    pub fn to_followup(&self) -> CreateInteractionResponseFollowup {
+        let followup = CreateInteractionResponseFollowup::new();
+
+        match self {
+            CommandResponse::String(string) => followup.content(string.clone()),
+            CommandResponse::Embed(embed) => followup.embed((**embed).clone().into()),
+            CommandResponse::Embeds(embeds) => followup.embeds(embeds.clone()),
+            CommandResponse::Attachments { content, files } => {
+                let followup = followup.add_files(files.clone());
+
+                match content {
+                    Some(content) => followup.content(content.clone()),
+                    None => followup,
+                }
+            }
+            CommandResponse::Components {
+                content,
+                components,
+            } => followup
+                .content(content.clone())
+                .components(components.clone()),
+            CommandResponse::Ephemeral(response) => response.to_followup().ephemeral(true),
+            CommandResponse::FollowUps { response, .. } => response.to_followup(),
+            _ => followup,
+        }
+    }
+
+    pub fn to_embed(&self) -> CreateEmbed {
+        match self {
+            CommandResponse::String(string) => CreateEmbed::default().description(string.clone()),
+            CommandResponse::Embed(command_embed) => CreateEmbed::from((**command_embed).clone()),
+            _ => CreateEmbed::default(),
+        }
+    }
+}
+
+/// Builders don't implement `PartialEq`, they're compared by their payload
+fn same_payload<T: Serialize>(a: &T, b: &T) -> bool {
+    match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+impl PartialEq for CommandResponse {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CommandResponse::String(string), CommandResponse::String(other_string)) => {
+                string == other_string
+            }
+            (CommandResponse::Embed(embed), CommandResponse::Embed(other_embed)) => {
+                embed.title == other_embed.title && embed.description == other_embed.description
+            }
+            (CommandResponse::Embeds(embeds), CommandResponse::Embeds(other_embeds)) => {
+                same_payload(embeds, other_embeds)
+            }
+            (
+                CommandResponse::Attachments { content, files },
+                CommandResponse::Attachments {
+                    content: other_content,
+                    files: other_files,
+                },
+            ) => {
+                content == other_content
+                    && files.len() == other_files.len()
+                    && files.iter().zip(other_files).all(|(file, other_file)| {
+                        file.filename == other_file.filename && file.data == other_file.data
+                    })
+            }
+            (
+                CommandResponse::Components {
+                    content,
+                    components,
+                },
+                CommandResponse::Components {
+                    content: other_content,
+                    components: other_components,
+                },
+            ) => content == other_content && same_payload(components, other_components),
+            (CommandResponse::Message(message), CommandResponse::Message(other_message)) => {
+                same_payload(message, other_message)
+            }
+            (
+                CommandResponse::Paginated(paginator),
+                CommandResponse::Paginated(other_paginator),
+            ) => Arc::ptr_eq(&paginator.source, &other_paginator.source),
+            (CommandResponse::Ephemeral(response), CommandResponse::Ephemeral(other_response)) => {
+                response == other_response
+            }
+            (CommandResponse::Modal(modal), CommandResponse::Modal(other_modal)) => {
+                same_payload(modal, other_modal)
+            }
+            (
+                CommandResponse::FollowUps {
+                    response,
+                    followups,
+                },
+                CommandResponse::FollowUps {
+                    response: other_response,
+                    followups: other_followups,
+                },
+            ) => response == other_response && same_payload(followups, other_followups),
+            (CommandResponse::None, CommandResponse::None) => true,
+            _ => false,
         }
     }
 }
@@ -94,9 +323,35 @@ impl std::fmt::Display for CommandResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             CommandResponse::String(string) => write!(f, "{}", string),
-            CommandResponse::Embed(embed) => write!(f, "{}", embed.description.clone().unwrap()),
+            CommandResponse::Embed(embed) => match (&embed.title, &embed.description) {
+                (_, Some(description)) => write!(f, "{}", description),
+                (Some(title), None) => write!(f, "{}", title),
+                (None, None) => write!(f, "Embed"),
+            },
+            CommandResponse::Embeds(embeds) => write!(f, "Embeds ({})", embeds.len()),
+            CommandResponse::Attachments { files, .. } => write!(
+                f,
+                "Attachments ({})",
+                files
+                    .iter()
+                    .map(|file| file.filename.clone())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            CommandResponse::Components { content, .. } => {
+                write!(f, "{} (with components)", content)
+            }
             CommandResponse::Message(_) => write!(f, "Message"),
-            _ => write!(f, "None"),
+            CommandResponse::Paginated(paginator) => {
+                write!(f, "Paginated ({} pages)", paginator.source.pages())
+            }
+            CommandResponse::Ephemeral(response) => write!(f, "{} (ephemeral)", response),
+            CommandResponse::Modal(_) => write!(f, "Modal"),
+            CommandResponse::FollowUps {
+                response,
+                followups,
+            } => write!(f, "{} (+{} follow-ups)", response, followups.len()),
+            CommandResponse::None => write!(f, "None"),
         }
     }
 }
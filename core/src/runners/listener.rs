@@ -2,13 +2,49 @@ use dyn_clone::DynClone;
 use serenity::async_trait;
 use std::any::Any;
 
+use crate::{
+    arguments::{ArgumentsLevel, FromArguments},
+    errors::BostilResult,
+};
+
+/// ListenerResult is a type of result (ok or error) that the listener can return
+pub type ListenerResult = BostilResult<()>;
+
 #[async_trait]
 pub trait ListenerRunnerFn: DynClone {
-    async fn run<'a>(&self, arguments: &Vec<Box<dyn Any + Send + Sync>>) -> ();
+    async fn run<'a>(&self, arguments: &[Box<dyn Any + Send + Sync>]) -> ListenerResult;
+    /// Arguments that the runner requires to be provided
+    fn arguments(&self) -> Vec<ArgumentsLevel> {
+        vec![]
+    }
 }
 
 dyn_clone::clone_trait_object!(ListenerRunnerFn);
 
+/// Function that will be executed when the listener is triggered, receiving its arguments already extracted
+#[async_trait]
+pub trait TypedListenerRunnerFn: DynClone {
+    type Arguments: FromArguments + Send;
+
+    async fn run_typed<'a>(&self, arguments: Self::Arguments) -> ListenerResult;
+}
+
+#[async_trait]
+impl<T> ListenerRunnerFn for T
+where
+    T: TypedListenerRunnerFn + Send + Sync,
+{
+    async fn run<'a>(&self, arguments: &[Box<dyn Any + Send + Sync>]) -> ListenerResult {
+        let arguments = T::Arguments::from_arguments(arguments)?;
+
+        self.run_typed(arguments).await
+    }
+
+    fn arguments(&self) -> Vec<ArgumentsLevel> {
+        T::Arguments::levels()
+    }
+}
+
 impl std::fmt::Debug for dyn ListenerRunnerFn {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "<RunnerFn>")
@@ -1,9 +1,16 @@
+mod autocomplete;
 mod command;
 mod listener;
 
+#[allow(clippy::module_inception)]
 pub mod runners {
+    pub use super::autocomplete::AutocompleteFn;
+    pub use super::autocomplete::AutocompleteProviderFn;
     pub use super::command::CommandResponse;
     pub use super::command::CommandResult;
     pub use super::command::CommandRunnerFn;
+    pub use super::command::TypedCommandRunnerFn;
+    pub use super::listener::ListenerResult;
     pub use super::listener::ListenerRunnerFn;
+    pub use super::listener::TypedListenerRunnerFn;
 }
@@ -0,0 +1,711 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    panic::AssertUnwindSafe,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, OnceLock,
+    },
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use serenity::{
+    async_trait,
+    client::Context,
+    futures::{future::join_all, FutureExt},
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    dispatchers::panic_message,
+    errors::{BostilError, BostilResult},
+};
+
+/// Shared scheduler of the background jobs
+pub static SCHEDULER: Lazy<Scheduler> = Lazy::new(Scheduler::new);
+
+/// Runner of a job, called with the context of the client and the arguments it was scheduled with
+#[async_trait]
+pub trait JobRunnerFn: Send + Sync {
+    async fn run(&self, ctx: &Context, arguments: &Value) -> BostilResult<()>;
+}
+
+#[async_trait]
+impl<F, Fut> JobRunnerFn for F
+where
+    F: Fn(Context, Value) -> Fut + Send + Sync,
+    Fut: Future<Output = BostilResult<()>> + Send,
+{
+    async fn run(&self, ctx: &Context, arguments: &Value) -> BostilResult<()> {
+        (self)(ctx.clone(), arguments.clone()).await
+    }
+}
+
+/// Job that can be scheduled by its name, the persisted jobs are resumed by it after a restart
+pub struct Job {
+    pub name: String,
+    pub runner: Box<dyn JobRunnerFn>,
+}
+
+impl Job {
+    pub fn new(name: &str, runner: impl JobRunnerFn + 'static) -> Self {
+        Self {
+            name: name.to_string(),
+            runner: Box::new(runner),
+        }
+    }
+}
+
+/// Job registered by `register_job!`, collected by `Scheduler::store_registered`
+pub struct JobRegistration(pub fn() -> &'static Job);
+
+inventory::collect!(JobRegistration);
+
+/// Register a job static (`Lazy` or `lazy_static`) to be scheduled by its name
+#[macro_export]
+macro_rules! register_job {
+    ($job:path) => {
+        $crate::__private::inventory::submit! {
+            $crate::scheduler::JobRegistration(|| &*$job)
+        }
+    };
+}
+
+/// When a scheduled job runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    /// Once, at the given time (right away when it already passed)
+    Once(SystemTime),
+    /// Every period, the first run is one period after it's scheduled
+    Interval(Duration),
+    /// On the times matched by the cron expression, in UTC (eg.: `0 0 4 * * *` every day at 4 AM)
+    Cron(String),
+}
+
+impl Schedule {
+    /// Once, after the delay
+    pub fn after(delay: Duration) -> Self {
+        Schedule::Once(SystemTime::now() + delay)
+    }
+
+    /// On the times matched by the cron expression (`sec min hour day month weekday [year]`)
+    pub fn cron(expression: &str) -> BostilResult<Self> {
+        cron::Schedule::from_str(expression).map_err(|why| {
+            BostilError::validation(format!("Invalid cron expression {}: {}", expression, why))
+        })?;
+
+        Ok(Schedule::Cron(expression.to_string()))
+    }
+
+    /// Name of the kind of the schedule, used to persist it
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Schedule::Once(_) => "once",
+            Schedule::Interval(_) => "interval",
+            Schedule::Cron(_) => "cron",
+        }
+    }
+
+    /// Period (in seconds) or cron expression of the schedule, empty for `Once`
+    pub fn expression(&self) -> String {
+        match self {
+            Schedule::Once(_) => String::new(),
+            Schedule::Interval(period) => period.as_secs().to_string(),
+            Schedule::Cron(expression) => expression.clone(),
+        }
+    }
+
+    /// Schedule persisted as `kind` and `expression`, `Once` runs at `next_run`
+    pub fn from_parts(kind: &str, expression: &str, next_run: SystemTime) -> Option<Self> {
+        match kind {
+            "once" => Some(Schedule::Once(next_run)),
+            "interval" => expression
+                .parse()
+                .ok()
+                .map(|seconds| Schedule::Interval(Duration::from_secs(seconds))),
+            "cron" => Schedule::cron(expression).ok(),
+            _ => None,
+        }
+    }
+
+    /// First run of the schedule
+    pub fn first_run(&self, now: SystemTime) -> Option<SystemTime> {
+        match self {
+            Schedule::Once(at) => Some(*at),
+            _ => self.next_run(now),
+        }
+    }
+
+    /// Run after the one at `now`, `None` when it doesn't run anymore
+    pub fn next_run(&self, now: SystemTime) -> Option<SystemTime> {
+        match self {
+            Schedule::Once(_) => None,
+            Schedule::Interval(period) => Some(now + *period),
+            Schedule::Cron(expression) => cron::Schedule::from_str(expression)
+                .ok()?
+                .after(&DateTime::<Utc>::from(now))
+                .next()
+                .map(SystemTime::from),
+        }
+    }
+}
+
+/// Pending job, as persisted on the store
+#[derive(Debug, Clone)]
+pub struct JobRecord {
+    /// The identifier of the record on the store, `None` before it's saved
+    pub id: Option<i64>,
+    /// The name of the `Job`
+    pub job: String,
+    pub schedule: Schedule,
+    /// The arguments given to the runner of the job
+    pub arguments: Value,
+    pub next_run: SystemTime,
+}
+
+/// Persistence of the pending jobs (eg.: a database table)
+pub trait JobStore: Send + Sync {
+    /// Insert or update (when it has an id) the record, returning its id
+    fn save(&self, record: &JobRecord) -> BostilResult<i64>;
+    /// All the stored records
+    fn load(&self) -> BostilResult<Vec<JobRecord>>;
+    fn delete(&self, id: i64) -> BostilResult<()>;
+}
+
+/// Identifier of a scheduled job, used to cancel it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JobId(u64);
+
+struct PendingJob {
+    job: &'static Job,
+    record: JobRecord,
+}
+
+/// Scheduler of the registered jobs, runs the due ones on every tick (see `Scheduler::start`)
+///
+/// Jobs scheduled with `Scheduler::schedule` are persisted and resumed after a restart,
+/// the ones scheduled with `Scheduler::schedule_transient` (eg.: recurring jobs scheduled on startup) aren't
+pub struct Scheduler {
+    jobs: Mutex<HashMap<String, &'static Job>>,
+    pending: Mutex<HashMap<JobId, PendingJob>>,
+    store: OnceLock<Arc<dyn JobStore>>,
+    next_id: AtomicU64,
+    started: AtomicBool,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+            store: OnceLock::new(),
+            next_id: AtomicU64::new(0),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    fn jobs(&self) -> MutexGuard<'_, HashMap<String, &'static Job>> {
+        match self.jobs.lock() {
+            Ok(jobs) => jobs,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn pending(&self) -> MutexGuard<'_, HashMap<JobId, PendingJob>> {
+        match self.pending.lock() {
+            Ok(pending) => pending,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Register a job, it's scheduled (and resumed) by its name
+    pub fn register(&self, job: &'static Job) {
+        self.jobs().insert(job.name.clone(), job);
+    }
+
+    /// Register all the jobs registered through `register_job!`
+    pub fn store_registered(&self) {
+        for registration in inventory::iter::<JobRegistration> {
+            self.register((registration.0)());
+        }
+    }
+
+    /// Set the store of the persisted jobs (only the first store is kept)
+    pub fn set_store(&self, store: impl JobStore + 'static) {
+        if self.store.set(Arc::new(store)).is_err() {
+            warn!("Job store already set");
+        }
+    }
+
+    fn find(&self, name: &str) -> BostilResult<&'static Job> {
+        self.jobs()
+            .get(name)
+            .copied()
+            .ok_or_else(|| BostilError::validation(format!("Job {} not registered", name)))
+    }
+
+    fn enqueue(&self, job: &'static Job, record: JobRecord) -> JobId {
+        let id = JobId(self.next_id.fetch_add(1, Ordering::SeqCst));
+
+        self.pending().insert(id, PendingJob { job, record });
+
+        id
+    }
+
+    /// Schedule a registered job, persisted to be resumed after a restart
+    pub fn schedule(
+        &self,
+        name: &str,
+        schedule: Schedule,
+        arguments: Value,
+    ) -> BostilResult<JobId> {
+        let (job, mut record) = self.record(name, schedule, arguments)?;
+
+        match self.store.get() {
+            Some(store) => record.id = Some(store.save(&record)?),
+            None => warn!(
+                "Job {} scheduled without a store, it won't be resumed",
+                name
+            ),
+        }
+
+        Ok(self.enqueue(job, record))
+    }
+
+    /// Schedule a registered job only in memory, it's lost on a restart
+    pub fn schedule_transient(
+        &self,
+        name: &str,
+        schedule: Schedule,
+        arguments: Value,
+    ) -> BostilResult<JobId> {
+        let (job, record) = self.record(name, schedule, arguments)?;
+
+        Ok(self.enqueue(job, record))
+    }
+
+    fn record(
+        &self,
+        name: &str,
+        schedule: Schedule,
+        arguments: Value,
+    ) -> BostilResult<(&'static Job, JobRecord)> {
+        let job = self.find(name)?;
+        let next_run = schedule.first_run(SystemTime::now()).ok_or_else(|| {
+            BostilError::validation(format!("Schedule of job {} never runs", name))
+        })?;
+
+        let record = JobRecord {
+            id: None,
+            job: job.name.clone(),
+            schedule,
+            arguments,
+            next_run,
+        };
+
+        Ok((job, record))
+    }
+
+    /// Cancel a scheduled job, removing it from the store
+    pub fn cancel(&self, id: JobId) -> BostilResult<()> {
+        let Some(pending) = self.pending().remove(&id) else {
+            return Err(BostilError::validation(format!(
+                "Job {:?} not scheduled",
+                id
+            )));
+        };
+
+        self.forget(&pending.record)
+    }
+
+    /// Cancel the scheduled jobs with the name and arguments (eg.: the timeout of a poll ended early)
+    ///
+    /// Returns the number of cancelled jobs
+    pub fn cancel_matching(&self, name: &str, arguments: &Value) -> BostilResult<usize> {
+        let records = {
+            let mut pending = self.pending();
+            let ids = pending
+                .iter()
+                .filter(|(_, pending)| {
+                    pending.record.job == name && pending.record.arguments == *arguments
+                })
+                .map(|(id, _)| *id)
+                .collect::<Vec<JobId>>();
+
+            ids.iter()
+                .filter_map(|id| pending.remove(id))
+                .map(|pending| pending.record)
+                .collect::<Vec<JobRecord>>()
+        };
+
+        for record in records.iter() {
+            self.forget(record)?;
+        }
+
+        Ok(records.len())
+    }
+
+    /// Next run of a scheduled job, `None` when it isn't scheduled anymore
+    pub fn next_run(&self, id: JobId) -> Option<SystemTime> {
+        self.pending()
+            .get(&id)
+            .map(|pending| pending.record.next_run)
+    }
+
+    /// Resume the stored jobs, the ones of unknown jobs are removed from the store
+    ///
+    /// Returns the number of resumed jobs
+    pub fn restore(&self) -> BostilResult<usize> {
+        let Some(store) = self.store.get() else {
+            return Ok(0);
+        };
+
+        let mut restored = 0;
+
+        for record in store.load()? {
+            match self.find(&record.job) {
+                Ok(job) => {
+                    debug!(
+                        "Job {} resumed, next run at {:?}",
+                        record.job, record.next_run
+                    );
+
+                    self.enqueue(job, record);
+                    restored += 1;
+                }
+                Err(why) => {
+                    warn!("Cannot resume job {}: {}", record.job, why);
+                    self.forget(&record)?;
+                }
+            }
+        }
+
+        Ok(restored)
+    }
+
+    /// Run the due jobs concurrently, rescheduling the recurring ones and forgetting the others
+    ///
+    /// Returns the number of jobs run
+    pub async fn tick(&self, ctx: &Context) -> usize {
+        let now = SystemTime::now();
+
+        // the due jobs are rescheduled with the lock held, the store and the runners are called after releasing it
+        let due = {
+            let mut pending = self.pending();
+            let ids = pending
+                .iter()
+                .filter(|(_, pending)| pending.record.next_run <= now)
+                .map(|(id, _)| *id)
+                .collect::<Vec<JobId>>();
+
+            ids.into_iter()
+                .filter_map(|id| {
+                    let pending_job = pending.get_mut(&id)?;
+                    let job = pending_job.job;
+                    let ran = pending_job.record.clone();
+
+                    match ran.schedule.next_run(now) {
+                        Some(next_run) => {
+                            pending_job.record.next_run = next_run;
+                            Some((job, ran, Some(pending_job.record.clone())))
+                        }
+                        None => {
+                            pending.remove(&id);
+                            Some((job, ran, None))
+                        }
+                    }
+                })
+                .collect::<Vec<(&'static Job, JobRecord, Option<JobRecord>)>>()
+        };
+
+        for (_, ran, rescheduled) in due.iter() {
+            let stored = match rescheduled {
+                Some(record) => self.save(record),
+                None => self.forget(ran),
+            };
+
+            if let Err(why) = stored {
+                error!("Cannot store job {}: {}", ran.job, why);
+            }
+        }
+
+        join_all(
+            due.iter()
+                .map(|(job, ran, _)| Self::run_job(job, ctx, &ran.arguments)),
+        )
+        .await;
+
+        due.len()
+    }
+
+    async fn run_job(job: &Job, ctx: &Context, arguments: &Value) {
+        debug!("Running job {}", job.name);
+
+        match AssertUnwindSafe(job.runner.run(ctx, arguments))
+            .catch_unwind()
+            .await
+        {
+            Ok(Ok(())) => {}
+            Ok(Err(why)) => error!("Job {} failed: {}", job.name, why),
+            Err(why) => error!("Job {} panicked: {}", job.name, panic_message(&why)),
+        }
+    }
+
+    /// Resume the stored jobs and start the background task ticking on every period (only the first call starts it)
+    ///
+    /// Each tick runs on its own task, so a slow job doesn't delay the next ones
+    pub fn start(&'static self, ctx: &Context, period: Duration) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        match self.restore() {
+            Ok(restored) => info!("Resumed {} scheduled jobs", restored),
+            Err(why) => error!("Cannot resume the scheduled jobs: {}", why),
+        }
+
+        let ctx = ctx.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            loop {
+                interval.tick().await;
+
+                let ctx = ctx.clone();
+                tokio::spawn(async move { self.tick(&ctx).await });
+            }
+        });
+
+        info!("Job scheduler started, every {:?}", period);
+    }
+
+    fn save(&self, record: &JobRecord) -> BostilResult<()> {
+        match (record.id, self.store.get()) {
+            (Some(_), Some(store)) => store.save(record).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    fn forget(&self, record: &JobRecord) -> BostilResult<()> {
+        match (record.id, self.store.get()) {
+            (Some(id), Some(store)) => store.delete(id),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        str::FromStr,
+        time::{Duration, SystemTime},
+    };
+
+    use chrono::{DateTime, Utc};
+    use serde_json::Value;
+
+    use super::Schedule;
+    use crate::errors::BostilError;
+
+    fn utc(date: &str) -> SystemTime {
+        SystemTime::from(DateTime::<Utc>::from_str(date).unwrap())
+    }
+
+    #[test]
+    fn once_runs_only_at_its_time() {
+        let at = utc("2026-10-18T10:00:00Z");
+        let schedule = Schedule::Once(at);
+
+        assert_eq!(schedule.first_run(utc("2026-10-18T09:00:00Z")), Some(at));
+        assert_eq!(schedule.next_run(at), None);
+    }
+
+    #[test]
+    fn interval_runs_one_period_after_the_last_run() {
+        let now = utc("2026-10-18T10:00:00Z");
+        let schedule = Schedule::Interval(Duration::from_secs(60));
+
+        assert_eq!(schedule.first_run(now), Some(utc("2026-10-18T10:01:00Z")));
+        assert_eq!(schedule.next_run(now), Some(utc("2026-10-18T10:01:00Z")));
+    }
+
+    #[test]
+    fn cron_runs_on_the_next_matching_time() {
+        let schedule = Schedule::cron("0 0 4 * * *").unwrap();
+
+        assert_eq!(
+            schedule.next_run(utc("2026-10-18T10:00:00Z")),
+            Some(utc("2026-10-19T04:00:00Z"))
+        );
+        assert_eq!(
+            schedule.next_run(utc("2026-10-18T03:59:59Z")),
+            Some(utc("2026-10-18T04:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn invalid_cron_expression() {
+        assert!(matches!(
+            Schedule::cron("every day at 4"),
+            Err(BostilError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn schedule_is_restored_from_its_parts() {
+        let next_run = utc("2026-10-18T10:00:00Z");
+        let schedules = [
+            Schedule::Once(next_run),
+            Schedule::Interval(Duration::from_secs(86400)),
+            Schedule::cron("0 0 4 * * *").unwrap(),
+        ];
+
+        for schedule in schedules {
+            assert_eq!(
+                Schedule::from_parts(schedule.kind(), &schedule.expression(), next_run),
+                Some(schedule)
+            );
+        }
+
+        assert_eq!(Schedule::from_parts("weekly", "", next_run), None);
+        assert_eq!(Schedule::from_parts("cron", "* *", next_run), None);
+    }
+
+    #[test]
+    fn unregistered_job_is_not_scheduled() {
+        let scheduler = super::Scheduler::new();
+
+        assert!(matches!(
+            scheduler.schedule_transient(
+                "unknown",
+                Schedule::Interval(Duration::from_secs(60)),
+                Value::Null
+            ),
+            Err(BostilError::Validation(_))
+        ));
+    }
+
+    #[cfg(feature = "testing")]
+    mod tick {
+        use std::{
+            collections::HashMap,
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc, Mutex,
+            },
+            time::{Duration, SystemTime},
+        };
+
+        use once_cell::sync::Lazy;
+        use serde_json::{json, Value};
+        use serenity::client::Context;
+
+        use crate::{
+            errors::BostilResult,
+            scheduler::{Job, JobRecord, JobStore, Schedule, Scheduler},
+            testing::TestHarness,
+        };
+
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+        static COUNTED_JOB: Lazy<Job> = Lazy::new(|| {
+            Job::new("counted", |_ctx: Context, _arguments: Value| async {
+                RUNS.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+        });
+
+        /// Store of the records in memory, shared with the test to check what was persisted
+        #[derive(Clone, Default)]
+        struct MemoryStore {
+            records: Arc<Mutex<HashMap<i64, JobRecord>>>,
+        }
+
+        impl JobStore for MemoryStore {
+            fn save(&self, record: &JobRecord) -> BostilResult<i64> {
+                let mut records = self.records.lock().unwrap();
+                let id = record.id.unwrap_or(records.len() as i64 + 1);
+
+                records.insert(
+                    id,
+                    JobRecord {
+                        id: Some(id),
+                        ..record.clone()
+                    },
+                );
+
+                Ok(id)
+            }
+
+            fn load(&self) -> BostilResult<Vec<JobRecord>> {
+                Ok(self.records.lock().unwrap().values().cloned().collect())
+            }
+
+            fn delete(&self, id: i64) -> BostilResult<()> {
+                self.records.lock().unwrap().remove(&id);
+
+                Ok(())
+            }
+        }
+
+        #[tokio::test]
+        async fn reschedules_the_recurring_jobs_and_forgets_the_others() {
+            let harness = TestHarness::new().await;
+            let store = MemoryStore::default();
+            let scheduler = Scheduler::new();
+            scheduler.set_store(store.clone());
+            scheduler.register(&COUNTED_JOB);
+
+            let once = scheduler
+                .schedule(
+                    "counted",
+                    Schedule::Once(SystemTime::now() - Duration::from_secs(1)),
+                    json!({ "once": true }),
+                )
+                .unwrap();
+            let interval = scheduler
+                .schedule(
+                    "counted",
+                    Schedule::Interval(Duration::from_secs(60)),
+                    json!({ "once": false }),
+                )
+                .unwrap();
+
+            // only the due job runs
+            assert_eq!(scheduler.tick(&harness.context).await, 1);
+            assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+            assert_eq!(scheduler.next_run(once), None);
+            assert_eq!(store.records.lock().unwrap().len(), 1);
+
+            // the interval job is due on its next run, so it's rescheduled after running
+            let next_run = scheduler.next_run(interval).unwrap();
+            scheduler
+                .pending()
+                .get_mut(&interval)
+                .unwrap()
+                .record
+                .next_run = SystemTime::now();
+
+            assert_eq!(scheduler.tick(&harness.context).await, 1);
+            assert_eq!(RUNS.load(Ordering::SeqCst), 2);
+
+            let rescheduled = scheduler.next_run(interval).unwrap();
+            let stored = store.load().unwrap();
+
+            assert!(rescheduled >= next_run);
+            assert_eq!(stored.len(), 1);
+            assert_eq!(stored[0].next_run, rescheduled);
+        }
+    }
+}
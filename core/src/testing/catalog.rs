@@ -0,0 +1,576 @@
+//! Consistency of the locale files (eg.: `public/locales/en-US.yml`) against the keys used by the code
+//!
+//! The used keys are the string literals shaped as a key of the locale files (`t!("commands.help.title")`,
+//! `BostilError::user_facing("commands.cooldown")`), and the prefixes of the formatted ones
+//! (`format!("commands.help.categories.{}", ..)`).
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde_yaml::Value;
+
+/// Locale file flattened by key (eg.: `commands.help.title`)
+#[derive(Debug, Clone)]
+pub struct LocaleCatalog {
+    /// Name of the locale file (eg.: `pt-BR`)
+    pub locale: String,
+    pub messages: BTreeMap<String, String>,
+}
+
+impl LocaleCatalog {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let locale = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        let value = serde_yaml::from_str::<Value>(&fs::read_to_string(path)?)
+            .map_err(|why| invalid_data(format!("{}: {}", path.display(), why)))?;
+
+        let mut messages = BTreeMap::new();
+        flatten(None, &value, &mut messages);
+
+        Ok(Self { locale, messages })
+    }
+
+    /// Every `.yml` file of the directory, sorted by locale
+    pub fn load_dir(dir: &Path) -> io::Result<Vec<Self>> {
+        let mut catalogs = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<PathBuf>>>()?
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|extension| extension == "yml"))
+            .map(|path| Self::load(&path))
+            .collect::<io::Result<Vec<Self>>>()?;
+
+        catalogs.sort_by(|a, b| a.locale.cmp(&b.locale));
+
+        Ok(catalogs)
+    }
+
+    /// Names of the placeholders of the message (`%{name}`)
+    pub fn placeholders(message: &str) -> BTreeSet<String> {
+        message
+            .split("%{")
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name.to_string()))
+            .collect()
+    }
+
+    fn has_prefix(&self, prefix: &str) -> bool {
+        self.messages.keys().any(|key| key.starts_with(prefix))
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn flatten(prefix: Option<String>, value: &Value, messages: &mut BTreeMap<String, String>) {
+    let Value::Mapping(mapping) = value else {
+        if let Some(key) = prefix {
+            let message = match value {
+                Value::String(message) => message.clone(),
+                other => serde_yaml::to_string(other)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            };
+
+            messages.insert(key, message);
+        }
+
+        return;
+    };
+
+    for (name, value) in mapping {
+        let name = match name {
+            Value::String(name) => name.clone(),
+            other => serde_yaml::to_string(other)
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        };
+
+        let key = match &prefix {
+            Some(prefix) => format!("{}.{}", prefix, name),
+            None => name,
+        };
+
+        flatten(Some(key), value, messages);
+    }
+}
+
+/// Key used by the code, with where it's used (`<file>:<line>`)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UsedKey {
+    Literal {
+        key: String,
+        location: String,
+    },
+    /// Prefix of a formatted key, at least one key must start with it
+    Prefix {
+        prefix: String,
+        location: String,
+    },
+}
+
+impl UsedKey {
+    /// Keys used by the Rust files of the directories (recursively)
+    pub fn scan(dirs: &[PathBuf], roots: &BTreeSet<String>) -> io::Result<Vec<Self>> {
+        let mut used = vec![];
+
+        for dir in dirs {
+            for path in rust_files(dir)? {
+                let source = fs::read_to_string(&path)?;
+
+                for (literal, line, is_formatted) in string_literals(&source) {
+                    let location = format!("{}:{}", path.display(), line);
+
+                    if let Some(key) = Self::from_literal(&literal, is_formatted, roots, location) {
+                        used.push(key);
+                    }
+                }
+            }
+        }
+
+        used.sort();
+        used.dedup();
+
+        Ok(used)
+    }
+
+    fn from_literal(
+        literal: &str,
+        is_formatted: bool,
+        roots: &BTreeSet<String>,
+        location: String,
+    ) -> Option<Self> {
+        let (key, is_prefix) = match literal.split_once('{') {
+            Some((prefix, _)) if is_formatted && prefix.ends_with('.') => (prefix, true),
+            Some(_) => return None,
+            None => (literal, false),
+        };
+
+        let segments = key.trim_end_matches('.').split('.').collect::<Vec<&str>>();
+
+        let is_key = segments.len() > 1
+            && roots.contains(segments[0])
+            && segments.iter().all(|segment| {
+                !segment.is_empty()
+                    && segment
+                        .chars()
+                        .all(|char| char.is_ascii_alphanumeric() || char == '_' || char == '-')
+            });
+
+        match (is_key, is_prefix) {
+            (false, _) => None,
+            (true, true) => Some(UsedKey::Prefix {
+                prefix: key.to_string(),
+                location,
+            }),
+            (true, false) => Some(UsedKey::Literal {
+                key: key.to_string(),
+                location,
+            }),
+        }
+    }
+
+    fn matches(&self, key: &str) -> bool {
+        match self {
+            UsedKey::Literal { key: used, .. } => used == key,
+            UsedKey::Prefix { prefix, .. } => key.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+fn rust_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            files.extend(rust_files(&path)?);
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+const FORMAT_MACRO: [char; 8] = ['f', 'o', 'r', 'm', 'a', 't', '!', '('];
+
+fn is_identifier(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+/// Whether the literal starting at the index is the format string of a `format!`
+fn is_format_string(chars: &[char], index: usize) -> bool {
+    let mut preceding = index;
+    while preceding > 0 && chars[preceding - 1].is_whitespace() {
+        preceding -= 1;
+    }
+
+    chars[..preceding].ends_with(&FORMAT_MACRO)
+}
+
+/// Number of `#` of the raw string starting at the index (`r"..."`, `r#"..."#`, `br"..."`), `None` when it isn't one
+fn raw_string_hashes(chars: &[char], index: usize) -> Option<usize> {
+    if chars[index] != 'r' {
+        return None;
+    }
+
+    let prefix = match index.checked_sub(1).map(|preceding| chars[preceding]) {
+        Some('b') => index - 1,
+        _ => index,
+    };
+
+    if prefix > 0 && is_identifier(chars[prefix - 1]) {
+        return None;
+    }
+
+    let hashes = chars[index + 1..]
+        .iter()
+        .take_while(|char| **char == '#')
+        .count();
+
+    (chars.get(index + 1 + hashes) == Some(&'"')).then_some(hashes)
+}
+
+/// String literals of the source with their line, and whether they're the format string of a `format!`
+///
+/// The quotes inside comments (line and block ones, nested too) aren't literals,
+/// the content of the raw strings is kept as written
+fn string_literals(source: &str) -> Vec<(String, usize, bool)> {
+    let chars = source.chars().collect::<Vec<char>>();
+    let mut literals = vec![];
+    let mut line = 1;
+    let mut index = 0;
+
+    while index < chars.len() {
+        // raw strings end on the quote followed by their `#`, they have no escapes
+        if let Some(hashes) = raw_string_hashes(&chars, index) {
+            let closing = std::iter::once('"')
+                .chain(std::iter::repeat_n('#', hashes))
+                .collect::<Vec<char>>();
+            let start_line = line;
+            let is_formatted = is_format_string(&chars, index);
+            let mut literal = String::new();
+
+            // the content starts after the `r`, the `#` and the quote
+            index += hashes + 2;
+
+            while index < chars.len() && !chars[index..].starts_with(&closing) {
+                if chars[index] == '\n' {
+                    line += 1;
+                }
+
+                literal.push(chars[index]);
+                index += 1;
+            }
+
+            literals.push((literal, start_line, is_formatted));
+
+            index += closing.len();
+            continue;
+        }
+
+        match chars[index] {
+            '\n' => line += 1,
+            // line comments, a quote on them isn't a literal
+            '/' if chars.get(index + 1) == Some(&'/') => {
+                while index < chars.len() && chars[index] != '\n' {
+                    index += 1;
+                }
+                continue;
+            }
+            // block comments, they can be nested
+            '/' if chars.get(index + 1) == Some(&'*') => {
+                let mut depth = 0;
+
+                while index < chars.len() {
+                    match (chars[index], chars.get(index + 1)) {
+                        ('/', Some('*')) => {
+                            depth += 1;
+                            index += 2;
+                        }
+                        ('*', Some('/')) => {
+                            depth -= 1;
+                            index += 2;
+
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        (char, _) => {
+                            if char == '\n' {
+                                line += 1;
+                            }
+
+                            index += 1;
+                        }
+                    }
+                }
+                continue;
+            }
+            // the quote char literal
+            '\'' if chars.get(index + 1) == Some(&'"') => index += 2,
+            '"' => {
+                let start_line = line;
+                let mut literal = String::new();
+                let is_formatted = is_format_string(&chars, index);
+
+                index += 1;
+
+                while index < chars.len() && chars[index] != '"' {
+                    // the escaped char is kept, so an escaped quote doesn't end the literal
+                    if chars[index] == '\\' {
+                        literal.push(chars[index]);
+                        index += 1;
+                    }
+
+                    if let Some(char) = chars.get(index) {
+                        if *char == '\n' {
+                            line += 1;
+                        }
+
+                        literal.push(*char);
+                    }
+
+                    index += 1;
+                }
+
+                literals.push((literal, start_line, is_formatted));
+            }
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    literals
+}
+
+/// Key missing from a locale file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingKey {
+    pub locale: String,
+    pub key: String,
+    /// Where the key is used, `None` when it's only missing compared to the other locale files
+    pub location: Option<String>,
+}
+
+/// Key whose placeholders differ between the locale files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MismatchedPlaceholders {
+    pub key: String,
+    pub placeholders: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Missing keys and mismatched placeholders of the locale files, and their keys not used by the code
+#[derive(Debug, Clone, Default)]
+pub struct CatalogReport {
+    pub missing: Vec<MissingKey>,
+    pub mismatched: Vec<MismatchedPlaceholders>,
+    /// Keys not used by the code, by locale
+    pub orphaned: Vec<(String, String)>,
+}
+
+impl CatalogReport {
+    /// Check the locale files of the directory against the keys used by the Rust files of the source directories
+    pub fn check(locales_dir: &Path, source_dirs: &[PathBuf]) -> io::Result<Self> {
+        let catalogs = LocaleCatalog::load_dir(locales_dir)?;
+
+        if catalogs.is_empty() {
+            return Err(invalid_data(format!(
+                "No locale files on {}",
+                locales_dir.display()
+            )));
+        }
+
+        let roots = catalogs
+            .iter()
+            .flat_map(|catalog| catalog.messages.keys())
+            .filter_map(|key| key.split('.').next())
+            .map(String::from)
+            .collect::<BTreeSet<String>>();
+
+        let used = UsedKey::scan(source_dirs, &roots)?;
+
+        Ok(Self::compare(&catalogs, &used))
+    }
+
+    pub fn compare(catalogs: &[LocaleCatalog], used: &[UsedKey]) -> Self {
+        let mut report = Self::default();
+
+        let known_keys = catalogs
+            .iter()
+            .flat_map(|catalog| catalog.messages.keys().cloned())
+            .collect::<BTreeSet<String>>();
+
+        for catalog in catalogs {
+            for used_key in used {
+                let (missing, key, location) = match used_key {
+                    UsedKey::Literal { key, location } => {
+                        (!catalog.messages.contains_key(key), key, location)
+                    }
+                    UsedKey::Prefix { prefix, location } => {
+                        (!catalog.has_prefix(prefix), prefix, location)
+                    }
+                };
+
+                if missing {
+                    report.missing.push(MissingKey {
+                        locale: catalog.locale.clone(),
+                        key: key.clone(),
+                        location: Some(location.clone()),
+                    });
+                }
+            }
+
+            for key in &known_keys {
+                let is_reported = report
+                    .missing
+                    .iter()
+                    .any(|missing| missing.locale == catalog.locale && &missing.key == key);
+
+                if !catalog.messages.contains_key(key) && !is_reported {
+                    report.missing.push(MissingKey {
+                        locale: catalog.locale.clone(),
+                        key: key.clone(),
+                        location: None,
+                    });
+                }
+            }
+
+            report.orphaned.extend(
+                catalog
+                    .messages
+                    .keys()
+                    .filter(|key| !used.iter().any(|used_key| used_key.matches(key)))
+                    .map(|key| (catalog.locale.clone(), key.clone())),
+            );
+        }
+
+        for key in &known_keys {
+            let placeholders = catalogs
+                .iter()
+                .filter_map(|catalog| {
+                    catalog.messages.get(key).map(|message| {
+                        (catalog.locale.clone(), LocaleCatalog::placeholders(message))
+                    })
+                })
+                .collect::<BTreeMap<String, BTreeSet<String>>>();
+
+            let mut sets = placeholders.values();
+            let first = sets.next();
+
+            if sets.any(|set| Some(set) != first) {
+                report.mismatched.push(MismatchedPlaceholders {
+                    key: key.clone(),
+                    placeholders,
+                });
+            }
+        }
+
+        report
+    }
+
+    /// Whether every used key exists on every locale file with the same placeholders (orphaned keys are allowed)
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+impl fmt::Display for CatalogReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for missing in &self.missing {
+            match &missing.location {
+                Some(location) => writeln!(
+                    f,
+                    "missing: {} on {} (used at {})",
+                    missing.key, missing.locale, location
+                )?,
+                None => writeln!(
+                    f,
+                    "missing: {} on {} (present on other locales)",
+                    missing.key, missing.locale
+                )?,
+            }
+        }
+
+        for mismatched in &self.mismatched {
+            let placeholders = mismatched
+                .placeholders
+                .iter()
+                .map(|(locale, names)| {
+                    let names = names.iter().cloned().collect::<Vec<String>>().join(", ");
+
+                    format!("{} [{}]", locale, names)
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            writeln!(
+                f,
+                "mismatched placeholders: {} ({})",
+                mismatched.key, placeholders
+            )?;
+        }
+
+        for (locale, key) in &self.orphaned {
+            writeln!(f, "orphaned: {} on {}", key, locale)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::string_literals;
+
+    #[test]
+    fn comments_have_no_literals() {
+        let source = "// t!(\"example.ping\")\n/* t!(\"example.help\")\n /* \"nested\" */ \"still\" */\nt!(\"example.jingle\")";
+
+        assert_eq!(
+            string_literals(source),
+            vec![("example.jingle".to_string(), 4, false)]
+        );
+    }
+
+    #[test]
+    fn raw_strings_are_literals() {
+        let source = "t!(r\"example.ping\");\nformat!(r#\"example.help.{}\"#, \"a\");\nlet bytes = br\"\\d\";";
+
+        assert_eq!(
+            string_literals(source),
+            vec![
+                ("example.ping".to_string(), 1, false),
+                ("example.help.{}".to_string(), 2, true),
+                ("a".to_string(), 2, false),
+                ("\\d".to_string(), 3, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_string_quotes_do_not_end_it() {
+        let source = "let json = r#\"{ \"key\": \"example.ping\" }\"#;\nlet bar\"x\";";
+
+        assert_eq!(
+            string_literals(source),
+            vec![
+                ("{ \"key\": \"example.ping\" }".to_string(), 1, false),
+                ("x".to_string(), 2, false),
+            ]
+        );
+    }
+}
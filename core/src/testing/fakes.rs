@@ -0,0 +1,184 @@
+use serde_json::{json, Value};
+use serenity::all::{
+    ChannelId, CommandDataOption, ComponentInteraction, Guild, GuildId, Message, MessageId,
+    ModalInteraction, ModalInteractionData, User, UserId, VoiceState,
+};
+
+/// Fake user with the given id and name
+pub fn user(id: u64, name: &str) -> User {
+    let mut user = User::default();
+    user.id = UserId::new(id);
+    user.name = name.to_string();
+
+    user
+}
+
+/// Fake guild with the given id and name (no channels, members or voice states)
+pub fn guild(id: u64, name: &str) -> Guild {
+    let mut guild = Guild::default();
+    guild.id = GuildId::new(id);
+    guild.name = name.to_string();
+
+    guild
+}
+
+/// Fake voice state of the user connected to the channel, to be inserted on `Guild::voice_states`
+pub fn voice_state(guild_id: u64, user_id: u64, channel_id: u64) -> VoiceState {
+    serde_json::from_value(json!({
+        "guild_id": guild_id.to_string(),
+        "channel_id": channel_id.to_string(),
+        "user_id": user_id.to_string(),
+        "session_id": "session",
+        "deaf": false,
+        "mute": false,
+        "self_deaf": false,
+        "self_mute": false,
+        "self_video": false,
+        "suppress": false,
+        "request_to_speak_timestamp": null,
+    }))
+    .expect("Invalid fake voice state")
+}
+
+/// Fake chat message sent by the user on the channel
+pub fn message(id: u64, channel_id: u64, author: &User, content: &str) -> Message {
+    let mut message = Message::default();
+    message.id = MessageId::new(id);
+    message.channel_id = ChannelId::new(channel_id);
+    message.author = author.clone();
+    message.content = content.to_string();
+
+    message
+}
+
+fn option(name: &str, kind: u8, value: Value) -> CommandDataOption {
+    serde_json::from_value(json!({ "name": name, "type": kind, "value": value }))
+        .expect("Invalid fake command option")
+}
+
+/// Fake string option of a command
+pub fn string_option(name: &str, value: &str) -> CommandDataOption {
+    option(name, 3, json!(value))
+}
+
+/// Fake integer option of a command
+pub fn integer_option(name: &str, value: i64) -> CommandDataOption {
+    option(name, 4, json!(value))
+}
+
+/// Fake boolean option of a command
+pub fn boolean_option(name: &str, value: bool) -> CommandDataOption {
+    option(name, 5, json!(value))
+}
+
+/// Fake option being typed by the user, sent on autocomplete interactions
+pub fn focused_option(name: &str, value: &str) -> CommandDataOption {
+    serde_json::from_value(json!({ "name": name, "type": 3, "value": value, "focused": true }))
+        .expect("Invalid fake command option")
+}
+
+/// Fake subcommand option with its own options
+pub fn subcommand(name: &str, options: Vec<CommandDataOption>) -> CommandDataOption {
+    serde_json::from_value(json!({
+        "name": name,
+        "type": 1,
+        "options": options_json(options),
+    }))
+    .expect("Invalid fake subcommand")
+}
+
+/// Fake subcommand group option with its subcommands
+pub fn subcommand_group(name: &str, subcommands: Vec<CommandDataOption>) -> CommandDataOption {
+    serde_json::from_value(json!({
+        "name": name,
+        "type": 2,
+        "options": options_json(subcommands),
+    }))
+    .expect("Invalid fake subcommand group")
+}
+
+fn options_json(options: Vec<CommandDataOption>) -> Value {
+    serde_json::to_value(options).expect("Invalid fake command options")
+}
+
+fn modal_data_json(custom_id: &str, inputs: &[(&str, &str)]) -> Value {
+    json!({
+        "custom_id": custom_id,
+        "components": inputs
+            .iter()
+            .map(|(input_id, value)| json!({
+                "type": 1,
+                "components": [{ "type": 4, "custom_id": input_id, "value": value }],
+            }))
+            .collect::<Vec<Value>>(),
+    })
+}
+
+/// Fake data of a submitted modal, with the values of its text inputs (`(custom_id, value)`)
+pub fn modal_data(custom_id: &str, inputs: &[(&str, &str)]) -> ModalInteractionData {
+    serde_json::from_value(modal_data_json(custom_id, inputs)).expect("Invalid fake modal data")
+}
+
+/// Fake modal submission of the user on the channel (outside of a guild)
+pub fn modal_interaction(
+    custom_id: &str,
+    inputs: &[(&str, &str)],
+    user: &User,
+    channel_id: u64,
+) -> ModalInteraction {
+    serde_json::from_value(json!({
+        "id": "1",
+        "application_id": "1",
+        "type": 5,
+        "data": modal_data_json(custom_id, inputs),
+        "channel_id": channel_id.to_string(),
+        "user": user,
+        "token": "token",
+        "version": 1,
+        "locale": "en-US",
+        "entitlements": [],
+        "attachment_size_limit": 8388608,
+    }))
+    .expect("Invalid fake modal interaction")
+}
+
+fn component_interaction(data: Value, user: &User, message: &Message) -> ComponentInteraction {
+    serde_json::from_value(json!({
+        "id": "1",
+        "application_id": "1",
+        "type": 3,
+        "data": data,
+        "channel_id": message.channel_id.to_string(),
+        "user": user,
+        "message": message,
+        "token": "token",
+        "version": 1,
+        "locale": "en-US",
+        "entitlements": [],
+        "attachment_size_limit": 8388608,
+    }))
+    .expect("Invalid fake component interaction")
+}
+
+/// Fake click of the user on a button (with the given custom_id) of the message
+pub fn button_interaction(custom_id: &str, user: &User, message: &Message) -> ComponentInteraction {
+    component_interaction(
+        json!({ "custom_id": custom_id, "component_type": 2 }),
+        user,
+        message,
+    )
+}
+
+/// Fake choice of the user on a string select menu (with the given custom_id) of the message
+pub fn select_interaction(
+    custom_id: &str,
+    values: &[&str],
+    user: &User,
+    message: &Message,
+) -> ComponentInteraction {
+    component_interaction(
+        json!({ "custom_id": custom_id, "component_type": 3, "values": values }),
+        user,
+        message,
+    )
+}
@@ -0,0 +1,286 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use serde_json::{json, Value};
+use serenity::futures::StreamExt;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+/// Prefix of the Discord API routes, removed from the recorded paths
+const API_PREFIX: &str = "/api/v10";
+
+/// Request received by the recording HTTP stand-in
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Route of the request without the API prefix and the query (eg.: `/channels/1/messages`)
+    pub path: String,
+    /// JSON body of the request, `Null` when there is no body
+    pub body: Value,
+}
+
+impl RecordedRequest {
+    /// Whether the request creates or edits a message (channel, webhook or interaction response)
+    pub fn is_message(&self) -> bool {
+        matches!(self.method.as_str(), "POST" | "PATCH")
+            && (self.path.contains("/messages") || self.path.ends_with("/callback"))
+    }
+}
+
+/// Stubbed response of the requests with the given method whose route ends with the given suffix
+#[derive(Debug, Clone)]
+struct Stub {
+    method: String,
+    suffix: String,
+    status: u16,
+    body: Value,
+}
+
+#[derive(Default)]
+struct State {
+    requests: Mutex<Vec<RecordedRequest>>,
+    stubs: Mutex<Vec<Stub>>,
+    next_id: AtomicU64,
+}
+
+impl State {
+    fn next_id(&self) -> String {
+        (self.next_id.fetch_add(1, Ordering::Relaxed) + 1_000).to_string()
+    }
+}
+
+/// Local HTTP server used as the Discord API proxy of the tests, records every request
+///
+/// Requests without a stub are answered with a fake message (message routes), a fake channel (thread routes)
+/// or an empty response
+#[derive(Clone)]
+pub struct RecordingHttp {
+    pub address: SocketAddr,
+    state: Arc<State>,
+}
+
+impl RecordingHttp {
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let state = Arc::new(State::default());
+
+        let server = Self {
+            address: listener.local_addr()?,
+            state: state.clone(),
+        };
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, state.clone()));
+            }
+        });
+
+        Ok(server)
+    }
+
+    /// URL used as the proxy of the serenity HTTP client
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Answer the requests with the given method whose route ends with the suffix (eg.: `/users/@me`)
+    pub fn stub(&self, method: &str, suffix: &str, status: u16, body: Value) {
+        self.state.stubs.lock().unwrap().push(Stub {
+            method: method.to_string(),
+            suffix: suffix.to_string(),
+            status,
+            body,
+        });
+    }
+
+    /// Every request received, in order
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    /// Body of every message sent or edited, in order
+    pub fn messages(&self) -> Vec<Value> {
+        self.requests()
+            .into_iter()
+            .filter(RecordedRequest::is_message)
+            .map(|request| request.body)
+            .collect()
+    }
+
+    /// Forget the received requests (the stubs are kept)
+    pub fn clear(&self) {
+        self.state.requests.lock().unwrap().clear();
+    }
+}
+
+async fn serve(stream: TcpStream, state: Arc<State>) {
+    let mut stream = BufReader::new(stream);
+
+    // the connection is kept alive by the client, so multiple requests are read from it
+    while let Some(request) = read_request(&mut stream).await {
+        let (status, body) = respond(&state, &request);
+        state.requests.lock().unwrap().push(request);
+
+        let body = match body {
+            Value::Null => String::new(),
+            body => body.to_string(),
+        };
+        let response = format!(
+            "HTTP/1.1 {} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+            status,
+            if status < 400 { "OK" } else { "ERROR" },
+            body.len(),
+            body
+        );
+
+        if stream
+            .get_mut()
+            .write_all(response.as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+async fn read_request(stream: &mut BufReader<TcpStream>) -> Option<RecordedRequest> {
+    let mut line = String::new();
+    stream
+        .read_line(&mut line)
+        .await
+        .ok()
+        .filter(|read| *read > 0)?;
+
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let path = target
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches(API_PREFIX)
+        .to_string();
+
+    let mut content_length = 0;
+
+    loop {
+        let mut header = String::new();
+        stream.read_line(&mut header).await.ok()?;
+
+        let header = header.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or_default();
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body).await.ok()?;
+
+    Some(RecordedRequest {
+        method,
+        path,
+        body: serde_json::from_slice(&body).unwrap_or_default(),
+    })
+}
+
+fn respond(state: &State, request: &RecordedRequest) -> (u16, Value) {
+    let stub = state
+        .stubs
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|stub| stub.method == request.method && request.path.ends_with(&stub.suffix))
+        .cloned();
+
+    if let Some(stub) = stub {
+        return (stub.status, stub.body);
+    }
+
+    let segments = request.path.split('/').collect::<Vec<&str>>();
+
+    match request.method.as_str() {
+        "POST" | "PATCH" if request.path.contains("/messages") => {
+            // `/channels/<channel_id>/messages` or `/webhooks/<application_id>/<token>/messages/<id>`
+            let channel_id = match segments.get(1) {
+                Some(&"channels") => segments.get(2).copied().unwrap_or("1"),
+                _ => "1",
+            };
+
+            (
+                200,
+                fake_message(state.next_id(), channel_id, &request.body),
+            )
+        }
+        "POST" if request.path.ends_with("/threads") => {
+            let channel_id = segments.get(2).copied().unwrap_or("1");
+
+            (201, fake_thread(state.next_id(), channel_id, &request.body))
+        }
+        _ => (204, Value::Null),
+    }
+}
+
+fn fake_message(id: String, channel_id: &str, body: &Value) -> Value {
+    json!({
+        "id": id,
+        "channel_id": channel_id,
+        "author": { "id": "1", "username": "bostil", "discriminator": "0000", "avatar": null, "bot": true },
+        "content": body["content"].as_str().unwrap_or_default(),
+        "timestamp": "2024-01-01T00:00:00.000000+00:00",
+        "edited_timestamp": null,
+        "tts": false,
+        "mention_everyone": false,
+        "mentions": [],
+        "mention_roles": [],
+        "attachments": [],
+        "embeds": body["embeds"].as_array().cloned().unwrap_or_default(),
+        "pinned": false,
+        "type": 0,
+    })
+}
+
+fn fake_thread(id: String, parent_id: &str, body: &Value) -> Value {
+    json!({
+        "id": id,
+        "type": body["type"].as_u64().unwrap_or(12),
+        "guild_id": "1",
+        "parent_id": parent_id,
+        "name": body["name"].as_str().unwrap_or_default(),
+        "position": 0,
+        "permission_overwrites": [],
+        "nsfw": false,
+    })
+}
+
+/// Local gateway that accepts the websocket of a shard and keeps it open (no event is ever sent)
+pub(crate) async fn start_gateway() -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                if let Ok(mut websocket) = tokio_tungstenite::accept_async(stream).await {
+                    while let Some(Ok(_)) = websocket.next().await {}
+                }
+            });
+        }
+    });
+
+    Ok(address)
+}
@@ -0,0 +1,277 @@
+//! In-process harness to run commands and listeners in tests, without a connection to Discord
+//!
+//! The harness builds a real serenity `Context` whose HTTP client is proxied to a local recording server,
+//! so the runners are executed as on the bot and every outgoing request can be asserted.
+
+pub mod catalog;
+pub mod fakes;
+mod http;
+
+use std::sync::{Arc, OnceLock};
+
+use serenity::{
+    all::{
+        ApplicationId, ChannelId, CommandDataOption, ComponentInteraction, GatewayIntents, Guild,
+        GuildId, ModalInteraction, Permissions, ShardId, ShardInfo, User,
+    },
+    builder::AutocompleteChoice,
+    cache::Cache,
+    client::Context,
+    gateway::{
+        Shard, ShardManager, ShardManagerOptions, ShardMessenger, ShardRunner, ShardRunnerOptions,
+    },
+    http::HttpBuilder,
+    prelude::{Mutex, RwLock, TypeMap},
+};
+
+pub use http::{RecordedRequest, RecordingHttp};
+
+use crate::{
+    arguments::InvocationContext,
+    collectors::{CommandCollector, ListenerCollector},
+    commands::Command,
+    dispatchers::{ListenerDispatcher, ListenerRouteError},
+    errors::BostilError,
+    listeners::Listener,
+    runners::runners::CommandResult,
+};
+
+const TOKEN: &str = "Bot test";
+
+/// Fake invoker (user, guild and channel) of the commands and listeners with a recording HTTP stand-in
+pub struct TestHarness {
+    pub context: Context,
+    /// Shard manager of the fake shard (eg.: to be inserted on the `data` of the context)
+    pub shard_manager: Arc<ShardManager>,
+    /// Recording HTTP stand-in, the requests made by the runners are sent to it
+    pub http: RecordingHttp,
+    pub user: User,
+    pub guild: Option<Guild>,
+    /// Guild of the invocation that isn't cached, only its id is known
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub member_permissions: Option<Permissions>,
+}
+
+impl TestHarness {
+    /// Harness invoked by a user on a DM channel
+    pub async fn new() -> Self {
+        let recorder = RecordingHttp::start()
+            .await
+            .expect("Cannot start the recording HTTP stand-in");
+        let gateway = http::start_gateway()
+            .await
+            .expect("Cannot start the fake gateway");
+
+        let http = Arc::new(
+            HttpBuilder::new(TOKEN)
+                .application_id(ApplicationId::new(1))
+                .proxy(recorder.url())
+                .ratelimiter_disabled(true)
+                .build(),
+        );
+        let cache = Arc::new(Cache::new());
+        let data = Arc::new(RwLock::new(TypeMap::new()));
+        let ws_url = Arc::new(Mutex::new(format!("ws://{}", gateway)));
+
+        let (shard_manager, _) = ShardManager::new(ShardManagerOptions {
+            data: data.clone(),
+            event_handlers: vec![],
+            raw_event_handlers: vec![],
+            framework: Arc::new(OnceLock::new()),
+            shard_index: 0,
+            shard_init: 0,
+            shard_total: 1,
+            voice_manager: None,
+            ws_url: ws_url.clone(),
+            cache: cache.clone(),
+            http: http.clone(),
+            intents: GatewayIntents::empty(),
+            presence: None,
+        });
+
+        // the shard is connected to the fake gateway, it's only used to build the messenger of the context
+        let shard = Shard::new(
+            ws_url,
+            TOKEN,
+            ShardInfo {
+                id: ShardId(0),
+                total: 1,
+            },
+            GatewayIntents::empty(),
+            None,
+        )
+        .await
+        .expect("Cannot connect to the fake gateway");
+
+        let runner = ShardRunner::new(ShardRunnerOptions {
+            data: data.clone(),
+            event_handlers: vec![],
+            raw_event_handlers: vec![],
+            framework: None,
+            manager: shard_manager.clone(),
+            shard,
+            voice_manager: None,
+            cache: cache.clone(),
+            http: http.clone(),
+        });
+
+        let context = Context {
+            data,
+            shard: ShardMessenger::new(&runner),
+            shard_id: ShardId(0),
+            http,
+            cache,
+        };
+
+        Self {
+            context,
+            shard_manager,
+            http: recorder,
+            user: fakes::user(1, "tester"),
+            guild: None,
+            guild_id: None,
+            channel_id: ChannelId::new(1),
+            member_permissions: None,
+        }
+    }
+
+    pub fn user(mut self, user: User) -> Self {
+        self.user = user;
+        self
+    }
+
+    /// Invoke inside the guild
+    pub fn guild(mut self, guild: Guild) -> Self {
+        self.guild = Some(guild);
+        self
+    }
+
+    /// Invoke inside a guild that isn't cached (eg.: an interaction of a guild the cache doesn't have yet)
+    pub fn uncached_guild(mut self, guild_id: u64) -> Self {
+        self.guild_id = Some(GuildId::new(guild_id));
+        self
+    }
+
+    pub fn channel(mut self, channel_id: u64) -> Self {
+        self.channel_id = ChannelId::new(channel_id);
+        self
+    }
+
+    pub fn member_permissions(mut self, permissions: Permissions) -> Self {
+        self.member_permissions = Some(permissions);
+        self
+    }
+
+    /// Invocation of the fake invoker, to be completed with the data of the event (eg.: a message)
+    pub fn invocation(&self) -> InvocationContext {
+        let invocation = InvocationContext::new(
+            &self.context,
+            self.guild.clone(),
+            &self.user,
+            &self.channel_id,
+        )
+        .guild_id(self.guild_id);
+
+        match self.member_permissions {
+            Some(permissions) => invocation.member_permissions(Some(permissions)),
+            None => invocation,
+        }
+    }
+
+    /// Run a command as a slash command interaction, through the built-in middlewares of a collector
+    pub async fn run_command(
+        &self,
+        command: &Command,
+        options: Vec<CommandDataOption>,
+    ) -> CommandResult<'static> {
+        let mut collector = CommandCollector::new();
+        collector.store_command(command.clone());
+
+        self.run_collected_command(&collector, &command.name, options)
+            .await
+    }
+
+    /// Run a command of the collector as a slash command interaction, through the middlewares of the collector
+    pub async fn run_collected_command(
+        &self,
+        collector: &CommandCollector,
+        name: &str,
+        options: Vec<CommandDataOption>,
+    ) -> CommandResult<'static> {
+        let command = collector
+            .commands
+            .iter()
+            .find(|command| command.name == name)
+            .ok_or_else(|| BostilError::validation(format!("Command {} not found", name)))?;
+
+        let resolved_command = command.resolve(&options)?;
+        let invocation = self.invocation().options(resolved_command.options.clone());
+
+        collector.run(&resolved_command, &invocation).await
+    }
+
+    /// Suggestions of the autocomplete provider of the focused option (see `fakes::focused_option`)
+    pub async fn suggest(
+        &self,
+        command: &Command,
+        options: Vec<CommandDataOption>,
+    ) -> Option<Vec<AutocompleteChoice>> {
+        let resolved_command = command.resolve(&options).ok()?;
+        let invocation = self.invocation().options(resolved_command.options.clone());
+
+        resolved_command.suggest(&invocation).await
+    }
+
+    /// Dispatch an event to the listener, the invocation is built with `TestHarness::invocation`
+    pub async fn dispatch(&self, listener: &Listener, invocation: &InvocationContext) {
+        self.dispatcher(listener)
+            .dispatch(listener.kind, invocation)
+            .await
+    }
+
+    /// Dispatch a chat message of the fake invoker to a `Message` listener
+    pub async fn send_message(&self, listener: &Listener, content: &str) {
+        let message = fakes::message(1, self.channel_id.get(), &self.user, content);
+
+        self.dispatch(listener, &self.invocation().message(message))
+            .await
+    }
+
+    /// Route a modal submission to the `Modal` listener (the submission is acknowledged as on the bot)
+    pub async fn submit_modal(
+        &self,
+        listener: &Listener,
+        submit: &ModalInteraction,
+    ) -> Result<(), ListenerRouteError> {
+        self.dispatcher(listener).modal(&self.context, submit).await
+    }
+
+    /// Route a component interaction (button, select menu) to the `Component` listeners of the collector
+    pub async fn interact_collected(
+        &self,
+        collector: &ListenerCollector,
+        component: &ComponentInteraction,
+    ) -> Result<(), ListenerRouteError> {
+        ListenerDispatcher::new(collector.clone())
+            .component(&self.context, component)
+            .await
+    }
+
+    /// Every request sent to Discord, in order
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.http.requests()
+    }
+
+    /// Body of every message sent or edited on Discord, in order
+    pub fn sent_messages(&self) -> Vec<serde_json::Value> {
+        self.http.messages()
+    }
+
+    fn dispatcher(&self, listener: &Listener) -> ListenerDispatcher {
+        let mut collector = ListenerCollector::new();
+        collector.store_listener(listener.clone());
+
+        ListenerDispatcher::new(collector)
+    }
+}
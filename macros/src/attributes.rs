@@ -0,0 +1,101 @@
+use proc_macro2::Span;
+use syn::{
+    parse::Parser, punctuated::Punctuated, Error, Expr, ExprLit, Lit, MetaNameValue, Path, Result,
+    Token,
+};
+
+/// Attributes `key = value` given to a `#[command]` or `#[listener]` macro
+pub struct Attributes {
+    values: Vec<MetaNameValue>,
+}
+
+impl Attributes {
+    pub fn parse(tokens: proc_macro2::TokenStream) -> Result<Self> {
+        let values = Punctuated::<MetaNameValue, Token![,]>::parse_terminated.parse2(tokens)?;
+
+        Ok(Self {
+            values: values.into_iter().collect(),
+        })
+    }
+
+    /// Fail when an attribute is not one of the known keys
+    pub fn check_keys(&self, known: &[&str]) -> Result<()> {
+        for value in &self.values {
+            if !known.iter().any(|key| value.path.is_ident(key)) {
+                return Err(Error::new_spanned(
+                    &value.path,
+                    format!("unknown attribute, expected one of: {}", known.join(", ")),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Option<&Expr> {
+        self.values
+            .iter()
+            .find(|value| value.path.is_ident(key))
+            .map(|value| &value.value)
+    }
+
+    pub fn string(&self, key: &str) -> Result<Option<String>> {
+        match self.get(key) {
+            Some(Expr::Lit(ExprLit {
+                lit: Lit::Str(value),
+                ..
+            })) => Ok(Some(value.value())),
+            Some(other) => Err(Error::new_spanned(
+                other,
+                format!("`{}` must be a string", key),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    pub fn required_string(&self, key: &str) -> Result<String> {
+        self.string(key)?
+            .ok_or_else(|| Error::new(Span::call_site(), format!("missing `{}` attribute", key)))
+    }
+
+    pub fn path(&self, key: &str) -> Result<Option<Path>> {
+        match self.get(key) {
+            Some(Expr::Path(value)) => Ok(Some(value.path.clone())),
+            Some(other) => Err(Error::new_spanned(
+                other,
+                format!("`{}` must be a path", key),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    pub fn required_path(&self, key: &str) -> Result<Path> {
+        self.path(key)?
+            .ok_or_else(|| Error::new(Span::call_site(), format!("missing `{}` attribute", key)))
+    }
+}
+
+/// Identifier of the generated static (eg.: `ping` -> `PING_COMMAND`)
+pub fn static_ident(name: &str, suffix: &str) -> syn::Ident {
+    syn::Ident::new(
+        &format!("{}_{}", name.replace('-', "_").to_uppercase(), suffix),
+        Span::call_site(),
+    )
+}
+
+/// Identifier of the generated runner struct (eg.: `poll_option` -> `__PollOptionRunner`)
+pub fn runner_ident(name: &str) -> syn::Ident {
+    let pascal_case = name
+        .split(['_', '-'])
+        .map(|word| {
+            let mut characters = word.chars();
+
+            match characters.next() {
+                Some(first) => first.to_uppercase().chain(characters).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>();
+
+    syn::Ident::new(&format!("__{}Runner", pascal_case), Span::call_site())
+}
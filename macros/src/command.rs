@@ -0,0 +1,119 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ItemFn, Result};
+
+use crate::{
+    attributes::{runner_ident, static_ident, Attributes},
+    runner::RunnerFn,
+};
+
+/// Expand `#[command(...)]` into the runner, the `Command` static and its registration
+pub fn expand(attributes: TokenStream, function: ItemFn) -> Result<TokenStream> {
+    let attributes = Attributes::parse(attributes)?;
+    attributes.check_keys(&[
+        "name",
+        "description",
+        "context",
+        "category",
+        "fingerprint",
+        "autocomplete",
+        "preconditions",
+        "cooldowns",
+    ])?;
+
+    let name = attributes.required_string("name")?;
+    let description = attributes.required_string("description")?;
+    let context = match attributes.path("context")? {
+        Some(context) => quote! { #context },
+        None => quote! { Guild },
+    };
+    let category = match attributes.path("category")? {
+        Some(category) => quote! { #category },
+        None => quote! { General },
+    };
+    let fingerprint = match attributes.path("fingerprint")? {
+        Some(fingerprint) => quote! { #fingerprint() },
+        None => quote! {
+            ::bostil_core::__private::serenity::builder::CreateCommand::new(#name)
+                .description(#description)
+        },
+    };
+
+    let autocompletes = match attributes.path("autocomplete")? {
+        Some(autocomplete) => quote! { #autocomplete() },
+        None => quote! { vec![] },
+    };
+
+    let preconditions = match attributes.path("preconditions")? {
+        Some(preconditions) => quote! { #preconditions() },
+        None => quote! { vec![] },
+    };
+
+    let cooldowns = match attributes.path("cooldowns")? {
+        Some(cooldowns) => quote! { #cooldowns() },
+        None => quote! { vec![] },
+    };
+
+    let runner = RunnerFn::parse(function)?;
+    let function = &runner.function;
+    let function_ident = &function.sig.ident;
+    let visibility = &function.vis;
+    let docs = runner.docs();
+    let argument_types = &runner.argument_types;
+    let argument_idents = &runner.argument_idents;
+    let runner_ident = runner_ident(&name);
+    let static_ident = static_ident(&name, "COMMAND");
+
+    Ok(quote! {
+        #function
+
+        #[doc(hidden)]
+        #[derive(Clone)]
+        struct #runner_ident;
+
+        #[::bostil_core::__private::async_trait]
+        impl ::bostil_core::runners::runners::TypedCommandRunnerFn for #runner_ident {
+            type Arguments = (#(#argument_types,)*);
+
+            async fn run_typed<'a>(
+                &self,
+                (#(#argument_idents,)*): Self::Arguments,
+            ) -> ::bostil_core::runners::runners::CommandResult<'a> {
+                #function_ident(#(#argument_idents),*).await
+            }
+        }
+
+        #(#docs)*
+        #visibility static #static_ident: ::bostil_core::__private::Lazy<::bostil_core::commands::Command> =
+            ::bostil_core::__private::Lazy::new(|| {
+                let autocompletes: Vec<::bostil_core::commands::Autocomplete> = #autocompletes;
+                let preconditions: Vec<::bostil_core::preconditions::Precondition> = #preconditions;
+                let cooldowns: Vec<::bostil_core::cooldowns::Cooldown> = #cooldowns;
+
+                let command = autocompletes.into_iter().fold(
+                    ::bostil_core::commands::Command::new(
+                        #name,
+                        #description,
+                        ::bostil_core::commands::CommandContext::#context,
+                        ::bostil_core::commands::CommandCategory::#category,
+                        vec![],
+                        Box::new(#runner_ident),
+                        Some(#fingerprint),
+                    ),
+                    ::bostil_core::commands::Command::add_autocomplete,
+                );
+
+                let command = preconditions.into_iter().fold(
+                    command,
+                    ::bostil_core::commands::Command::add_precondition,
+                );
+
+                cooldowns.into_iter().fold(
+                    command,
+                    ::bostil_core::commands::Command::add_cooldown,
+                )
+            });
+
+        ::bostil_core::register_command!(#static_ident);
+    })
+}
@@ -0,0 +1,63 @@
+//! Procedural macros to declare commands and listeners of the Bostil bot
+//!
+//! Both macros are applied on an async function whose parameters are the typed arguments of the
+//! runner (see `bostil_core::arguments::FromArguments`), and generate:
+//! - a runner implementing the typed runner trait;
+//! - a static `<NAME>_COMMAND` / `<NAME>_LISTENER` holding the `Command` / `Listener`;
+//! - the registration of the static, collected by `CommandCollector::store_registered` / `ListenerCollector::store_registered`.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, ItemFn};
+
+mod attributes;
+mod command;
+mod listener;
+mod runner;
+
+/// Declare an application command
+///
+/// Attributes:
+/// - `name`: name of the command (required)
+/// - `description`: description of the command (required)
+/// - `context`: variant of `CommandContext` (default: `Guild`)
+/// - `category`: variant of `CommandCategory` (default: `General`)
+/// - `fingerprint`: function returning the `CreateCommand` (default: built from name and description)
+/// - `autocomplete`: function returning the `Vec<Autocomplete>` of the command options (default: none)
+/// - `preconditions`: function returning the `Vec<Precondition>` checked before running (default: none)
+/// - `cooldowns`: function returning the `Vec<Cooldown>` of the command (default: none)
+///
+/// ```ignore
+/// #[command(name = "ping", description = "Check if the bot is alive", context = Global)]
+/// async fn ping(context: Context) -> CommandResult<'static> { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn command(attributes: TokenStream, item: TokenStream) -> TokenStream {
+    let function = parse_macro_input!(item as ItemFn);
+
+    command::expand(attributes.into(), function)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Declare a listener
+///
+/// Attributes:
+/// - `name`: name of the listener (required)
+/// - `description`: description of the listener (required)
+/// - `kind`: variant of `ListenerKind` (required)
+/// - `cooldowns`: function returning the `Vec<Cooldown>` of the listener (default: none)
+///
+/// The function can return `()` or a `Result<(), E>` where `E` converts into `BostilError`
+///
+/// ```ignore
+/// #[listener(name = "love", description = "Send love messages", kind = Message)]
+/// async fn love(ctx: Context, author: User, channel: ChannelId) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn listener(attributes: TokenStream, item: TokenStream) -> TokenStream {
+    let function = parse_macro_input!(item as ItemFn);
+
+    listener::expand(attributes.into(), function)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
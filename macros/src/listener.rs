@@ -0,0 +1,73 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ItemFn, Result};
+
+use crate::{
+    attributes::{runner_ident, static_ident, Attributes},
+    runner::RunnerFn,
+};
+
+/// Expand `#[listener(...)]` into the runner, the `Listener` static and its registration
+pub fn expand(attributes: TokenStream, function: ItemFn) -> Result<TokenStream> {
+    let attributes = Attributes::parse(attributes)?;
+    attributes.check_keys(&["name", "description", "kind", "cooldowns"])?;
+
+    let name = attributes.required_string("name")?;
+    let description = attributes.required_string("description")?;
+    let kind = attributes.required_path("kind")?;
+    let cooldowns = match attributes.path("cooldowns")? {
+        Some(cooldowns) => quote! { #cooldowns() },
+        None => quote! { vec![] },
+    };
+
+    let runner = RunnerFn::parse(function)?;
+    let function = &runner.function;
+    let function_ident = &function.sig.ident;
+    let visibility = &function.vis;
+    let docs = runner.docs();
+    let argument_types = &runner.argument_types;
+    let argument_idents = &runner.argument_idents;
+    let runner_ident = runner_ident(&name);
+    let static_ident = static_ident(&name, "LISTENER");
+
+    Ok(quote! {
+        #function
+
+        #[doc(hidden)]
+        #[derive(Clone)]
+        struct #runner_ident;
+
+        #[::bostil_core::__private::async_trait]
+        impl ::bostil_core::runners::runners::TypedListenerRunnerFn for #runner_ident {
+            type Arguments = (#(#argument_types,)*);
+
+            async fn run_typed<'a>(
+                &self,
+                (#(#argument_idents,)*): Self::Arguments,
+            ) -> ::bostil_core::runners::runners::ListenerResult {
+                ::bostil_core::errors::IntoListenerResult::into_listener_result(
+                    #function_ident(#(#argument_idents),*).await,
+                )
+            }
+        }
+
+        #(#docs)*
+        #visibility static #static_ident: ::bostil_core::__private::Lazy<::bostil_core::listeners::Listener> =
+            ::bostil_core::__private::Lazy::new(|| {
+                let cooldowns: Vec<::bostil_core::cooldowns::Cooldown> = #cooldowns;
+
+                cooldowns.into_iter().fold(
+                    ::bostil_core::listeners::Listener::new(
+                        #name,
+                        #description,
+                        ::bostil_core::listeners::ListenerKind::#kind,
+                        vec![],
+                        Box::new(#runner_ident),
+                    ),
+                    ::bostil_core::listeners::Listener::add_cooldown,
+                )
+            });
+
+        ::bostil_core::register_listener!(#static_ident);
+    })
+}
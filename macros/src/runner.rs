@@ -0,0 +1,53 @@
+use quote::format_ident;
+use syn::{Attribute, Error, FnArg, Ident, ItemFn, Result, Type};
+
+/// Runner function annotated by a macro, its parameters are the typed arguments of the runner
+pub struct RunnerFn {
+    pub function: ItemFn,
+    pub argument_types: Vec<Type>,
+    pub argument_idents: Vec<Ident>,
+}
+
+impl RunnerFn {
+    pub fn parse(function: ItemFn) -> Result<Self> {
+        if function.sig.asyncness.is_none() {
+            return Err(Error::new_spanned(
+                function.sig.fn_token,
+                "runner function must be async",
+            ));
+        }
+
+        let mut argument_types = vec![];
+        let mut argument_idents = vec![];
+
+        for (index, input) in function.sig.inputs.iter().enumerate() {
+            match input {
+                FnArg::Typed(argument) => {
+                    argument_types.push((*argument.ty).clone());
+                    argument_idents.push(format_ident!("__argument_{}", index));
+                }
+                FnArg::Receiver(receiver) => {
+                    return Err(Error::new_spanned(
+                        receiver,
+                        "runner function cannot take `self`",
+                    ));
+                }
+            }
+        }
+
+        Ok(Self {
+            function,
+            argument_types,
+            argument_idents,
+        })
+    }
+
+    /// Documentation of the runner function, forwarded to the generated static
+    pub fn docs(&self) -> Vec<&Attribute> {
+        self.function
+            .attrs
+            .iter()
+            .filter(|attribute| attribute.path().is_ident("doc"))
+            .collect()
+    }
+}